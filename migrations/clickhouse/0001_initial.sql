@@ -0,0 +1,208 @@
+-- Базовая схема ClickHouse. Таблицы, созданные вручную до появления миграций,
+-- не пересоздаются: IF NOT EXISTS оставляет их как есть
+
+-- Минутные свечи (бэкенд хранения clickhouse)
+CREATE TABLE IF NOT EXISTS tinkoff_candles_1min
+(
+    instrument_uid String,
+    time DateTime('UTC'),
+    open_units Int64,
+    open_nano Int32,
+    high_units Int64,
+    high_nano Int32,
+    low_units Int64,
+    low_nano Int32,
+    close_units Int64,
+    close_nano Int32,
+    volume Int64
+)
+ENGINE = ReplacingMergeTree
+PARTITION BY toYYYYMM(time)
+ORDER BY (instrument_uid, time);
+
+-- Свечи, не прошедшие проверку; отсутствующие цены хранятся как NULL
+CREATE TABLE IF NOT EXISTS tinkoff_candles_quarantine
+(
+    instrument_uid String,
+    time Int64,
+    open_units Nullable(Int64),
+    open_nano Nullable(Int32),
+    high_units Nullable(Int64),
+    high_nano Nullable(Int32),
+    low_units Nullable(Int64),
+    low_nano Nullable(Int32),
+    close_units Nullable(Int64),
+    close_nano Nullable(Int32),
+    volume Int64,
+    reason String,
+    detected_at Int64
+)
+ENGINE = MergeTree
+ORDER BY (instrument_uid, time);
+
+-- Актуальный срез каталога акций, читается через FINAL
+CREATE TABLE IF NOT EXISTS tinkoff_shares
+(
+    figi String,
+    ticker String,
+    class_code String,
+    isin String,
+    lot Int32,
+    currency String,
+    klong_units Nullable(Int64),
+    klong_nano Nullable(Int32),
+    kshort_units Nullable(Int64),
+    kshort_nano Nullable(Int32),
+    dlong_units Nullable(Int64),
+    dlong_nano Nullable(Int32),
+    dshort_units Nullable(Int64),
+    dshort_nano Nullable(Int32),
+    dlong_min_units Nullable(Int64),
+    dlong_min_nano Nullable(Int32),
+    dshort_min_units Nullable(Int64),
+    dshort_min_nano Nullable(Int32),
+    short_enabled_flag UInt8,
+    name String,
+    exchange String,
+    ipo_date Nullable(Int64),
+    issue_size Int64,
+    country_of_risk String,
+    country_of_risk_name String,
+    sector String,
+    issue_size_plan Int64,
+    nominal_currency Nullable(String),
+    nominal_units Nullable(Int64),
+    nominal_nano Nullable(Int32),
+    trading_status Int32,
+    otc_flag UInt8,
+    buy_available_flag UInt8,
+    sell_available_flag UInt8,
+    div_yield_flag UInt8,
+    share_type Int32,
+    min_price_increment_units Nullable(Int64),
+    min_price_increment_nano Nullable(Int32),
+    api_trade_available_flag UInt8,
+    uid String,
+    real_exchange Int32,
+    position_uid String,
+    for_iis_flag UInt8,
+    for_qual_investor_flag UInt8,
+    weekend_flag UInt8,
+    blocked_tca_flag UInt8,
+    liquidity_flag UInt8,
+    first_1min_candle_date Nullable(Int64),
+    first_1day_candle_date Nullable(Int64),
+    updated_at DateTime64(3) DEFAULT now64(3)
+)
+ENGINE = ReplacingMergeTree(updated_at)
+ORDER BY uid;
+
+-- История каталога акций (SCD type 2), читается через FINAL
+CREATE TABLE IF NOT EXISTS tinkoff_shares_history
+(
+    uid String,
+    figi String,
+    ticker String,
+    class_code String,
+    name String,
+    exchange String,
+    currency String,
+    lot Int32,
+    trading_status String,
+    short_enabled_flag Bool,
+    otc_flag Bool,
+    buy_available_flag Bool,
+    sell_available_flag Bool,
+    div_yield_flag Bool,
+    api_trade_available_flag Bool,
+    for_iis_flag Bool,
+    for_qual_investor_flag Bool,
+    weekend_flag Bool,
+    blocked_tca_flag Bool,
+    liquidity_flag Bool,
+    min_price_increment_units Int64,
+    min_price_increment_nano Int32,
+    valid_from Int64,
+    valid_to Nullable(Int64),
+    updated_at DateTime64(3) DEFAULT now64(3)
+)
+ENGINE = ReplacingMergeTree(updated_at)
+ORDER BY (uid, valid_from);
+
+-- Инструменты вселенной; последняя запись по uid побеждает
+CREATE TABLE IF NOT EXISTS liquid_shares
+(
+    uid String,
+    is_liquid_now Bool,
+    updated_at DateTime64(3) DEFAULT now64(3)
+)
+ENGINE = ReplacingMergeTree(updated_at)
+ORDER BY uid;
+
+-- Прежний список загрузки свечей; читается один раз для переноса в PostgreSQL
+CREATE TABLE IF NOT EXISTS instrument_candle_info
+(
+    uid String,
+    first_1min_candle_date Int64,
+    last_1min_candle_date Int64,
+    is_active Bool DEFAULT true,
+    updated_at DateTime64(3) DEFAULT now64(3)
+)
+ENGINE = ReplacingMergeTree(updated_at)
+ORDER BY uid;
+
+-- Журнал изменений списка загрузки
+CREATE TABLE IF NOT EXISTS universe_changes
+(
+    change_time Int64,
+    uid String,
+    ticker String,
+    action String
+)
+ENGINE = MergeTree
+ORDER BY (change_time, uid);
+
+-- Листинги, делистинги и смены статусов инструментов
+CREATE TABLE IF NOT EXISTS instrument_events
+(
+    event_time Int64,
+    event_type String,
+    uid String,
+    figi String,
+    ticker String,
+    old_value String,
+    new_value String
+)
+ENGINE = MergeTree
+ORDER BY (event_time, uid);
+
+-- Исключённые инструменты; удаление записывается строкой с is_deleted = 1
+CREATE TABLE IF NOT EXISTS instrument_exclusions
+(
+    key_type String,
+    key_value String,
+    reason String,
+    expires_at Nullable(Int64),
+    is_deleted UInt8,
+    updated_at DateTime64(3)
+)
+ENGINE = ReplacingMergeTree(updated_at)
+ORDER BY (key_type, key_value);
+
+-- Срезы стаканов, уровни хранятся параллельными массивами
+CREATE TABLE IF NOT EXISTS tinkoff_orderbooks
+(
+    instrument_uid String,
+    time DateTime64(3, 'UTC'),
+    depth Int32,
+    is_consistent Bool,
+    bids_price_units Array(Int64),
+    bids_price_nano Array(Int32),
+    bids_quantity Array(Int64),
+    asks_price_units Array(Int64),
+    asks_price_nano Array(Int32),
+    asks_quantity Array(Int64)
+)
+ENGINE = MergeTree
+PARTITION BY toYYYYMM(time)
+ORDER BY (instrument_uid, time);
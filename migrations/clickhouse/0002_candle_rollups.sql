@@ -0,0 +1,33 @@
+-- Свечи крупных разрешений, свёрнутые из минутных. Состояния агрегатов позволяют
+-- дописывать в ту же свечу по частям; читать через argMinMerge/maxMerge/... с GROUP BY.
+-- Время в состояниях open/close хранится как Int64, чтобы не зависеть от типа time в 1min
+
+CREATE TABLE IF NOT EXISTS tinkoff_candles_1hour
+(
+    instrument_uid String,
+    time DateTime('UTC'),
+    open AggregateFunction(argMin, Tuple(Int64, Int32), Int64),
+    high AggregateFunction(max, Tuple(Int64, Int32)),
+    low AggregateFunction(min, Tuple(Int64, Int32)),
+    close AggregateFunction(argMax, Tuple(Int64, Int32), Int64),
+    volume SimpleAggregateFunction(sum, Int64),
+    candles SimpleAggregateFunction(sum, UInt64)
+)
+ENGINE = AggregatingMergeTree
+PARTITION BY toYear(time)
+ORDER BY (instrument_uid, time);
+
+CREATE TABLE IF NOT EXISTS tinkoff_candles_1day
+(
+    instrument_uid String,
+    time DateTime('UTC'),
+    open AggregateFunction(argMin, Tuple(Int64, Int32), Int64),
+    high AggregateFunction(max, Tuple(Int64, Int32)),
+    low AggregateFunction(min, Tuple(Int64, Int32)),
+    close AggregateFunction(argMax, Tuple(Int64, Int32), Int64),
+    volume SimpleAggregateFunction(sum, Int64),
+    candles SimpleAggregateFunction(sum, UInt64)
+)
+ENGINE = AggregatingMergeTree
+PARTITION BY toYear(time)
+ORDER BY (instrument_uid, time);
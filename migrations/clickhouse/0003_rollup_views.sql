@@ -0,0 +1,93 @@
+-- Свечи 5min, 1hour и 1day обновляются материализованными представлениями при каждой
+-- вставке в tinkoff_candles_1min. История до появления представлений заполняется
+-- командой t-candles rollup-backfill. Границы свечей считаются в UTC
+
+CREATE TABLE IF NOT EXISTS tinkoff_candles_5min
+(
+    instrument_uid String,
+    time DateTime('UTC'),
+    open AggregateFunction(argMin, Tuple(Int64, Int32), Int64),
+    high AggregateFunction(max, Tuple(Int64, Int32)),
+    low AggregateFunction(min, Tuple(Int64, Int32)),
+    close AggregateFunction(argMax, Tuple(Int64, Int32), Int64),
+    volume SimpleAggregateFunction(sum, Int64),
+    candles SimpleAggregateFunction(sum, UInt64)
+)
+ENGINE = AggregatingMergeTree
+PARTITION BY toYYYYMM(time)
+ORDER BY (instrument_uid, time);
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS tinkoff_candles_5min_mv TO tinkoff_candles_5min AS
+SELECT
+    uid AS instrument_uid,
+    bucket AS time,
+    argMinState(o, ts) AS open,
+    maxState(h) AS high,
+    minState(l) AS low,
+    argMaxState(c, ts) AS close,
+    sum(v) AS volume,
+    count() AS candles
+FROM
+(
+    SELECT
+        instrument_uid AS uid,
+        toStartOfFiveMinutes(time, 'UTC') AS bucket,
+        toInt64(time) AS ts,
+        (open_units, open_nano) AS o,
+        (high_units, high_nano) AS h,
+        (low_units, low_nano) AS l,
+        (close_units, close_nano) AS c,
+        volume AS v
+    FROM tinkoff_candles_1min
+)
+GROUP BY uid, bucket;
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS tinkoff_candles_1hour_mv TO tinkoff_candles_1hour AS
+SELECT
+    uid AS instrument_uid,
+    bucket AS time,
+    argMinState(o, ts) AS open,
+    maxState(h) AS high,
+    minState(l) AS low,
+    argMaxState(c, ts) AS close,
+    sum(v) AS volume,
+    count() AS candles
+FROM
+(
+    SELECT
+        instrument_uid AS uid,
+        toStartOfHour(time, 'UTC') AS bucket,
+        toInt64(time) AS ts,
+        (open_units, open_nano) AS o,
+        (high_units, high_nano) AS h,
+        (low_units, low_nano) AS l,
+        (close_units, close_nano) AS c,
+        volume AS v
+    FROM tinkoff_candles_1min
+)
+GROUP BY uid, bucket;
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS tinkoff_candles_1day_mv TO tinkoff_candles_1day AS
+SELECT
+    uid AS instrument_uid,
+    bucket AS time,
+    argMinState(o, ts) AS open,
+    maxState(h) AS high,
+    minState(l) AS low,
+    argMaxState(c, ts) AS close,
+    sum(v) AS volume,
+    count() AS candles
+FROM
+(
+    SELECT
+        instrument_uid AS uid,
+        toStartOfDay(time, 'UTC') AS bucket,
+        toInt64(time) AS ts,
+        (open_units, open_nano) AS o,
+        (high_units, high_nano) AS h,
+        (low_units, low_nano) AS l,
+        (close_units, close_nano) AS c,
+        volume AS v
+    FROM tinkoff_candles_1min
+)
+GROUP BY uid, bucket;
@@ -0,0 +1,25 @@
+-- Расхождения минутной истории с официальными дневными свечами Tinkoff.
+-- Одна строка на инструмент и сутки (UTC) в каждом запуске сверки
+
+CREATE TABLE IF NOT EXISTS candle_reconciliation
+(
+    run_id String,
+    instrument_uid String,
+    day DateTime('UTC'),
+    fields String,
+    official_open Nullable(Float64),
+    official_high Nullable(Float64),
+    official_low Nullable(Float64),
+    official_close Nullable(Float64),
+    official_volume Nullable(Int64),
+    stored_open Nullable(Float64),
+    stored_high Nullable(Float64),
+    stored_low Nullable(Float64),
+    stored_close Nullable(Float64),
+    stored_volume Nullable(Int64),
+    refetch_queued UInt8,
+    detected_at DateTime('UTC')
+)
+ENGINE = MergeTree
+PARTITION BY toYYYYMM(day)
+ORDER BY (day, instrument_uid)
@@ -0,0 +1,26 @@
+-- Расхождения сохранённых свечей Tinkoff со свечами MOEX ISS.
+-- Одна строка на инструмент и свечу в каждом запуске сравнения
+
+CREATE TABLE IF NOT EXISTS source_divergences
+(
+    run_id String,
+    instrument_uid String,
+    ticker String,
+    resolution LowCardinality(String),
+    time DateTime('UTC'),
+    fields String,
+    tinkoff_open Nullable(Float64),
+    tinkoff_high Nullable(Float64),
+    tinkoff_low Nullable(Float64),
+    tinkoff_close Nullable(Float64),
+    tinkoff_volume Nullable(Int64),
+    iss_open Nullable(Float64),
+    iss_high Nullable(Float64),
+    iss_low Nullable(Float64),
+    iss_close Nullable(Float64),
+    iss_volume Nullable(Int64),
+    detected_at DateTime('UTC')
+)
+ENGINE = MergeTree
+PARTITION BY toYYYYMM(time)
+ORDER BY (time, instrument_uid)
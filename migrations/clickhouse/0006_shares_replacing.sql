@@ -0,0 +1,106 @@
+-- tinkoff_shares читается через FINAL и должна быть ReplacingMergeTree, но в базах,
+-- созданных до миграций, она осталась с другим движком: CREATE TABLE IF NOT EXISTS
+-- в 0001 её не поменял. Таблица собирается заново рядом, получает последнюю версию
+-- каждого uid и подменяет старую через EXCHANGE TABLES, так что читатели не видят
+-- момента без таблицы. Прежняя таблица остаётся как tinkoff_shares_legacy.
+-- Запросы можно выполнить повторно, если миграция прервалась
+
+ALTER TABLE tinkoff_shares ADD COLUMN IF NOT EXISTS updated_at DateTime64(3) DEFAULT now64(3);
+
+DROP TABLE IF EXISTS tinkoff_shares_rebuild;
+
+CREATE TABLE tinkoff_shares_rebuild
+(
+    figi String,
+    ticker String,
+    class_code String,
+    isin String,
+    lot Int32,
+    currency String,
+    klong_units Nullable(Int64),
+    klong_nano Nullable(Int32),
+    kshort_units Nullable(Int64),
+    kshort_nano Nullable(Int32),
+    dlong_units Nullable(Int64),
+    dlong_nano Nullable(Int32),
+    dshort_units Nullable(Int64),
+    dshort_nano Nullable(Int32),
+    dlong_min_units Nullable(Int64),
+    dlong_min_nano Nullable(Int32),
+    dshort_min_units Nullable(Int64),
+    dshort_min_nano Nullable(Int32),
+    short_enabled_flag UInt8,
+    name String,
+    exchange String,
+    ipo_date Nullable(Int64),
+    issue_size Int64,
+    country_of_risk String,
+    country_of_risk_name String,
+    sector String,
+    issue_size_plan Int64,
+    nominal_currency Nullable(String),
+    nominal_units Nullable(Int64),
+    nominal_nano Nullable(Int32),
+    trading_status Int32,
+    otc_flag UInt8,
+    buy_available_flag UInt8,
+    sell_available_flag UInt8,
+    div_yield_flag UInt8,
+    share_type Int32,
+    min_price_increment_units Nullable(Int64),
+    min_price_increment_nano Nullable(Int32),
+    api_trade_available_flag UInt8,
+    uid String,
+    real_exchange Int32,
+    position_uid String,
+    for_iis_flag UInt8,
+    for_qual_investor_flag UInt8,
+    weekend_flag UInt8,
+    blocked_tca_flag UInt8,
+    liquidity_flag UInt8,
+    first_1min_candle_date Nullable(Int64),
+    first_1day_candle_date Nullable(Int64),
+    updated_at DateTime64(3) DEFAULT now64(3)
+)
+ENGINE = ReplacingMergeTree(updated_at)
+ORDER BY uid;
+
+INSERT INTO tinkoff_shares_rebuild
+(
+    figi, ticker, class_code, isin, lot, currency, klong_units,
+    klong_nano, kshort_units, kshort_nano, dlong_units, dlong_nano,
+    dshort_units, dshort_nano, dlong_min_units, dlong_min_nano,
+    dshort_min_units, dshort_min_nano, short_enabled_flag, name,
+    exchange, ipo_date, issue_size, country_of_risk,
+    country_of_risk_name, sector, issue_size_plan, nominal_currency,
+    nominal_units, nominal_nano, trading_status, otc_flag,
+    buy_available_flag, sell_available_flag, div_yield_flag, share_type,
+    min_price_increment_units, min_price_increment_nano,
+    api_trade_available_flag, uid, real_exchange, position_uid,
+    for_iis_flag, for_qual_investor_flag, weekend_flag, blocked_tca_flag,
+    liquidity_flag, first_1min_candle_date, first_1day_candle_date,
+    updated_at
+)
+SELECT
+    figi, ticker, class_code, isin, lot, currency, klong_units,
+    klong_nano, kshort_units, kshort_nano, dlong_units, dlong_nano,
+    dshort_units, dshort_nano, dlong_min_units, dlong_min_nano,
+    dshort_min_units, dshort_min_nano, short_enabled_flag, name,
+    exchange, ipo_date, issue_size, country_of_risk,
+    country_of_risk_name, sector, issue_size_plan, nominal_currency,
+    nominal_units, nominal_nano, trading_status, otc_flag,
+    buy_available_flag, sell_available_flag, div_yield_flag, share_type,
+    min_price_increment_units, min_price_increment_nano,
+    api_trade_available_flag, uid, real_exchange, position_uid,
+    for_iis_flag, for_qual_investor_flag, weekend_flag, blocked_tca_flag,
+    liquidity_flag, first_1min_candle_date, first_1day_candle_date,
+    updated_at
+FROM tinkoff_shares
+ORDER BY updated_at DESC
+LIMIT 1 BY uid;
+
+EXCHANGE TABLES tinkoff_shares AND tinkoff_shares_rebuild;
+
+DROP TABLE IF EXISTS tinkoff_shares_legacy;
+
+RENAME TABLE tinkoff_shares_rebuild TO tinkoff_shares_legacy;
@@ -0,0 +1,62 @@
+-- Операционные метаданные: изменяемое состояние, которое плохо ложится на ClickHouse
+
+-- Список загрузки свечей (раньше instrument_candle_info в ClickHouse)
+CREATE TABLE IF NOT EXISTS watchlist (
+    instrument_uid          TEXT        PRIMARY KEY,
+    first_1min_candle_date  BIGINT      NOT NULL,   -- С какой даты грузить историю, секунды
+    is_active               BOOLEAN     NOT NULL DEFAULT TRUE,
+    added_at                TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at              TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Контрольная точка загрузки: время последней сохранённой минутной свечи
+CREATE TABLE IF NOT EXISTS instrument_checkpoints (
+    instrument_uid          TEXT        PRIMARY KEY REFERENCES watchlist (instrument_uid) ON DELETE CASCADE,
+    last_1min_candle_date   BIGINT      NOT NULL,
+    updated_at              TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Запуски задач планировщиков
+CREATE TABLE IF NOT EXISTS job_runs (
+    run_id              TEXT        PRIMARY KEY,
+    job                 TEXT        NOT NULL,
+    started_at          BIGINT      NOT NULL,
+    finished_at         BIGINT,
+    status              TEXT        NOT NULL,   -- RUNNING, SUCCESS, FAILED или CANCELLED
+    instruments_total   BIGINT      NOT NULL DEFAULT 0,
+    instruments_failed  BIGINT      NOT NULL DEFAULT 0,
+    candles_received    BIGINT      NOT NULL DEFAULT 0,
+    candles_inserted    BIGINT      NOT NULL DEFAULT 0,
+    error               TEXT        NOT NULL DEFAULT '',
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS job_runs_job_started_at_idx ON job_runs (job, started_at DESC);
+
+-- Результат загрузки каждого инструмента в рамках запуска
+CREATE TABLE IF NOT EXISTS job_run_instruments (
+    run_id              TEXT        NOT NULL REFERENCES job_runs (run_id) ON DELETE CASCADE,
+    instrument_uid      TEXT        NOT NULL,
+    ticker              TEXT        NOT NULL DEFAULT '',
+    started_at          BIGINT      NOT NULL,
+    finished_at         BIGINT      NOT NULL,
+    range_from          BIGINT      NOT NULL,
+    range_to            BIGINT      NOT NULL,
+    candles_received    BIGINT      NOT NULL DEFAULT 0,
+    candles_inserted    BIGINT      NOT NULL DEFAULT 0,
+    candles_quarantined BIGINT      NOT NULL DEFAULT 0,
+    error               TEXT        NOT NULL DEFAULT '',
+    PRIMARY KEY (run_id, instrument_uid)
+);
+
+CREATE INDEX IF NOT EXISTS job_run_instruments_started_at_idx ON job_run_instruments (started_at);
+
+-- Ключи доступа к изменяющим методам API; хранится только SHA-256 ключа
+CREATE TABLE IF NOT EXISTS api_keys (
+    id              BIGSERIAL   PRIMARY KEY,
+    name            TEXT        NOT NULL UNIQUE,
+    key_hash        TEXT        NOT NULL UNIQUE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_used_at    TIMESTAMPTZ,
+    revoked_at      TIMESTAMPTZ
+);
@@ -0,0 +1,29 @@
+-- Минутные свечи для бэкенда хранения postgres ([storage] backend = "postgres")
+CREATE TABLE IF NOT EXISTS candles_1min (
+    instrument_uid  TEXT    NOT NULL,
+    time            BIGINT  NOT NULL,   -- Начало свечи, секунды
+    open_units      BIGINT  NOT NULL,
+    open_nano       INTEGER NOT NULL,
+    high_units      BIGINT  NOT NULL,
+    high_nano       INTEGER NOT NULL,
+    low_units       BIGINT  NOT NULL,
+    low_nano        INTEGER NOT NULL,
+    close_units     BIGINT  NOT NULL,
+    close_nano      INTEGER NOT NULL,
+    volume          BIGINT  NOT NULL,
+    PRIMARY KEY (instrument_uid, time)
+);
+
+-- С расширением TimescaleDB таблица становится гипертаблицей с недельными чанками
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb') THEN
+        PERFORM create_hypertable(
+            'candles_1min', 'time',
+            chunk_time_interval => 604800,
+            if_not_exists => TRUE,
+            migrate_data => TRUE
+        );
+    END IF;
+END
+$$;
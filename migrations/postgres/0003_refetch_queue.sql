@@ -0,0 +1,12 @@
+-- Сутки, которые загрузчик свечей должен скачать заново, например после расхождения
+-- со сверкой по дневным свечам. Выполненная заявка хранит время выполнения
+CREATE TABLE IF NOT EXISTS refetch_queue (
+    instrument_uid  TEXT        NOT NULL,
+    day             BIGINT      NOT NULL,   -- Начало суток UTC, секунды
+    reason          TEXT        NOT NULL,
+    queued_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    done_at         TIMESTAMPTZ,
+    PRIMARY KEY (instrument_uid, day)
+);
+
+CREATE INDEX IF NOT EXISTS refetch_queue_pending_idx ON refetch_queue (queued_at) WHERE done_at IS NULL;
@@ -0,0 +1,11 @@
+-- Открытые алерты о свежести свечей: строка живёт, пока инструмент нарушает SLA.
+-- По ним алерты не повторяются чаще repeat_after_minutes, а при восстановлении
+-- отправляется уведомление и строка удаляется
+CREATE TABLE IF NOT EXISTS freshness_alerts (
+    instrument_uid      TEXT    PRIMARY KEY,
+    sla                 TEXT    NOT NULL,
+    opened_at           BIGINT  NOT NULL,
+    last_notified_at    BIGINT  NOT NULL,
+    last_candle_time    BIGINT  NOT NULL,   -- 0 — свечей нет
+    lag_trading_days    BIGINT  NOT NULL
+);
@@ -0,0 +1,4 @@
+-- Откуда инструмент попал в список загрузки: manual — через API, universe — синхронизацией
+-- со вселенной. Синхронизация выключает только свои инструменты; строки, добавленные
+-- до появления колонки, считаются ручными
+ALTER TABLE watchlist ADD COLUMN IF NOT EXISTS origin TEXT NOT NULL DEFAULT 'manual';
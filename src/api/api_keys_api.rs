@@ -0,0 +1,95 @@
+use axum::{
+    Json,
+    extract::{Extension, Path},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::require_postgres;
+use crate::{
+    app_state::models::AppState, db::postgres::models::api_key::DbApiKey, services::api_keys,
+};
+
+/// Ключи API без самих ключей: имя, даты создания, использования и отзыва
+pub async fn list_api_keys(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<Json<Vec<DbApiKey>>, StatusCode> {
+    require_postgres(&app_state)?
+        .repository_api_key
+        .list()
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to fetch API keys: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    pub name: String,
+    /// Ключ возвращается только в этом ответе, в базе хранится лишь его хеш
+    pub key: String,
+}
+
+/// Создаёт новый ключ API
+pub async fn create_api_key(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreatedApiKey>), StatusCode> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let key = api_keys::generate_key();
+    require_postgres(&app_state)?
+        .repository_api_key
+        .create(name, &api_keys::hash_key(&key))
+        .await
+        .map_err(|e| match e.as_database_error() {
+            Some(db_error) if db_error.is_unique_violation() => StatusCode::CONFLICT,
+            _ => {
+                error!("Failed to create API key {}: {}", name, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    info!("Created API key {}", name);
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiKey {
+            name: name.to_string(),
+            key,
+        }),
+    ))
+}
+
+/// Отзывает ключ API
+pub async fn revoke_api_key(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let revoked = require_postgres(&app_state)?
+        .repository_api_key
+        .revoke(&name)
+        .await
+        .map_err(|e| {
+            error!("Failed to revoke API key {}: {}", name, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if !revoked {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("Revoked API key {}", name);
+    Ok(StatusCode::NO_CONTENT)
+}
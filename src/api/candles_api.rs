@@ -0,0 +1,98 @@
+use axum::{
+    Json,
+    extract::{Extension, Query},
+    http::StatusCode,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::error;
+
+use super::require_clickhouse;
+use crate::{
+    app_state::models::AppState,
+    db::{
+        clickhouse::models::quarantined_candle::DbQuarantinedCandle,
+        storage::stored_candle::StoredCandle,
+    },
+    env_config::models::retention::CandleResolution,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CandlesQuery {
+    pub instrument_uid: String,
+    pub from: DateTime<Utc>,
+    pub to: Option<DateTime<Utc>>,
+    pub interval: Option<CandleResolution>, // 1min (по умолчанию), 5min, 1hour, 1day
+}
+
+/// Свечи инструмента за период из настроенного хранилища; крупные интервалы
+/// читаются из свёрток
+pub async fn get_candles(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<CandlesQuery>,
+) -> Result<Json<Vec<StoredCandle>>, StatusCode> {
+    let to = query.to.unwrap_or_else(Utc::now).timestamp();
+    let interval = query.interval.unwrap_or(CandleResolution::Minute);
+
+    app_state
+        .candle_repository
+        .get_candles_at(&query.instrument_uid, query.from.timestamp(), to, interval)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!(
+                "Failed to fetch candles for {}: {}",
+                query.instrument_uid, e
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuarantineQuery {
+    pub from: DateTime<Utc>,
+    pub to: Option<DateTime<Utc>>,
+    pub instrument_uid: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuarantineReport {
+    pub total: usize,
+    pub by_reason: BTreeMap<String, usize>,
+    pub candles: Vec<DbQuarantinedCandle>,
+}
+
+/// Отчёт по свечам, отправленным в карантин, за период по времени свечи
+pub async fn get_quarantine_report(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<QuarantineQuery>,
+) -> Result<Json<QuarantineReport>, StatusCode> {
+    let to = query.to.unwrap_or_else(Utc::now).timestamp();
+
+    let candles = require_clickhouse(&app_state)?
+        .repository_candle_quarantine
+        .get_candles(
+            query.from.timestamp(),
+            to,
+            query.instrument_uid.as_deref(),
+            query.reason.as_deref(),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch quarantined candles: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let mut by_reason = BTreeMap::new();
+    for candle in &candles {
+        *by_reason.entry(candle.reason.clone()).or_insert(0) += 1;
+    }
+
+    Ok(Json(QuarantineReport {
+        total: candles.len(),
+        by_reason,
+        candles,
+    }))
+}
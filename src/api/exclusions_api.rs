@@ -0,0 +1,78 @@
+use axum::{
+    Json,
+    extract::{Extension, Path},
+    http::StatusCode,
+};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::{
+    app_state::models::AppState, env_config::models::app_config::ExclusionKeyType,
+    services::exclusions::exclusion_list::Exclusion,
+};
+
+/// Возвращает все исключения: из конфигурации и добавленные через API
+pub async fn list_exclusions(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Json<Vec<Exclusion>> {
+    Json(app_state.exclusions.list().await)
+}
+
+/// Добавляет или обновляет исключение
+pub async fn add_exclusion(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(exclusion): Json<Exclusion>,
+) -> Result<StatusCode, StatusCode> {
+    if exclusion.key_value.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if !app_state.exclusions.is_editable() {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    if app_state
+        .exclusions
+        .is_config_entry(exclusion.key_type, &exclusion.key_value)
+    {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    app_state.exclusions.add(&exclusion).await.map_err(|e| {
+        error!("Failed to add exclusion: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    info!(
+        "Added exclusion {} {}: {}",
+        exclusion.key_type.as_str_name(),
+        exclusion.key_value,
+        exclusion.reason
+    );
+    Ok(StatusCode::CREATED)
+}
+
+/// Удаляет исключение, добавленное через API; исключения из конфигурации не удаляются
+pub async fn delete_exclusion(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path((key_type, key_value)): Path<(String, String)>,
+) -> Result<StatusCode, StatusCode> {
+    let key_type: ExclusionKeyType = key_type.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !app_state.exclusions.is_editable() {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    if app_state.exclusions.is_config_entry(key_type, &key_value) {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    app_state
+        .exclusions
+        .remove(key_type, &key_value)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete exclusion: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("Removed exclusion {} {}", key_type.as_str_name(), key_value);
+    Ok(StatusCode::NO_CONTENT)
+}
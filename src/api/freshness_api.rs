@@ -0,0 +1,21 @@
+use axum::{Json, extract::Extension, http::StatusCode};
+use std::sync::Arc;
+use tracing::error;
+
+use super::require_postgres;
+use crate::{app_state::models::AppState, db::postgres::models::freshness_alert::DbFreshnessAlert};
+
+/// Инструменты, которые сейчас нарушают SLA свежести свечей
+pub async fn get_freshness_alerts(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<Json<Vec<DbFreshnessAlert>>, StatusCode> {
+    require_postgres(&app_state)?
+        .repository_freshness_alert
+        .get_open()
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to fetch freshness alerts: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
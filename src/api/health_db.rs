@@ -1,18 +1,33 @@
 use axum::{extract::Extension, http::StatusCode};
 use std::sync::Arc;
+use tracing::error;
 
 use crate::app_state::models::AppState;
 
 pub async fn health_db(
     Extension(app_state): Extension<Arc<AppState>>,
 ) -> Result<StatusCode, StatusCode> {
-    // Check ClickHouse connection
-    let client = app_state.clickhouse_service.connection.get_client();
-    let clickhouse_ok = client.query("SELECT 1").execute().await.is_ok();
+    // A database that is not connected was optional at startup and is not checked
+    let clickhouse_ok = match &app_state.clickhouse_service {
+        Some(clickhouse) => {
+            let client = clickhouse.connection.get_client();
+            client.query("SELECT 1").execute().await.is_ok()
+        }
+        None => true,
+    };
 
+    // Check PostgreSQL connection
+    let postgres_ok = match &app_state.postgres_service {
+        Some(postgres) => sqlx::query("SELECT 1")
+            .execute(postgres.connection.get_pool())
+            .await
+            .inspect_err(|e| error!("PostgreSQL health check failed: {}", e))
+            .is_ok(),
+        None => true,
+    };
 
-    // Return OK only if the database is healthy
-    if clickhouse_ok  {
+    // Return OK only if every connected database is healthy
+    if clickhouse_ok && postgres_ok {
         Ok(StatusCode::OK)
     } else {
         Err(StatusCode::INTERNAL_SERVER_ERROR)
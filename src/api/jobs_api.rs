@@ -0,0 +1,73 @@
+use axum::{
+    Json,
+    extract::{Extension, Query},
+    http::StatusCode,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+use super::require_postgres;
+use crate::{
+    app_state::models::AppState,
+    db::postgres::models::job_run::{DbJobRun, DbLoadStatus},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct JobRunsQuery {
+    pub from: DateTime<Utc>,
+    pub to: Option<DateTime<Utc>>,
+    /// candles или shares
+    pub job: Option<String>,
+}
+
+/// Запуски задач планировщиков, начавшиеся за период
+pub async fn get_job_runs(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<JobRunsQuery>,
+) -> Result<Json<Vec<DbJobRun>>, StatusCode> {
+    let to = query.to.unwrap_or_else(Utc::now).timestamp();
+
+    require_postgres(&app_state)?
+        .repository_job_run
+        .get_runs(query.from.timestamp(), to, query.job.as_deref())
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to fetch job runs: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobInstrumentsQuery {
+    pub from: DateTime<Utc>,
+    pub to: Option<DateTime<Utc>>,
+    /// uid или тикер инструмента
+    pub instrument: Option<String>,
+    pub run_id: Option<String>,
+}
+
+/// Результаты загрузки по инструментам: диапазон, число свечей и ошибки
+pub async fn get_job_instruments(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<JobInstrumentsQuery>,
+) -> Result<Json<Vec<DbLoadStatus>>, StatusCode> {
+    let to = query.to.unwrap_or_else(Utc::now).timestamp();
+
+    require_postgres(&app_state)?
+        .repository_job_run
+        .get_load_statuses(
+            query.from.timestamp(),
+            to,
+            query.instrument.as_deref(),
+            query.run_id.as_deref(),
+        )
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to fetch job instrument statuses: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
@@ -0,0 +1,123 @@
+use axum::{extract::Extension, http::header};
+use std::fmt::Write;
+use std::sync::Arc;
+
+use crate::{app_state::models::AppState, services::tinkoff_tokens::token_pool::TokenUsage};
+
+/// Метрики в текстовом формате Prometheus
+pub async fn get_metrics(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> ([(header::HeaderName, &'static str); 1], String) {
+    let usage = app_state.grpc_tinkoff.tokens.usage();
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render_token_usage(&usage),
+    )
+}
+
+/// Значение метрики токена и метода; None — значение не известно
+type MethodValue = fn(&TokenUsage, &str) -> Option<i64>;
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Использование токенов Tinkoff: по токену и по методу
+fn render_token_usage(usage: &[TokenUsage]) -> String {
+    let mut out = String::new();
+
+    metric(
+        &mut out,
+        "tinkoff_token_disabled",
+        "gauge",
+        "1 if the token was disabled after UNAUTHENTICATED",
+    );
+    for token in usage {
+        let _ = writeln!(
+            out,
+            "tinkoff_token_disabled{{token=\"{}\"}} {}",
+            token.label, token.disabled as u8
+        );
+    }
+
+    let per_method: [(&str, &str, &str, MethodValue); 5] = [
+        (
+            "tinkoff_token_requests_total",
+            "counter",
+            "Requests answered for the token and method",
+            |t, m| Some(t.methods[m].requests as i64),
+        ),
+        (
+            "tinkoff_token_errors_total",
+            "counter",
+            "Requests that ended with a gRPC or transport error",
+            |t, m| Some(t.methods[m].errors as i64),
+        ),
+        (
+            "tinkoff_token_rate_limited_total",
+            "counter",
+            "Requests rejected with RESOURCE_EXHAUSTED",
+            |t, m| Some(t.methods[m].rate_limited as i64),
+        ),
+        (
+            "tinkoff_token_ratelimit_limit",
+            "gauge",
+            "Last x-ratelimit-limit reported for the token and method",
+            |t, m| t.methods[m].limit,
+        ),
+        (
+            "tinkoff_token_ratelimit_remaining",
+            "gauge",
+            "Last x-ratelimit-remaining reported for the token and method",
+            |t, m| t.methods[m].remaining,
+        ),
+    ];
+    for (name, kind, help, value) in per_method {
+        metric(&mut out, name, kind, help);
+        for token in usage {
+            for method in token.methods.keys() {
+                if let Some(value) = value(token, method) {
+                    let _ = writeln!(
+                        out,
+                        "{}{{token=\"{}\",method=\"{}\"}} {}",
+                        name, token.label, method, value
+                    );
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env_config::models::app_config::TokenSelection;
+    use crate::services::tinkoff_tokens::token_pool::{TokenPool, TokenSlot};
+    use tonic::Code;
+
+    #[test]
+    fn renders_token_usage() {
+        let pool = TokenPool::new(
+            vec!["a".to_string(), "b".to_string()],
+            TokenSelection::RoundRobin,
+        );
+        let mut headers = http::HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", "299".parse().unwrap());
+        pool.record(TokenSlot(0), "GetCandles", &headers, Code::Ok);
+        pool.record(TokenSlot(1), "Shares", &headers, Code::Unauthenticated);
+
+        let text = render_token_usage(&pool.usage());
+        assert!(text.contains("tinkoff_token_disabled{token=\"token-2\"} 1"));
+        assert!(
+            text.contains(
+                "tinkoff_token_requests_total{token=\"token-1\",method=\"GetCandles\"} 1"
+            )
+        );
+        assert!(text.contains(
+            "tinkoff_token_ratelimit_remaining{token=\"token-1\",method=\"GetCandles\"} 299"
+        ));
+        assert!(!text.contains("tinkoff_token_ratelimit_limit{"));
+    }
+}
@@ -1,5 +1,61 @@
+pub mod api_keys_api;
+pub mod candles_api;
+pub mod exclusions_api;
+pub mod freshness_api;
 pub mod health_api;
 pub mod health_db;
+pub mod jobs_api;
+pub mod metrics_api;
+pub mod orderbook_api;
+pub mod readiness_api;
+pub mod reconciliation_api;
+pub mod retention_api;
+pub mod rollups_api;
+pub mod schedules_api;
+pub mod shares_api;
+pub mod sources_api;
+pub mod watchlist_api;
 
+pub use api_keys_api::{create_api_key, list_api_keys, revoke_api_key};
+pub use candles_api::{get_candles, get_quarantine_report};
+pub use exclusions_api::{add_exclusion, delete_exclusion, list_exclusions};
+pub use freshness_api::get_freshness_alerts;
 pub use health_api::health_api;
 pub use health_db::health_db;
+pub use jobs_api::{get_job_instruments, get_job_runs};
+pub use metrics_api::get_metrics;
+pub use orderbook_api::get_orderbook;
+pub use readiness_api::get_readiness;
+pub use reconciliation_api::get_reconciliation_report;
+pub use retention_api::get_retention_report;
+pub use rollups_api::get_rollup_check;
+pub use schedules_api::get_schedules;
+pub use shares_api::{get_instrument_events, get_share_catalog};
+pub use sources_api::get_source_divergences;
+pub use watchlist_api::{add_to_watchlist, get_watchlist, remove_from_watchlist};
+
+use axum::http::StatusCode;
+
+use crate::{
+    app_state::models::AppState,
+    db::{
+        clickhouse::clickhouse_service::ClickhouseService,
+        postgres::postgres_service::PostgresService,
+    },
+};
+
+/// ClickHouse для обработчика; 503, если он не подключён
+fn require_clickhouse(app_state: &AppState) -> Result<&ClickhouseService, StatusCode> {
+    app_state
+        .clickhouse_service
+        .as_deref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)
+}
+
+/// PostgreSQL для обработчика; 503, если он не подключён
+fn require_postgres(app_state: &AppState) -> Result<&PostgresService, StatusCode> {
+    app_state
+        .postgres_service
+        .as_deref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)
+}
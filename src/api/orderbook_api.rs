@@ -0,0 +1,80 @@
+use axum::{
+    Json,
+    extract::{Extension, Query},
+    http::StatusCode,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+
+use super::require_clickhouse;
+use crate::{app_state::models::AppState, services::shares::models::quotation::quotation_to_f64};
+
+#[derive(Debug, Deserialize)]
+pub struct OrderBookQuery {
+    pub instrument_uid: String,
+    /// Момент времени в формате RFC 3339, например 2025-03-04T07:00:00Z
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderBookLevel {
+    pub price: f64,
+    pub quantity: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderBookResponse {
+    pub instrument_uid: String,
+    pub time: DateTime<Utc>,
+    pub depth: i32,
+    pub is_consistent: bool,
+    pub bids: Vec<OrderBookLevel>,
+    pub asks: Vec<OrderBookLevel>,
+}
+
+/// Возвращает сохранённый срез стакана, ближайший к запрошенному времени
+pub async fn get_orderbook(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<OrderBookQuery>,
+) -> Result<Json<OrderBookResponse>, StatusCode> {
+    let snapshot = require_clickhouse(&app_state)?
+        .repository_orderbook
+        .get_nearest(&query.instrument_uid, query.time.timestamp_millis())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch order book: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    fn levels(units: &[i64], nanos: &[i32], quantities: &[i64]) -> Vec<OrderBookLevel> {
+        units
+            .iter()
+            .zip(nanos)
+            .zip(quantities)
+            .map(|((u, n), q)| OrderBookLevel {
+                price: quotation_to_f64(*u, *n),
+                quantity: *q,
+            })
+            .collect()
+    }
+
+    Ok(Json(OrderBookResponse {
+        time: DateTime::from_timestamp_millis(snapshot.time_ms).unwrap_or_default(),
+        depth: snapshot.depth,
+        is_consistent: snapshot.is_consistent,
+        bids: levels(
+            &snapshot.bids_price_units,
+            &snapshot.bids_price_nano,
+            &snapshot.bids_quantity,
+        ),
+        asks: levels(
+            &snapshot.asks_price_units,
+            &snapshot.asks_price_nano,
+            &snapshot.asks_quantity,
+        ),
+        instrument_uid: snapshot.instrument_uid,
+    }))
+}
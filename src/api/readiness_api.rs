@@ -0,0 +1,40 @@
+use axum::{Json, extract::Extension, http::StatusCode};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::{
+    app_state::models::AppState, services::tinkoff_channel::resilient_channel::EndpointStatus,
+};
+
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub replay: bool, // Ответы Tinkoff воспроизводятся из записи, сеть не нужна
+    pub tinkoff: Vec<EndpointStatus>,
+}
+
+/// Готовность принимать работу: активный адрес Tinkoff API доступен
+///
+/// Адрес, который ещё не проверялся, считается доступным; базы данных проверяет /db-health
+pub async fn get_readiness(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> (StatusCode, Json<Readiness>) {
+    let readiness = match &app_state.grpc_tinkoff.health {
+        Some(health) => Readiness {
+            ready: health.is_ready(),
+            replay: false,
+            tinkoff: health.statuses(),
+        },
+        None => Readiness {
+            ready: true,
+            replay: true,
+            tinkoff: Vec::new(),
+        },
+    };
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
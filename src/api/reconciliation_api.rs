@@ -0,0 +1,64 @@
+use axum::{
+    Json,
+    extract::{Extension, Query},
+    http::StatusCode,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::error;
+
+use super::require_clickhouse;
+use crate::{
+    app_state::models::AppState,
+    db::clickhouse::models::reconciliation_mismatch::DbReconciliationMismatch,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ReconciliationQuery {
+    pub from: Option<DateTime<Utc>>, // По умолчанию — последние 30 суток
+    pub to: Option<DateTime<Utc>>,
+    pub instrument_uid: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconciliationReport {
+    pub total: usize,
+    pub by_field: BTreeMap<String, usize>,
+    pub refetch_queued: usize,
+    pub mismatches: Vec<DbReconciliationMismatch>,
+}
+
+/// Сутки, в которые минутная история расходится с дневными свечами Tinkoff
+pub async fn get_reconciliation_report(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<ReconciliationQuery>,
+) -> Result<Json<ReconciliationReport>, StatusCode> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::days(30));
+
+    let mismatches = require_clickhouse(&app_state)?
+        .repository_reconciliation
+        .get_mismatches(
+            from.timestamp(),
+            to.timestamp(),
+            query.instrument_uid.as_deref(),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch reconciliation mismatches: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let mut by_field = BTreeMap::new();
+    for field in mismatches.iter().flat_map(|m| m.fields.split(',')) {
+        *by_field.entry(field.to_string()).or_insert(0) += 1;
+    }
+
+    Ok(Json(ReconciliationReport {
+        total: mismatches.len(),
+        by_field,
+        refetch_queued: mismatches.iter().filter(|m| m.refetch_queued).count(),
+        mismatches,
+    }))
+}
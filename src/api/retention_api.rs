@@ -0,0 +1,27 @@
+use axum::{Json, extract::Extension, http::StatusCode};
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::error;
+
+use super::require_clickhouse;
+use crate::{
+    app_state::models::AppState,
+    services::retention::retention_maintenance::{RetentionMaintenance, RetentionPlanItem},
+};
+
+/// Что политики хранения свернут и удалят, если запустить обслуживание сейчас
+pub async fn get_retention_report(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<Json<Vec<RetentionPlanItem>>, StatusCode> {
+    let maintenance =
+        RetentionMaintenance::new(require_clickhouse(&app_state)?.repository_retention.clone());
+
+    maintenance
+        .plan(&app_state.settings.app_config.retention, Utc::now())
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to build retention report: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
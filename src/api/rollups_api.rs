@@ -0,0 +1,52 @@
+use axum::{
+    Json,
+    extract::{Extension, Query},
+    http::StatusCode,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+use super::require_clickhouse;
+use crate::{
+    app_state::models::AppState,
+    env_config::models::retention::CandleResolution,
+    services::rollup::rollup_maintenance::{ROLLUP_RESOLUTIONS, RollupCheck, RollupMaintenance},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct RollupCheckQuery {
+    pub resolution: Option<CandleResolution>, // Без параметра проверяются все свёртки
+    pub from: Option<DateTime<Utc>>,          // По умолчанию — последние 7 суток
+    pub to: Option<DateTime<Utc>>,
+    pub instrument_uid: Option<String>,
+}
+
+/// Сверка свёрток 5min, 1hour и 1day с минутными свечами
+pub async fn get_rollup_check(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<RollupCheckQuery>,
+) -> Result<Json<Vec<RollupCheck>>, StatusCode> {
+    let resolutions = match query.resolution {
+        Some(CandleResolution::Minute) => return Err(StatusCode::BAD_REQUEST),
+        Some(resolution) => vec![resolution],
+        None => ROLLUP_RESOLUTIONS.to_vec(),
+    };
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::days(7));
+
+    RollupMaintenance::new(require_clickhouse(&app_state)?.repository_rollup.clone())
+        .check(
+            &resolutions,
+            from.timestamp(),
+            to.timestamp(),
+            query.instrument_uid.as_deref(),
+        )
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to check rollups: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
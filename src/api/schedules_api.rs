@@ -0,0 +1,11 @@
+use axum::{Json, extract::Extension};
+use std::sync::Arc;
+
+use crate::{app_state::models::AppState, services::scheduling::schedule_registry::ScheduleStatus};
+
+/// Расписания задач: следующий запуск, последний запуск и выполняется ли задача сейчас
+pub async fn get_schedules(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Json<Vec<ScheduleStatus>> {
+    Json(app_state.schedules.snapshot().await)
+}
@@ -0,0 +1,64 @@
+use axum::{
+    Json,
+    extract::{Extension, Query},
+    http::StatusCode,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+use super::require_clickhouse;
+use crate::{
+    app_state::models::AppState,
+    db::clickhouse::models::{instrument_event::DbInstrumentEvent, share_version::DbShareVersion},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CatalogQuery {
+    /// Дата в формате RFC 3339; если не указана — текущее состояние каталога
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// Возвращает каталог акций в том виде, в каком он был на дату `as_of`
+pub async fn get_share_catalog(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<CatalogQuery>,
+) -> Result<Json<Vec<DbShareVersion>>, StatusCode> {
+    let as_of = query.as_of.unwrap_or_else(Utc::now).timestamp();
+
+    require_clickhouse(&app_state)?
+        .repository_share_history
+        .get_catalog_as_of(as_of)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to fetch share catalog: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    pub from: DateTime<Utc>,
+    pub to: Option<DateTime<Utc>>,
+    pub uid: Option<String>,
+}
+
+/// Возвращает события жизненного цикла инструментов за период
+pub async fn get_instrument_events(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Json<Vec<DbInstrumentEvent>>, StatusCode> {
+    let to = query.to.unwrap_or_else(Utc::now).timestamp();
+
+    require_clickhouse(&app_state)?
+        .repository_instrument_event
+        .get_events(query.from.timestamp(), to, query.uid.as_deref())
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to fetch instrument events: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
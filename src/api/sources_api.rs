@@ -0,0 +1,61 @@
+use axum::{
+    Json,
+    extract::{Extension, Query},
+    http::StatusCode,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::error;
+
+use super::require_clickhouse;
+use crate::{
+    app_state::models::AppState, db::clickhouse::models::source_divergence::DbSourceDivergence,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct DivergenceQuery {
+    pub from: Option<DateTime<Utc>>, // По умолчанию — последние 7 суток
+    pub to: Option<DateTime<Utc>>,
+    pub instrument_uid: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DivergenceReport {
+    pub total: usize,
+    pub by_field: BTreeMap<String, usize>,
+    pub divergences: Vec<DbSourceDivergence>,
+}
+
+/// Свечи, в которых сохранённые данные Tinkoff расходятся с MOEX ISS
+pub async fn get_source_divergences(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Query(query): Query<DivergenceQuery>,
+) -> Result<Json<DivergenceReport>, StatusCode> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::days(7));
+
+    let divergences = require_clickhouse(&app_state)?
+        .repository_source_divergence
+        .get_divergences(
+            from.timestamp(),
+            to.timestamp(),
+            query.instrument_uid.as_deref(),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch source divergences: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let mut by_field = BTreeMap::new();
+    for field in divergences.iter().flat_map(|d| d.fields.split(',')) {
+        *by_field.entry(field.to_string()).or_insert(0) += 1;
+    }
+
+    Ok(Json(DivergenceReport {
+        total: divergences.len(),
+        by_field,
+        divergences,
+    }))
+}
@@ -0,0 +1,84 @@
+use axum::{
+    Json,
+    extract::{Extension, Path},
+    http::StatusCode,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::require_postgres;
+use crate::{
+    app_state::models::AppState,
+    db::postgres::models::watchlist::{DbWatchlistInstrument, WatchlistOrigin},
+};
+
+/// Список загрузки свечей с контрольными точками
+pub async fn get_watchlist(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> Result<Json<Vec<DbWatchlistInstrument>>, StatusCode> {
+    require_postgres(&app_state)?
+        .repository_watchlist
+        .get_all()
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to fetch watchlist: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WatchlistAddRequest {
+    pub uid: String,
+    /// С какой даты загружать историю
+    pub from: DateTime<Utc>,
+}
+
+/// Добавляет инструмент в список загрузки или включает его обратно
+pub async fn add_to_watchlist(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(request): Json<WatchlistAddRequest>,
+) -> Result<StatusCode, StatusCode> {
+    if request.uid.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    require_postgres(&app_state)?
+        .repository_watchlist
+        .add_instruments(
+            &[(request.uid.clone(), request.from.timestamp())],
+            WatchlistOrigin::Manual,
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to add {} to watchlist: {}", request.uid, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("Added {} to watchlist from {}", request.uid, request.from);
+    Ok(StatusCode::CREATED)
+}
+
+/// Выключает загрузку свечей по инструменту; контрольная точка сохраняется
+pub async fn remove_from_watchlist(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(uid): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let updated = require_postgres(&app_state)?
+        .repository_watchlist
+        .remove(&uid)
+        .await
+        .map_err(|e| {
+            error!("Failed to remove {} from watchlist: {}", uid, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if updated == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("Deactivated {} in watchlist", uid);
+    Ok(StatusCode::NO_CONTENT)
+}
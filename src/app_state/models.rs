@@ -1,48 +1,204 @@
 use crate::db::clickhouse::clickhouse_service::ClickhouseService;
+use crate::db::postgres::models::watchlist::{DbWatchlistInstrument, WatchlistOrigin};
+use crate::db::postgres::postgres_service::PostgresService;
+use crate::db::storage::candle_repository::CandleRepository;
 
 // src/app_state/mod.rs
 use crate::env_config::models::app_setting::AppSettings;
 
+use crate::services::api_keys::{self, ADMIN_KEY_NAME};
+use crate::services::archive::{
+    archive_sink::ArchivingCandleRepository, parquet_archive::ParquetArchive,
+};
 use crate::services::candles::client_candle::ClientCandle;
+use crate::services::exclusions::exclusion_list::ExclusionList;
+use crate::services::job_ledger::JobLedger;
+use crate::services::job_lock::JobLocks;
+use crate::services::scheduling::schedule_registry::ScheduleRegistry;
 
 use crate::services::shares::client::ClientShares;
+use crate::services::sources::candle_source::CandleSource;
 use crate::services::tinkoff_client_grpc::TinkoffClient;
 
 use std::sync::Arc;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{error, info};
 
 pub struct AppState {
     pub settings: Arc<AppSettings>,
-    pub clickhouse_service: Arc<ClickhouseService>,
+    pub clickhouse_service: Option<Arc<ClickhouseService>>, // None, если хранилищу он не нужен и недоступен
+    pub postgres_service: Option<Arc<PostgresService>>,     // То же для PostgreSQL
+    pub candle_repository: Arc<dyn CandleRepository + Send + Sync>,
+    pub archive: Option<Arc<ParquetArchive>>, // None when [archive] is disabled
     pub grpc_tinkoff: Arc<TinkoffClient>,
+    pub candle_source: Arc<dyn CandleSource + Send + Sync>, // Откуда загружаются свечи и каталог
+    pub exclusions: Arc<ExclusionList>,
+
+    // Остановка: токен отменяется по SIGTERM, фоновые задачи запускаются через tasks
+    pub shutdown: CancellationToken,
+    pub tasks: TaskTracker,
+    pub schedules: Arc<ScheduleRegistry>,
+    pub job_locks: Arc<JobLocks>,
 
     // Клиенты
     pub client_tinkoff_candle: Arc<ClientCandle>,
-    pub client_shares: Arc<ClientShares>,
+    pub client_shares: Option<Arc<ClientShares>>, // Каталог хранится в ClickHouse
 }
 
 impl AppState {
     pub async fn new(
         settings: Arc<AppSettings>,
-        clickhouse_service: Arc<ClickhouseService>,
+        clickhouse_service: Option<Arc<ClickhouseService>>,
+        postgres_service: Option<Arc<PostgresService>>,
+        candle_repository: Arc<dyn CandleRepository + Send + Sync>,
         grpc_tinkoff: Arc<TinkoffClient>,
+        candle_source: Arc<dyn CandleSource + Send + Sync>,
     ) -> Self {
+        // Список исключённых инструментов общий для всех клиентов
+        let exclusions = Arc::new(ExclusionList::new(
+            clickhouse_service
+                .as_ref()
+                .map(|clickhouse| clickhouse.repository_exclusion.clone()),
+            &settings,
+        ));
+        if let Err(e) = exclusions.reload().await {
+            error!("Failed to load instrument exclusions: {}", e);
+        }
+
+        // Watchlist and checkpoints used to live in ClickHouse instrument_candle_info
+        let imported = match (&clickhouse_service, &postgres_service) {
+            (Some(clickhouse), Some(postgres)) => import_watchlist(clickhouse, postgres).await,
+            _ => Ok(()),
+        };
+        if let Err(e) = imported {
+            error!("Failed to import watchlist from ClickHouse: {}", e);
+        }
+
+        if let (Some(admin_key), Some(postgres_service)) =
+            (&settings.app_env.api_admin_key, &postgres_service)
+        {
+            let admin_key_hash = api_keys::hash_key(admin_key);
+            match postgres_service
+                .repository_api_key
+                .upsert(ADMIN_KEY_NAME, &admin_key_hash)
+                .await
+            {
+                Ok(()) => info!("Admin API key registered"),
+                Err(e) => error!("Failed to register the admin API key: {}", e),
+            }
+        }
+
+        let archive = settings
+            .app_config
+            .archive
+            .enabled
+            .then(|| Arc::new(ParquetArchive::new(&settings.app_config.archive.path)));
+        // Every batch written to the storage backend is also copied to the archive
+        let candle_repository: Arc<dyn CandleRepository + Send + Sync> = match &archive {
+            Some(archive) => Arc::new(ArchivingCandleRepository::new(
+                candle_repository,
+                archive.clone(),
+            )),
+            None => candle_repository,
+        };
+
+        let shutdown = CancellationToken::new();
+
+        let job_locks = Arc::new(JobLocks::new(
+            postgres_service
+                .as_ref()
+                .map(|postgres| postgres.connection.clone()),
+            settings.app_config.leader_election.enabled,
+        ));
+
         //  создаем бюзнес-клиентов
         let client_tinkoff_candle = Arc::new(ClientCandle::new(
             clickhouse_service.clone(),
-            grpc_tinkoff.clone(),
+            postgres_service.clone(),
+            candle_repository.clone(),
+            candle_source.clone(),
+            exclusions.clone(),
+            shutdown.clone(),
             settings.clone(),
         ));
 
-        let client_shares =
-            Arc::new(ClientShares::new(clickhouse_service.clone(), grpc_tinkoff.clone()).await);
+        let client_shares = match &clickhouse_service {
+            Some(clickhouse_service) => Some(Arc::new(
+                ClientShares::new(
+                    clickhouse_service.clone(),
+                    postgres_service.clone(),
+                    candle_source.clone(),
+                    exclusions.clone(),
+                    settings.clone(),
+                )
+                .await,
+            )),
+            None => None,
+        };
 
         Self {
             settings,
             clickhouse_service,
+            postgres_service,
+            candle_repository,
+            archive,
             grpc_tinkoff,
+            candle_source,
+            exclusions,
+
+            shutdown,
+            tasks: TaskTracker::new(),
+            schedules: Arc::new(ScheduleRegistry::default()),
+            job_locks,
 
             client_tinkoff_candle,
             client_shares,
         }
     }
 }
+
+impl AppState {
+    /// Журнал запусков для фоновых задач; без PostgreSQL запуски не записываются
+    pub fn job_ledger(&self) -> JobLedger {
+        JobLedger::new(
+            self.postgres_service
+                .as_ref()
+                .map(|postgres| postgres.repository_job_run.clone()),
+        )
+    }
+}
+
+/// Переносит список загрузки и контрольные точки из ClickHouse, пока список в PostgreSQL пуст
+async fn import_watchlist(
+    clickhouse_service: &ClickhouseService,
+    postgres_service: &PostgresService,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let watchlist = &postgres_service.repository_watchlist;
+    if !watchlist.is_empty().await? {
+        return Ok(());
+    }
+
+    let instruments: Vec<DbWatchlistInstrument> = clickhouse_service
+        .repository_my_instrument
+        .get_backfill_list()
+        .await?
+        .into_iter()
+        .map(|instrument| DbWatchlistInstrument {
+            uid: instrument.uid,
+            first_1min_candle_date: instrument.first_1min_candle_date,
+            last_1min_candle_date: instrument.last_1min_candle_date,
+            is_active: instrument.is_active,
+            origin: WatchlistOrigin::Manual.as_str().to_string(),
+        })
+        .collect();
+    if instruments.is_empty() {
+        return Ok(());
+    }
+
+    watchlist.import(&instruments).await?;
+    info!(
+        "Imported {} instruments with checkpoints from ClickHouse into the watchlist",
+        instruments.len()
+    );
+    Ok(())
+}
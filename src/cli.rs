@@ -0,0 +1,143 @@
+use chrono::NaiveDate;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+pub const DEFAULT_MOCK_FIXTURES: &str = "fixtures/tinkoff_mock.json";
+pub const DEFAULT_MOCK_LISTEN: &str = "127.0.0.1:50051";
+
+/// Команда запуска: без аргументов приложение работает как сервис
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Serve,
+    /// t-candles migrate: применить миграции ClickHouse и PostgreSQL и выйти
+    Migrate,
+    /// t-candles archive-rebuild [--instrument <uid>]
+    ArchiveRebuild {
+        instrument_uid: Option<String>,
+    },
+    /// t-candles rollup-backfill [--instrument <uid>] [--from YYYY-MM-DD] [--to YYYY-MM-DD]
+    RollupBackfill {
+        instrument_uid: Option<String>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>, // Не включительно; по умолчанию — текущие сутки UTC
+    },
+    /// t-candles mock-tinkoff [--fixtures <path>] [--listen <addr>]: локальный сервер вместо Tinkoff API
+    MockTinkoff {
+        fixtures: PathBuf,
+        listen: SocketAddr,
+    },
+}
+
+fn parse_date(flag: &str, value: Option<String>) -> Result<NaiveDate, String> {
+    let value = value.ok_or(format!("{} requires a date", flag))?;
+    NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+        .map_err(|_| format!("{} expects YYYY-MM-DD, got {}", flag, value))
+}
+
+impl Command {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter().skip(1);
+        match args.next().as_deref() {
+            None => Ok(Command::Serve),
+            Some("migrate") => match args.next() {
+                None => Ok(Command::Migrate),
+                Some(other) => Err(format!("Unknown argument: {}", other)),
+            },
+            Some("archive-rebuild") => {
+                let mut instrument_uid = None;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--instrument" => {
+                            instrument_uid =
+                                Some(args.next().ok_or("--instrument requires a uid")?);
+                        }
+                        other => return Err(format!("Unknown argument: {}", other)),
+                    }
+                }
+                Ok(Command::ArchiveRebuild { instrument_uid })
+            }
+            Some("rollup-backfill") => {
+                let (mut instrument_uid, mut from, mut to) = (None, None, None);
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--instrument" => {
+                            instrument_uid =
+                                Some(args.next().ok_or("--instrument requires a uid")?);
+                        }
+                        "--from" => from = Some(parse_date("--from", args.next())?),
+                        "--to" => to = Some(parse_date("--to", args.next())?),
+                        other => return Err(format!("Unknown argument: {}", other)),
+                    }
+                }
+                Ok(Command::RollupBackfill {
+                    instrument_uid,
+                    from,
+                    to,
+                })
+            }
+            Some("mock-tinkoff") => {
+                let mut fixtures = PathBuf::from(DEFAULT_MOCK_FIXTURES);
+                let mut listen: SocketAddr = DEFAULT_MOCK_LISTEN.parse().unwrap();
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--fixtures" => {
+                            fixtures = args.next().ok_or("--fixtures requires a path")?.into();
+                        }
+                        "--listen" => {
+                            let value = args.next().ok_or("--listen requires an address")?;
+                            listen = value.parse().map_err(|_| {
+                                format!("--listen expects host:port, got {}", value)
+                            })?;
+                        }
+                        other => return Err(format!("Unknown argument: {}", other)),
+                    }
+                }
+                Ok(Command::MockTinkoff { fixtures, listen })
+            }
+            Some(other) => Err(format!(
+                "Unknown command: {} (expected migrate, archive-rebuild, rollup-backfill or mock-tinkoff)",
+                other
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        Command::from_args(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse(&["t-candles"]), Ok(Command::Serve));
+        assert_eq!(parse(&["t-candles", "migrate"]), Ok(Command::Migrate));
+        assert_eq!(
+            parse(&["t-candles", "archive-rebuild", "--instrument", "uid"]),
+            Ok(Command::ArchiveRebuild {
+                instrument_uid: Some("uid".to_string())
+            })
+        );
+        assert!(parse(&["t-candles", "archive-rebuild", "--instrument"]).is_err());
+        assert_eq!(
+            parse(&["t-candles", "rollup-backfill", "--from", "2024-01-01"]),
+            Ok(Command::RollupBackfill {
+                instrument_uid: None,
+                from: NaiveDate::from_ymd_opt(2024, 1, 1),
+                to: None,
+            })
+        );
+        assert!(parse(&["t-candles", "rollup-backfill", "--to", "01.01.2024"]).is_err());
+        assert_eq!(
+            parse(&["t-candles", "mock-tinkoff", "--listen", "0.0.0.0:50052"]),
+            Ok(Command::MockTinkoff {
+                fixtures: PathBuf::from(DEFAULT_MOCK_FIXTURES),
+                listen: "0.0.0.0:50052".parse().unwrap(),
+            })
+        );
+        assert!(parse(&["t-candles", "mock-tinkoff", "--listen", "50052"]).is_err());
+        assert!(parse(&["t-candles", "rebuild"]).is_err());
+    }
+}
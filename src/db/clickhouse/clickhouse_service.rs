@@ -1,23 +1,37 @@
-use crate::db::clickhouse::{
-    connection::ClickhouseConnection, repository::candle_repository::ClickhouseCandleRepository,
-};
+use crate::db::clickhouse::connection::ClickhouseConnection;
 
 use crate::env_config::models::app_setting::AppSettings;
 use std::sync::Arc;
 use tracing::{error, info};
 
-use super::repository::candle_repository::CandleRepository;
+use super::repository::repository_candle_quarantine::CandleQuarantineRepository;
+use super::repository::repository_exclusion::ExclusionRepository;
+use super::repository::repository_instrument_event::InstrumentEventRepository;
 use super::repository::repository_my_instrument::RepositoryMyInstrument;
+use super::repository::repository_orderbook::OrderBookRepository;
+use super::repository::repository_reconciliation::ReconciliationRepository;
+use super::repository::repository_retention::RetentionRepository;
+use super::repository::repository_rollup::RollupRepository;
 use super::repository::repository_share::ShareRepository;
+use super::repository::repository_share_history::ShareHistoryRepository;
+use super::repository::repository_source_divergence::SourceDivergenceRepository;
 
 pub struct ClickhouseService {
     // Connections
     pub connection: Arc<ClickhouseConnection>,
 
-    pub repository_candle: Arc<dyn CandleRepository + Send + Sync>,
+    pub repository_candle_quarantine: Arc<CandleQuarantineRepository>,
 
     pub repository_share: Arc<ShareRepository>,
+    pub repository_share_history: Arc<ShareHistoryRepository>,
+    pub repository_instrument_event: Arc<InstrumentEventRepository>,
+    pub repository_exclusion: Arc<ExclusionRepository>,
     pub repository_my_instrument: Arc<RepositoryMyInstrument>,
+    pub repository_orderbook: Arc<OrderBookRepository>,
+    pub repository_retention: Arc<RetentionRepository>,
+    pub repository_rollup: Arc<RollupRepository>,
+    pub repository_reconciliation: Arc<ReconciliationRepository>,
+    pub repository_source_divergence: Arc<SourceDivergenceRepository>,
 }
 
 impl ClickhouseService {
@@ -39,27 +53,56 @@ impl ClickhouseService {
 
         // Initialize analytical repositories (ClickHouse)
         info!("Initialize repositories (ClickHouse)");
-        let repository_candle = Arc::new(ClickhouseCandleRepository::new(
+        let repository_candle_quarantine = Arc::new(CandleQuarantineRepository::new(
             clickhouse_connection.clone(),
-        )) as Arc<dyn CandleRepository + Send + Sync>;
+        ));
 
         let repository_share = Arc::new(ShareRepository::new(clickhouse_connection.clone()));
 
+        let repository_share_history =
+            Arc::new(ShareHistoryRepository::new(clickhouse_connection.clone()));
+
+        let repository_instrument_event = Arc::new(InstrumentEventRepository::new(
+            clickhouse_connection.clone(),
+        ));
+
+        let repository_exclusion =
+            Arc::new(ExclusionRepository::new(clickhouse_connection.clone()));
+
         let repository_my_instrument =
             Arc::new(RepositoryMyInstrument::new(clickhouse_connection.clone()));
-        // Initialize operational repositories (PostgreSQL)
-        info!("Initialize repositories (PostgreSQL)");
+
+        let repository_orderbook =
+            Arc::new(OrderBookRepository::new(clickhouse_connection.clone()));
+
+        let repository_retention =
+            Arc::new(RetentionRepository::new(clickhouse_connection.clone()));
+
+        let repository_rollup = Arc::new(RollupRepository::new(clickhouse_connection.clone()));
+
+        let repository_reconciliation =
+            Arc::new(ReconciliationRepository::new(clickhouse_connection.clone()));
+
+        let repository_source_divergence = Arc::new(SourceDivergenceRepository::new(
+            clickhouse_connection.clone(),
+        ));
 
         info!("Database service initialized successfully");
         Ok(Self {
             connection: clickhouse_connection,
 
-            repository_candle,
+            repository_candle_quarantine,
 
             repository_share,
+            repository_share_history,
+            repository_instrument_event,
+            repository_exclusion,
             repository_my_instrument,
+            repository_orderbook,
+            repository_retention,
+            repository_rollup,
+            repository_reconciliation,
+            repository_source_divergence,
         })
     }
-
-
 }
@@ -1,3 +1,4 @@
+use super::schema::{self, SchemaMode};
 use crate::env_config::models::app_setting::AppSettings;
 use clickhouse::Client;
 use std::sync::Arc;
@@ -6,21 +7,37 @@ use tracing::{debug, error, info};
 #[derive(Clone)]
 pub struct ClickhouseConnection {
     client: Client,
-       database: String, 
+    database: String,
 }
 
 impl ClickhouseConnection {
+    /// Подключается и готовит схему согласно clickhouse.auto_migrate
     pub async fn new(settings: Arc<AppSettings>) -> Result<Self, clickhouse::error::Error> {
+        let mode = if settings.app_config.clickhouse.auto_migrate {
+            SchemaMode::Migrate
+        } else {
+            SchemaMode::Verify
+        };
+        Self::connect(settings, mode).await
+    }
+
+    /// Подключается, применяет миграции (в режиме Migrate) и проверяет схему
+    ///
+    /// Ошибка проверки не даёт приложению запуститься до старта планировщиков
+    pub async fn connect(
+        settings: Arc<AppSettings>,
+        mode: SchemaMode,
+    ) -> Result<Self, clickhouse::error::Error> {
         info!("Initializing ClickHouse connection...");
 
         // Сохраняем имя базы данных
         let database = settings.app_env.clickhouse_database.clone();
-             // Создаем клиент с настройками аутентификации
-        let client = Client::default()
+        // Создаем клиент с настройками аутентификации; база выбирается после проверки,
+        // что она существует
+        let server = Client::default()
             .with_url(&settings.app_env.clickhouse_url)
             .with_user(&settings.app_env.clickhouse_user)
             .with_password(&settings.app_env.clickhouse_password)
-             .with_database(&database) 
             .with_option(
                 "connect_timeout",
                 settings.app_config.clickhouse.timeout.to_string(),
@@ -38,7 +55,7 @@ impl ClickhouseConnection {
         let test_query = "SELECT 1";
         debug!("Executing test query: {}", test_query);
 
-        match client.query(test_query).execute().await {
+        match server.query(test_query).execute().await {
             Ok(_) => info!("ClickHouse connection successful"),
             Err(e) => {
                 error!("Failed to connect to ClickHouse: {}", e);
@@ -46,6 +63,16 @@ impl ClickhouseConnection {
             }
         }
 
+        if mode == SchemaMode::Migrate {
+            schema::ensure_database(&server, &database).await?;
+        }
+        let client = server.with_database(&database);
+
+        if let Err(e) = schema::prepare(&client, &database, mode).await {
+            error!("ClickHouse schema is not ready: {}", e);
+            return Err(e);
+        }
+
         Ok(Self { client, database })
     }
 
@@ -2,3 +2,4 @@ pub mod clickhouse_service;
 pub mod connection;
 pub mod models;
 pub mod repository;
+pub mod schema;
@@ -1,11 +1,9 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, clickhouse::Row, Deserialize, Serialize)]
-pub struct DbModelMyInstrument{
-
-pub uid: String,
-pub first_1min_candle_date: i64,
-pub last_1min_candle_date: i64,
-
-
-}
\ No newline at end of file
+pub struct DbModelMyInstrument {
+    pub uid: String,
+    pub first_1min_candle_date: i64,
+    pub last_1min_candle_date: i64,
+    pub is_active: bool,
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// Исключение инструмента в таблице instrument_exclusions
+///
+/// Таблица — ReplacingMergeTree по (key_type, key_value), удаление
+/// записывается строкой с is_deleted = 1
+#[derive(Debug, Clone, Serialize, Deserialize, clickhouse::Row)]
+pub struct DbExclusion {
+    pub key_type: String,
+    pub key_value: String,
+    pub reason: String,
+    pub expires_at: Option<i64>,
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// Событие жизненного цикла инструмента в таблице instrument_events
+#[derive(Debug, Clone, Serialize, Deserialize, clickhouse::Row)]
+pub struct DbInstrumentEvent {
+    pub event_time: i64,
+    pub event_type: String,
+    pub uid: String,
+    pub figi: String,
+    pub ticker: String,
+    pub old_value: String,
+    pub new_value: String,
+}
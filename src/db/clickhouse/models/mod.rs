@@ -1,4 +1,12 @@
 pub mod candle;
-pub mod load_status;
 pub mod db_liquid_shares;
 pub mod db_model_my_instrument;
+pub mod exclusion;
+pub mod instrument_event;
+pub mod orderbook;
+pub mod quarantined_candle;
+pub mod reconciliation_mismatch;
+pub mod share_listing;
+pub mod share_version;
+pub mod source_divergence;
+pub mod universe_change;
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// Срез стакана, сохранённый в ClickHouse.
+/// Цены хранятся как пары units/nano, уровни стакана — параллельными массивами
+#[derive(Debug, Clone, Serialize, Deserialize, clickhouse::Row)]
+pub struct DbOrderBookSnapshot {
+    pub instrument_uid: String,
+    pub time_ms: i64,
+    pub depth: i32,
+    pub is_consistent: bool,
+    pub bids_price_units: Vec<i64>,
+    pub bids_price_nano: Vec<i32>,
+    pub bids_quantity: Vec<i64>,
+    pub asks_price_units: Vec<i64>,
+    pub asks_price_nano: Vec<i32>,
+    pub asks_quantity: Vec<i64>,
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// Свеча, не прошедшая проверку, в таблице tinkoff_candles_quarantine
+///
+/// Отсутствующие цены хранятся как NULL, чтобы их нельзя было спутать с нулевыми
+#[derive(Debug, Clone, Serialize, Deserialize, clickhouse::Row)]
+pub struct DbQuarantinedCandle {
+    pub instrument_uid: String,
+    pub time: i64,
+    pub open_units: Option<i64>,
+    pub open_nano: Option<i32>,
+    pub high_units: Option<i64>,
+    pub high_nano: Option<i32>,
+    pub low_units: Option<i64>,
+    pub low_nano: Option<i32>,
+    pub close_units: Option<i64>,
+    pub close_nano: Option<i32>,
+    pub volume: i64,
+    pub reason: String,
+    pub detected_at: i64,
+}
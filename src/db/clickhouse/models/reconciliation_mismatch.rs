@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// Сутки, в которые минутная история разошлась с официальной дневной свечой
+///
+/// Значения отсутствующей стороны хранятся как NULL: нет дневной свечи Tinkoff
+/// или нет ни одной сохранённой минуты
+#[derive(Debug, Clone, Serialize, Deserialize, clickhouse::Row)]
+pub struct DbReconciliationMismatch {
+    pub run_id: String,
+    pub instrument_uid: String,
+    pub day: i64,       // Начало суток UTC, секунды
+    pub fields: String, // Через запятую: missing, unexpected, open, high, low, close, volume
+    pub official_open: Option<f64>,
+    pub official_high: Option<f64>,
+    pub official_low: Option<f64>,
+    pub official_close: Option<f64>,
+    pub official_volume: Option<i64>,
+    pub stored_open: Option<f64>,
+    pub stored_high: Option<f64>,
+    pub stored_low: Option<f64>,
+    pub stored_close: Option<f64>,
+    pub stored_volume: Option<i64>,
+    pub refetch_queued: bool,
+    pub detected_at: i64,
+}
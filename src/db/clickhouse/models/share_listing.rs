@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// Акция из tinkoff_shares с биржевыми реквизитами MOEX: по ним бумага ищется в ISS
+#[derive(Debug, Clone, Serialize, Deserialize, clickhouse::Row)]
+pub struct DbShareListing {
+    pub uid: String,
+    pub ticker: String,
+    pub class_code: String, // Режим торгов (board) ISS, например TQBR
+    pub lot: i32,
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+use crate::generate::tinkoff_public_invest_api_contract_v1::{SecurityTradingStatus, Share};
+use crate::services::shares::models::quotation::extract_quotation;
+
+/// Версия записи каталога акций в таблице tinkoff_shares_history
+///
+/// Новая версия появляется при каждом изменении отслеживаемых атрибутов.
+/// `valid_to = None` означает, что версия актуальна сейчас.
+/// Время хранится в секундах (Unix timestamp), как и в tinkoff_shares
+#[derive(Debug, Clone, Serialize, Deserialize, clickhouse::Row)]
+pub struct DbShareVersion {
+    pub uid: String,
+    pub figi: String,
+    pub ticker: String,
+    pub class_code: String,
+    pub name: String,
+    pub exchange: String,
+    pub currency: String,
+
+    // Отслеживаемые атрибуты
+    pub lot: i32,
+    pub trading_status: String,
+    pub short_enabled_flag: bool,
+    pub otc_flag: bool,
+    pub buy_available_flag: bool,
+    pub sell_available_flag: bool,
+    pub div_yield_flag: bool,
+    pub api_trade_available_flag: bool,
+    pub for_iis_flag: bool,
+    pub for_qual_investor_flag: bool,
+    pub weekend_flag: bool,
+    pub blocked_tca_flag: bool,
+    pub liquidity_flag: bool,
+    pub min_price_increment_units: i64,
+    pub min_price_increment_nano: i32,
+
+    pub valid_from: i64,
+    pub valid_to: Option<i64>,
+}
+
+impl DbShareVersion {
+    /// Создаёт открытую версию из ответа API, действующую с `valid_from`
+    pub fn from_share(share: &Share, valid_from: i64) -> Self {
+        let trading_status = SecurityTradingStatus::try_from(share.trading_status)
+            .map(|status| status.as_str_name().to_string())
+            .unwrap_or_else(|_| "UNKNOWN".to_string());
+        let (min_price_increment_units, min_price_increment_nano) =
+            extract_quotation(&share.min_price_increment);
+
+        Self {
+            uid: share.uid.clone(),
+            figi: share.figi.clone(),
+            ticker: share.ticker.clone(),
+            class_code: share.class_code.clone(),
+            name: share.name.clone(),
+            exchange: share.exchange.clone(),
+            currency: share.currency.clone(),
+            lot: share.lot,
+            trading_status,
+            short_enabled_flag: share.short_enabled_flag,
+            otc_flag: share.otc_flag,
+            buy_available_flag: share.buy_available_flag,
+            sell_available_flag: share.sell_available_flag,
+            div_yield_flag: share.div_yield_flag,
+            api_trade_available_flag: share.api_trade_available_flag,
+            for_iis_flag: share.for_iis_flag,
+            for_qual_investor_flag: share.for_qual_investor_flag,
+            weekend_flag: share.weekend_flag,
+            blocked_tca_flag: share.blocked_tca_flag,
+            liquidity_flag: share.liquidity_flag,
+            min_price_increment_units,
+            min_price_increment_nano,
+            valid_from,
+            valid_to: None,
+        }
+    }
+
+    /// Сравнивает отслеживаемые атрибуты двух версий
+    pub fn same_attributes(&self, other: &Self) -> bool {
+        self.lot == other.lot
+            && self.trading_status == other.trading_status
+            && self.short_enabled_flag == other.short_enabled_flag
+            && self.otc_flag == other.otc_flag
+            && self.buy_available_flag == other.buy_available_flag
+            && self.sell_available_flag == other.sell_available_flag
+            && self.div_yield_flag == other.div_yield_flag
+            && self.api_trade_available_flag == other.api_trade_available_flag
+            && self.for_iis_flag == other.for_iis_flag
+            && self.for_qual_investor_flag == other.for_qual_investor_flag
+            && self.weekend_flag == other.weekend_flag
+            && self.blocked_tca_flag == other.blocked_tca_flag
+            && self.liquidity_flag == other.liquidity_flag
+            && self.min_price_increment_units == other.min_price_increment_units
+            && self.min_price_increment_nano == other.min_price_increment_nano
+    }
+}
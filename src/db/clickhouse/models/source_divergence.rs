@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// Свеча, в которой сохранённые данные Tinkoff расходятся со свечой MOEX ISS
+///
+/// Значения отсутствующей стороны хранятся как NULL: свеча есть только в одном источнике
+#[derive(Debug, Clone, Serialize, Deserialize, clickhouse::Row)]
+pub struct DbSourceDivergence {
+    pub run_id: String,
+    pub instrument_uid: String,
+    pub ticker: String,
+    pub resolution: String, // 1min, 1hour или 1day
+    pub time: i64,          // Начало свечи UTC, секунды
+    pub fields: String,     // Через запятую: only_tinkoff, only_iss, open, high, low, close, volume
+    pub tinkoff_open: Option<f64>,
+    pub tinkoff_high: Option<f64>,
+    pub tinkoff_low: Option<f64>,
+    pub tinkoff_close: Option<f64>,
+    pub tinkoff_volume: Option<i64>,
+    pub iss_open: Option<f64>,
+    pub iss_high: Option<f64>,
+    pub iss_low: Option<f64>,
+    pub iss_close: Option<f64>,
+    pub iss_volume: Option<i64>, // В лотах Tinkoff
+    pub detected_at: i64,
+}
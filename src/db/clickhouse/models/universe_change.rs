@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// Запись журнала изменений списка загрузки свечей (таблица universe_changes)
+#[derive(Debug, Clone, Serialize, Deserialize, clickhouse::Row)]
+pub struct DbUniverseChange {
+    pub change_time: i64,
+    pub uid: String,
+    pub ticker: String,
+    /// ADDED или REMOVED
+    pub action: String,
+}
@@ -1,45 +1,57 @@
 use crate::db::clickhouse::connection::ClickhouseConnection;
-
-use crate::db::clickhouse::models::candle::{DailyCandle, DbCandle};
+use crate::db::postgres::repository::repository_watchlist::WatchlistRepository;
+use crate::db::storage::candle_repository::{CandleRepository, StorageError};
+use crate::db::storage::stored_candle::StoredCandle;
+use crate::env_config::models::retention::CandleResolution;
 use crate::generate::tinkoff_public_invest_api_contract_v1::HistoricCandle;
 
 use async_trait::async_trait;
-use chrono::{DateTime, NaiveDate, Utc};
-use clickhouse::error::Error as ClickhouseError;
-use clickhouse::{Row, error, insert};
-use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, error, info};
 
-#[async_trait]
-pub trait CandleRepository {
-    /// Вставка исторических свечей в ClickHouse
-    ///
-    /// # Параметры
-    /// * `candles` - Вектор свечей для вставки
-    /// * `instrument_uid` - Идентификатор инструмента
-    ///
-    /// # Возвращает
-    /// * `Result<u64, ClickhouseError>` - Количество успешно вставленных свечей или ошибку
-    async fn insert_candles(
-        &self,
-        candles: Vec<HistoricCandle>,
-        instrument_uid: &str,
-    ) -> Result<u64, ClickhouseError>;
-}
-
+/// Хранилище свечей в ClickHouse: таблица tinkoff_candles_1min
+///
+/// Контрольные точки ClickHouse не изменяет на месте, они хранятся в PostgreSQL
 pub struct ClickhouseCandleRepository {
     connection: Arc<ClickhouseConnection>,
-
+    watchlist: Arc<WatchlistRepository>,
 }
 
 impl ClickhouseCandleRepository {
-    pub fn new(connection: Arc<ClickhouseConnection>) -> Self {
+    pub fn new(connection: Arc<ClickhouseConnection>, watchlist: Arc<WatchlistRepository>) -> Self {
         Self {
             connection,
- 
+            watchlist,
         }
     }
+
+    /// Удаляет свечи инструмента за период [from, to) (секунды), например перед перезаливкой из архива
+    pub async fn delete_candles(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<(), StorageError> {
+        let query = format!(
+            "DELETE FROM {}.tinkoff_candles_1min
+            WHERE instrument_uid = ? AND time >= {} AND time < {}",
+            self.connection.get_database(),
+            from,
+            to
+        );
+
+        debug!(
+            "Deleting candles for instrument_uid={} in [{}, {})",
+            instrument_uid, from, to
+        );
+        Ok(self
+            .connection
+            .get_client()
+            .query(&query)
+            .bind(instrument_uid)
+            .execute()
+            .await?)
+    }
 }
 
 #[async_trait]
@@ -48,7 +60,7 @@ impl CandleRepository for ClickhouseCandleRepository {
         &self,
         candles: Vec<HistoricCandle>,
         instrument_uid: &str,
-    ) -> Result<u64, ClickhouseError> {
+    ) -> Result<u64, StorageError> {
         if candles.is_empty() {
             info!("No candles to insert");
             return Ok(0);
@@ -75,7 +87,11 @@ impl CandleRepository for ClickhouseCandleRepository {
         let mut current_batch_size = BATCH_SIZE;
 
         // Получаем полное имя таблицы с использованием схемы из конфигурации
-        let table_name = format!("{}.{}", self.connection.get_database(), "tinkoff_candles_1min");
+        let table_name = format!(
+            "{}.{}",
+            self.connection.get_database(),
+            "tinkoff_candles_1min"
+        );
 
         while !remaining_candles.is_empty() {
             // Ограничиваем размер пакета оставшимися элементами
@@ -178,4 +194,91 @@ impl CandleRepository for ClickhouseCandleRepository {
 
         Ok(successful_inserts)
     }
+
+    async fn get_candles(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<StoredCandle>, StorageError> {
+        let client = self.connection.get_client();
+        let query = format!(
+            "SELECT instrument_uid, toInt64(time) AS time, open_units, open_nano,
+                high_units, high_nano, low_units, low_nano, close_units, close_nano,
+                toInt64(volume) AS volume
+            FROM {}.tinkoff_candles_1min
+            WHERE instrument_uid = ? AND time >= {} AND time <= {}
+            ORDER BY time",
+            self.connection.get_database(),
+            from,
+            to
+        );
+
+        Ok(client
+            .query(&query)
+            .bind(instrument_uid)
+            .fetch_all::<StoredCandle>()
+            .await?)
+    }
+
+    async fn get_candles_at(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+        resolution: CandleResolution,
+    ) -> Result<Vec<StoredCandle>, StorageError> {
+        if resolution == CandleResolution::Minute {
+            return self.get_candles(instrument_uid, from, to).await;
+        }
+
+        // Таблицы свёрток заполняют материализованные представления; строки одной свечи
+        // до слияния частей досворачиваются здесь
+        let query = format!(
+            "SELECT instrument_uid, toInt64(bucket) AS time,
+                tupleElement(o, 1), tupleElement(o, 2), tupleElement(h, 1), tupleElement(h, 2),
+                tupleElement(l, 1), tupleElement(l, 2), tupleElement(c, 1), tupleElement(c, 2),
+                toInt64(v)
+            FROM (
+                SELECT instrument_uid, time AS bucket,
+                    argMinMerge(open) AS o, maxMerge(high) AS h, minMerge(low) AS l,
+                    argMaxMerge(close) AS c, sum(volume) AS v
+                FROM {}.{}
+                WHERE instrument_uid = ? AND time >= {} AND time <= {}
+                GROUP BY instrument_uid, bucket
+            )
+            ORDER BY bucket",
+            self.connection.get_database(),
+            resolution.table(),
+            resolution.bucket_start(from),
+            to
+        );
+
+        Ok(self
+            .connection
+            .get_client()
+            .query(&query)
+            .bind(instrument_uid)
+            .fetch_all::<StoredCandle>()
+            .await?)
+    }
+
+    async fn get_checkpoint(&self, instrument_uid: &str) -> Result<Option<i64>, StorageError> {
+        Ok(self.watchlist.get_checkpoint(instrument_uid).await?)
+    }
+
+    async fn save_checkpoint(
+        &self,
+        instrument_uid: &str,
+        last_candle_time: i64,
+    ) -> Result<(), StorageError> {
+        Ok(self
+            .watchlist
+            .update_checkpoint(instrument_uid, last_candle_time)
+            .await?)
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "clickhouse"
+    }
 }
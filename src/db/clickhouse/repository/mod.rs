@@ -1,5 +1,14 @@
 pub mod candle_repository;
 
-pub mod repository_share;
+pub(crate) mod helper;
+pub mod repository_candle_quarantine;
+pub mod repository_exclusion;
+pub mod repository_instrument_event;
 pub mod repository_my_instrument;
-mod helper;
\ No newline at end of file
+pub mod repository_orderbook;
+pub mod repository_reconciliation;
+pub mod repository_retention;
+pub mod repository_rollup;
+pub mod repository_share;
+pub mod repository_share_history;
+pub mod repository_source_divergence;
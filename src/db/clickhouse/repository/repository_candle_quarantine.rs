@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use clickhouse::error::Error as ClickhouseError;
+use tracing::{debug, error, info};
+
+use crate::db::clickhouse::{
+    connection::ClickhouseConnection, models::quarantined_candle::DbQuarantinedCandle,
+};
+
+const QUARANTINE_COLUMNS: &str = "instrument_uid, time, open_units, open_nano, high_units, high_nano, \
+     low_units, low_nano, close_units, close_nano, volume, reason, detected_at";
+
+pub struct CandleQuarantineRepository {
+    connection: Arc<ClickhouseConnection>,
+}
+
+fn opt<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "NULL".to_string(), |v| v.to_string())
+}
+
+impl CandleQuarantineRepository {
+    pub fn new(connection: Arc<ClickhouseConnection>) -> Self {
+        Self { connection }
+    }
+
+    pub async fn insert_candles(
+        &self,
+        candles: &[DbQuarantinedCandle],
+    ) -> Result<u64, ClickhouseError> {
+        if candles.is_empty() {
+            debug!("No quarantined candles to insert");
+            return Ok(0);
+        }
+
+        let client = self.connection.get_client();
+        let values_parts: Vec<String> = candles
+            .iter()
+            .map(|c| {
+                format!(
+                    "('{}', {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, '{}', {})",
+                    c.instrument_uid,
+                    c.time,
+                    opt(c.open_units),
+                    opt(c.open_nano),
+                    opt(c.high_units),
+                    opt(c.high_nano),
+                    opt(c.low_units),
+                    opt(c.low_nano),
+                    opt(c.close_units),
+                    opt(c.close_nano),
+                    c.volume,
+                    c.reason,
+                    c.detected_at,
+                )
+            })
+            .collect();
+
+        let sql = format!(
+            "INSERT INTO {}.tinkoff_candles_quarantine ({}) VALUES {}",
+            self.connection.get_database(),
+            QUARANTINE_COLUMNS,
+            values_parts.join(",")
+        );
+
+        match client.query(&sql).execute().await {
+            Ok(_) => {
+                info!("Quarantined {} candles", candles.len());
+                Ok(candles.len() as u64)
+            }
+            Err(e) => {
+                error!("Failed to insert quarantined candles: {}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Свечи в карантине за период [from, to] по времени свечи (секунды)
+    pub async fn get_candles(
+        &self,
+        from: i64,
+        to: i64,
+        instrument_uid: Option<&str>,
+        reason: Option<&str>,
+    ) -> Result<Vec<DbQuarantinedCandle>, ClickhouseError> {
+        let client = self.connection.get_client();
+        let uid_filter = if instrument_uid.is_some() {
+            "AND instrument_uid = ?"
+        } else {
+            ""
+        };
+        let reason_filter = if reason.is_some() {
+            "AND reason = ?"
+        } else {
+            ""
+        };
+        let query = format!(
+            "SELECT {} FROM {}.tinkoff_candles_quarantine
+            WHERE time >= {} AND time <= {} {} {}
+            ORDER BY instrument_uid, time",
+            QUARANTINE_COLUMNS,
+            self.connection.get_database(),
+            from,
+            to,
+            uid_filter,
+            reason_filter
+        );
+
+        let mut query = client.query(&query);
+        if let Some(uid) = instrument_uid {
+            query = query.bind(uid);
+        }
+        if let Some(reason) = reason {
+            query = query.bind(reason);
+        }
+        query.fetch_all::<DbQuarantinedCandle>().await
+    }
+}
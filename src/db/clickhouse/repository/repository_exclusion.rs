@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use clickhouse::error::Error as ClickhouseError;
+use tracing::info;
+
+use super::helper;
+use crate::db::clickhouse::{connection::ClickhouseConnection, models::exclusion::DbExclusion};
+
+pub struct ExclusionRepository {
+    connection: Arc<ClickhouseConnection>,
+}
+
+impl ExclusionRepository {
+    pub fn new(connection: Arc<ClickhouseConnection>) -> Self {
+        Self { connection }
+    }
+
+    /// Все неудалённые исключения, включая истёкшие
+    pub async fn get_exclusions(&self) -> Result<Vec<DbExclusion>, ClickhouseError> {
+        let client = self.connection.get_client();
+        let query = format!(
+            "SELECT key_type, key_value, reason, expires_at
+            FROM {}.instrument_exclusions FINAL
+            WHERE is_deleted = 0",
+            self.connection.get_database()
+        );
+
+        client.query(&query).fetch_all::<DbExclusion>().await
+    }
+
+    /// Добавляет исключение или заменяет существующее с тем же ключом
+    pub async fn upsert_exclusion(&self, exclusion: &DbExclusion) -> Result<(), ClickhouseError> {
+        let client = self.connection.get_client();
+        let query = format!(
+            "INSERT INTO {}.instrument_exclusions
+            (key_type, key_value, reason, expires_at, is_deleted, updated_at)
+            VALUES ('{}', '{}', '{}', {}, 0, now64(3))",
+            self.connection.get_database(),
+            exclusion.key_type,
+            helper::escape_string_max(&exclusion.key_value),
+            helper::escape_string_max(&exclusion.reason),
+            exclusion
+                .expires_at
+                .map_or_else(|| "NULL".to_string(), |t| t.to_string()),
+        );
+
+        info!(
+            "Saving exclusion {}={}: {}",
+            exclusion.key_type, exclusion.key_value, exclusion.reason
+        );
+        client.query(&query).execute().await
+    }
+
+    pub async fn delete_exclusion(
+        &self,
+        key_type: &str,
+        key_value: &str,
+    ) -> Result<(), ClickhouseError> {
+        let client = self.connection.get_client();
+        let query = format!(
+            "INSERT INTO {}.instrument_exclusions
+            (key_type, key_value, reason, expires_at, is_deleted, updated_at)
+            VALUES ('{}', '{}', '', NULL, 1, now64(3))",
+            self.connection.get_database(),
+            key_type,
+            helper::escape_string_max(key_value),
+        );
+
+        info!("Deleting exclusion {}={}", key_type, key_value);
+        client.query(&query).execute().await
+    }
+}
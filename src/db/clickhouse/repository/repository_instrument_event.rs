@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use clickhouse::error::Error as ClickhouseError;
+use tracing::{debug, error, info};
+
+use super::helper;
+use crate::db::clickhouse::{
+    connection::ClickhouseConnection, models::instrument_event::DbInstrumentEvent,
+};
+
+pub struct InstrumentEventRepository {
+    connection: Arc<ClickhouseConnection>,
+}
+
+impl InstrumentEventRepository {
+    pub fn new(connection: Arc<ClickhouseConnection>) -> Self {
+        Self { connection }
+    }
+
+    pub async fn insert_events(
+        &self,
+        events: &[DbInstrumentEvent],
+    ) -> Result<u64, ClickhouseError> {
+        if events.is_empty() {
+            debug!("No instrument events to insert");
+            return Ok(0);
+        }
+
+        let client = self.connection.get_client();
+        let table_name = format!("{}.{}", self.connection.get_database(), "instrument_events");
+
+        let values_parts: Vec<String> = events
+            .iter()
+            .map(|e| {
+                format!(
+                    "({}, '{}', '{}', '{}', '{}', '{}', '{}')",
+                    e.event_time,
+                    e.event_type,
+                    e.uid,
+                    e.figi,
+                    helper::escape_string_max(&e.ticker),
+                    helper::escape_string_max(&e.old_value),
+                    helper::escape_string_max(&e.new_value),
+                )
+            })
+            .collect();
+
+        let sql = format!(
+            "INSERT INTO {} (event_time, event_type, uid, figi, ticker, old_value, new_value) VALUES {}",
+            table_name,
+            values_parts.join(",")
+        );
+
+        match client.query(&sql).execute().await {
+            Ok(_) => {
+                info!("Inserted {} instrument events", events.len());
+                Ok(events.len() as u64)
+            }
+            Err(e) => {
+                error!("Failed to insert instrument events: {}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// События за период [from, to] (секунды), опционально по одному инструменту
+    pub async fn get_events(
+        &self,
+        from: i64,
+        to: i64,
+        uid: Option<&str>,
+    ) -> Result<Vec<DbInstrumentEvent>, ClickhouseError> {
+        let client = self.connection.get_client();
+        let uid_filter = if uid.is_some() { "AND uid = ?" } else { "" };
+        let query = format!(
+            "SELECT event_time, event_type, uid, figi, ticker, old_value, new_value
+            FROM {}.instrument_events
+            WHERE event_time >= {} AND event_time <= {} {}
+            ORDER BY event_time, uid",
+            self.connection.get_database(),
+            from,
+            to,
+            uid_filter
+        );
+
+        let mut query = client.query(&query);
+        if let Some(uid) = uid {
+            query = query.bind(uid);
+        }
+        query.fetch_all::<DbInstrumentEvent>().await
+    }
+}
@@ -1,10 +1,11 @@
 use std::sync::Arc;
 
 use clickhouse::error::Error as ClickhouseError;
-use tracing::info;
 
+use super::helper;
 use crate::db::clickhouse::{
-    connection::ClickhouseConnection, models::db_model_my_instrument::DbModelMyInstrument,
+    connection::ClickhouseConnection,
+    models::{db_model_my_instrument::DbModelMyInstrument, universe_change::DbUniverseChange},
 };
 
 pub struct RepositoryMyInstrument {
@@ -16,48 +17,52 @@ impl RepositoryMyInstrument {
         Self { connection }
     }
 
-    pub async fn get_my_instrument(&self) -> Result<Vec<DbModelMyInstrument>, ClickhouseError> {
+    /// Весь прежний список загрузки из instrument_candle_info
+    ///
+    /// Список и контрольные точки теперь хранятся в PostgreSQL, таблица читается
+    /// один раз — для переноса при первом запуске
+    pub async fn get_backfill_list(&self) -> Result<Vec<DbModelMyInstrument>, ClickhouseError> {
         let client = self.connection.get_client();
-        let database = self.connection.get_database();
         let query = format!(
-            "SELECT 
-                uid, 
-                 
-                first_1min_candle_date, 
-                last_1min_candle_date, 
-            FROM {}.instrument_candle_info 
-        ",
-            database
+            "SELECT uid, first_1min_candle_date, last_1min_candle_date, is_active
+            FROM {}.instrument_candle_info",
+            self.connection.get_database()
         );
-        let temp_rows = client
+
+        client
             .query(&query)
             .fetch_all::<DbModelMyInstrument>()
-            .await?;
-
-        return Ok(temp_rows);
+            .await
     }
-    pub async fn update_last_candle_date(
+
+    /// Записывает изменения списка загрузки в журнал universe_changes
+    pub async fn log_universe_changes(
         &self,
-        uid: &str,
-        last_date: i64,
-    ) -> Result<(), clickhouse::error::Error> {
-        let client = self.connection.get_client();
-        let database = self.connection.get_database();
+        changes: &[DbUniverseChange],
+    ) -> Result<(), ClickhouseError> {
+        if changes.is_empty() {
+            return Ok(());
+        }
 
+        let client = self.connection.get_client();
+        let values: Vec<String> = changes
+            .iter()
+            .map(|c| {
+                format!(
+                    "({}, '{}', '{}', '{}')",
+                    c.change_time,
+                    c.uid,
+                    helper::escape_string_max(&c.ticker),
+                    c.action
+                )
+            })
+            .collect();
         let query = format!(
-            "ALTER TABLE {}.instrument_candle_info UPDATE 
-            last_1min_candle_date = {}, 
-            update_time = now() 
-            WHERE uid = '{}'",
-            database, last_date, uid
-        );
-
-        info!(
-            "Updating last candle date for instrument {}: {}",
-            uid, last_date
+            "INSERT INTO {}.universe_changes (change_time, uid, ticker, action) VALUES {}",
+            self.connection.get_database(),
+            values.join(",")
         );
-        client.query(&query).execute().await?;
 
-        Ok(())
+        client.query(&query).execute().await
     }
 }
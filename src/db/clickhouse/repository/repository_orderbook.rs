@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use clickhouse::error::Error as ClickhouseError;
+use tracing::{debug, error, info};
+
+use super::helper;
+use crate::db::clickhouse::{
+    connection::ClickhouseConnection, models::orderbook::DbOrderBookSnapshot,
+};
+
+/// Строка VALUES для одного среза стакана
+fn snapshot_values(s: &DbOrderBookSnapshot) -> String {
+    // Массивы ClickHouse записываются литералами вида [1,2,3]
+    fn array<T: ToString>(values: &[T]) -> String {
+        let items: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        format!("[{}]", items.join(","))
+    }
+
+    format!(
+        "('{}', fromUnixTimestamp64Milli({}), {}, {}, {}, {}, {}, {}, {}, {})",
+        helper::escape_string_max(&s.instrument_uid),
+        s.time_ms,
+        s.depth,
+        if s.is_consistent { 1 } else { 0 },
+        array(&s.bids_price_units),
+        array(&s.bids_price_nano),
+        array(&s.bids_quantity),
+        array(&s.asks_price_units),
+        array(&s.asks_price_nano),
+        array(&s.asks_quantity),
+    )
+}
+
+/// Из срезов до и после `time_ms` выбирает ближайший; при равенстве — более ранний
+fn nearest_snapshot(
+    candidates: Vec<DbOrderBookSnapshot>,
+    time_ms: i64,
+) -> Option<DbOrderBookSnapshot> {
+    candidates
+        .into_iter()
+        .min_by_key(|s| ((s.time_ms - time_ms).abs(), s.time_ms))
+}
+
+pub struct OrderBookRepository {
+    connection: Arc<ClickhouseConnection>,
+}
+
+impl OrderBookRepository {
+    pub fn new(connection: Arc<ClickhouseConnection>) -> Self {
+        Self { connection }
+    }
+
+    /// Вставка пачки срезов стакана в таблицу tinkoff_orderbooks
+    ///
+    /// # Возвращает
+    /// * `Result<u64, ClickhouseError>` - Количество вставленных срезов или ошибку
+    pub async fn insert_snapshots(
+        &self,
+        snapshots: &[DbOrderBookSnapshot],
+    ) -> Result<u64, ClickhouseError> {
+        if snapshots.is_empty() {
+            debug!("No order book snapshots to insert");
+            return Ok(0);
+        }
+
+        let client = self.connection.get_client();
+        let table_name = format!(
+            "{}.{}",
+            self.connection.get_database(),
+            "tinkoff_orderbooks"
+        );
+
+        let values_parts: Vec<String> = snapshots.iter().map(snapshot_values).collect();
+
+        let sql = format!(
+            "INSERT INTO {}
+            (instrument_uid, time, depth, is_consistent,
+             bids_price_units, bids_price_nano, bids_quantity,
+             asks_price_units, asks_price_nano, asks_quantity)
+             VALUES {}",
+            table_name,
+            values_parts.join(",")
+        );
+
+        match client.query(&sql).execute().await {
+            Ok(_) => {
+                debug!("Inserted {} order book snapshots", snapshots.len());
+                Ok(snapshots.len() as u64)
+            }
+            Err(e) => {
+                error!("Failed to insert order book snapshots: {}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Возвращает срез стакана, ближайший по времени к `time_ms`
+    ///
+    /// Ищет ближайший срез не позже и не раньше заданного момента
+    /// и выбирает из двух тот, что ближе
+    pub async fn get_nearest(
+        &self,
+        instrument_uid: &str,
+        time_ms: i64,
+    ) -> Result<Option<DbOrderBookSnapshot>, ClickhouseError> {
+        let client = self.connection.get_client();
+        let database = self.connection.get_database();
+
+        let columns = "instrument_uid,
+                toUnixTimestamp64Milli(time) AS time_ms,
+                depth, is_consistent,
+                bids_price_units, bids_price_nano, bids_quantity,
+                asks_price_units, asks_price_nano, asks_quantity";
+
+        let query = format!(
+            r#"
+            (SELECT {columns}
+            FROM {database}.tinkoff_orderbooks
+            WHERE instrument_uid = ? AND time <= fromUnixTimestamp64Milli({time_ms})
+            ORDER BY time DESC
+            LIMIT 1)
+            UNION ALL
+            (SELECT {columns}
+            FROM {database}.tinkoff_orderbooks
+            WHERE instrument_uid = ? AND time >= fromUnixTimestamp64Milli({time_ms})
+            ORDER BY time ASC
+            LIMIT 1)
+        "#
+        );
+
+        info!(
+            "Fetching order book nearest to {} for instrument {}",
+            time_ms, instrument_uid
+        );
+
+        let candidates = client
+            .query(&query)
+            .bind(instrument_uid)
+            .bind(instrument_uid)
+            .fetch_all::<DbOrderBookSnapshot>()
+            .await?;
+        Ok(nearest_snapshot(candidates, time_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(uid: &str, time_ms: i64) -> DbOrderBookSnapshot {
+        DbOrderBookSnapshot {
+            instrument_uid: uid.to_string(),
+            time_ms,
+            depth: 1,
+            is_consistent: true,
+            bids_price_units: vec![100, 99],
+            bids_price_nano: vec![500_000_000, 0],
+            bids_quantity: vec![3, 7],
+            asks_price_units: vec![101],
+            asks_price_nano: vec![0],
+            asks_quantity: vec![2],
+        }
+    }
+
+    #[test]
+    fn escapes_instrument_uid_in_values() {
+        assert_eq!(
+            snapshot_values(&snapshot("uid-1", 1_700_000_000_123)),
+            "('uid-1', fromUnixTimestamp64Milli(1700000000123), 1, 1, \
+             [100,99], [500000000,0], [3,7], [101], [0], [2])"
+        );
+        let values = snapshot_values(&snapshot("x'); DROP TABLE t; --", 0));
+        assert!(values.starts_with("('x\\'); DROP TABLE t; --', "));
+    }
+
+    #[test]
+    fn picks_the_nearest_snapshot() {
+        let before = snapshot("uid-1", 1_000);
+        let after = snapshot("uid-1", 1_600);
+        let nearest = |candidates: Vec<DbOrderBookSnapshot>, time_ms| {
+            nearest_snapshot(candidates, time_ms).map(|s| s.time_ms)
+        };
+
+        assert_eq!(
+            nearest(vec![before.clone(), after.clone()], 1_200),
+            Some(1_000)
+        );
+        assert_eq!(
+            nearest(vec![before.clone(), after.clone()], 1_500),
+            Some(1_600)
+        );
+        // Equally far — the earlier book wins
+        assert_eq!(
+            nearest(vec![after.clone(), before.clone()], 1_300),
+            Some(1_000)
+        );
+        // Exact match comes back from both halves of the query
+        assert_eq!(
+            nearest(vec![after.clone(), after.clone()], 1_600),
+            Some(1_600)
+        );
+        assert_eq!(nearest(vec![after], 0), Some(1_600));
+        assert_eq!(nearest(Vec::new(), 1_000), None);
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use clickhouse::error::Error as ClickhouseError;
+use tracing::{debug, info};
+
+use super::helper;
+use crate::db::clickhouse::{
+    connection::ClickhouseConnection, models::reconciliation_mismatch::DbReconciliationMismatch,
+};
+
+const RECONCILIATION_COLUMNS: &str = "run_id, instrument_uid, day, fields, \
+     official_open, official_high, official_low, official_close, official_volume, \
+     stored_open, stored_high, stored_low, stored_close, stored_volume, refetch_queued, detected_at";
+
+fn opt<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "NULL".to_string(), |v| v.to_string())
+}
+
+/// Отчёт сверки с дневными свечами: таблица candle_reconciliation
+pub struct ReconciliationRepository {
+    connection: Arc<ClickhouseConnection>,
+}
+
+impl ReconciliationRepository {
+    pub fn new(connection: Arc<ClickhouseConnection>) -> Self {
+        Self { connection }
+    }
+
+    pub async fn insert_mismatches(
+        &self,
+        mismatches: &[DbReconciliationMismatch],
+    ) -> Result<u64, ClickhouseError> {
+        if mismatches.is_empty() {
+            debug!("No reconciliation mismatches to insert");
+            return Ok(0);
+        }
+
+        let values_parts: Vec<String> = mismatches
+            .iter()
+            .map(|m| {
+                format!(
+                    "('{}', '{}', {}, '{}', {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {})",
+                    m.run_id,
+                    helper::escape_string_max(&m.instrument_uid),
+                    m.day,
+                    m.fields,
+                    opt(m.official_open),
+                    opt(m.official_high),
+                    opt(m.official_low),
+                    opt(m.official_close),
+                    opt(m.official_volume),
+                    opt(m.stored_open),
+                    opt(m.stored_high),
+                    opt(m.stored_low),
+                    opt(m.stored_close),
+                    opt(m.stored_volume),
+                    m.refetch_queued as u8,
+                    m.detected_at,
+                )
+            })
+            .collect();
+
+        let sql = format!(
+            "INSERT INTO {}.candle_reconciliation ({}) VALUES {}",
+            self.connection.get_database(),
+            RECONCILIATION_COLUMNS,
+            values_parts.join(",")
+        );
+
+        self.connection.get_client().query(&sql).execute().await?;
+        info!("Recorded {} reconciliation mismatches", mismatches.len());
+        Ok(mismatches.len() as u64)
+    }
+
+    /// Расхождения за сутки в периоде [from, to] (секунды); по каждым суткам
+    /// инструмента — только результат последней сверки
+    pub async fn get_mismatches(
+        &self,
+        from: i64,
+        to: i64,
+        instrument_uid: Option<&str>,
+    ) -> Result<Vec<DbReconciliationMismatch>, ClickhouseError> {
+        let uid_filter = if instrument_uid.is_some() {
+            "AND instrument_uid = ?"
+        } else {
+            ""
+        };
+        let query = format!(
+            "SELECT run_id, instrument_uid, toInt64(day), fields,
+                official_open, official_high, official_low, official_close, official_volume,
+                stored_open, stored_high, stored_low, stored_close, stored_volume,
+                refetch_queued = 1, toInt64(detected_at)
+            FROM {}.candle_reconciliation
+            WHERE day >= {} AND day <= {} {}
+            ORDER BY day, instrument_uid, detected_at DESC
+            LIMIT 1 BY day, instrument_uid",
+            self.connection.get_database(),
+            from,
+            to,
+            uid_filter
+        );
+
+        let mut query = self.connection.get_client().query(&query);
+        if let Some(uid) = instrument_uid {
+            query = query.bind(uid);
+        }
+        query.fetch_all::<DbReconciliationMismatch>().await
+    }
+}
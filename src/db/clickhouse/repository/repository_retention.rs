@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use clickhouse::error::Error as ClickhouseError;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::helper;
+use super::repository_rollup::rollup_select;
+use crate::db::clickhouse::connection::ClickhouseConnection;
+use crate::env_config::models::retention::CandleResolution;
+
+/// Инструменты, к которым применяется политика хранения
+#[derive(Debug, Clone)]
+pub enum InstrumentGroup {
+    /// Только перечисленные инструменты
+    Only(Vec<String>),
+    /// Все, кроме инструментов со своей политикой
+    AllExcept(Vec<String>),
+}
+
+impl InstrumentGroup {
+    pub(crate) fn condition(&self) -> String {
+        let list = |uids: &[String]| {
+            uids.iter()
+                .map(|uid| format!("'{}'", helper::escape_string_max(uid)))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        match self {
+            InstrumentGroup::Only(uids) => format!("instrument_uid IN ({})", list(uids)),
+            InstrumentGroup::AllExcept(uids) if uids.is_empty() => "1".to_string(),
+            InstrumentGroup::AllExcept(uids) => format!("instrument_uid NOT IN ({})", list(uids)),
+        }
+    }
+}
+
+/// Что попадает под удаление: строки старше границы
+#[derive(Debug, Clone, Default, Serialize, Deserialize, clickhouse::Row)]
+pub struct DbRetentionPreview {
+    pub rows: u64,
+    pub instruments: u64,
+    pub oldest: i64, // Секунды; 0, если строк нет
+    pub newest: i64,
+}
+
+/// Обслуживание таблиц свечей: удаление старых строк и свёртка минутных свечей
+pub struct RetentionRepository {
+    connection: Arc<ClickhouseConnection>,
+}
+
+impl RetentionRepository {
+    pub fn new(connection: Arc<ClickhouseConnection>) -> Self {
+        Self { connection }
+    }
+
+    /// Строки таблицы разрешения старше `cutoff` (секунды)
+    pub async fn preview(
+        &self,
+        resolution: CandleResolution,
+        cutoff: i64,
+        group: &InstrumentGroup,
+    ) -> Result<DbRetentionPreview, ClickhouseError> {
+        let query = format!(
+            "SELECT count() AS rows, uniqExact(instrument_uid) AS instruments,
+                toInt64(min(time)) AS oldest, toInt64(max(time)) AS newest
+            FROM {}.{}
+            WHERE time < {} AND {}",
+            self.connection.get_database(),
+            resolution.table(),
+            cutoff,
+            group.condition()
+        );
+
+        self.connection
+            .get_client()
+            .query(&query)
+            .fetch_one::<DbRetentionPreview>()
+            .await
+    }
+
+    /// Число свечей `target`, которых ещё нет и которые появятся при свёртке минут до `cutoff`
+    pub async fn count_missing_rollups(
+        &self,
+        target: CandleResolution,
+        cutoff: i64,
+        group: &InstrumentGroup,
+    ) -> Result<u64, ClickhouseError> {
+        let query = format!(
+            "SELECT count() FROM (
+                SELECT DISTINCT instrument_uid, {bucket} AS bucket
+                FROM {db}.tinkoff_candles_1min
+                WHERE time < {cutoff} AND {group}
+                    AND (instrument_uid, {bucket}) NOT IN
+                        (SELECT instrument_uid, time FROM {db}.{target} WHERE time < {cutoff})
+            )",
+            bucket = target.bucket_expr("time"),
+            db = self.connection.get_database(),
+            target = target.table(),
+            cutoff = cutoff,
+            group = group.condition()
+        );
+
+        self.connection
+            .get_client()
+            .query(&query)
+            .fetch_one::<u64>()
+            .await
+    }
+
+    /// Сворачивает минутные свечи до `cutoff` в таблицу `target`
+    ///
+    /// Свечи, уже присутствующие в `target`, пропускаются: повторный запуск после
+    /// сбоя между свёрткой и удалением не удваивает объёмы. `cutoff` должен быть
+    /// выровнен по границе свечи `target`
+    pub async fn downsample(
+        &self,
+        target: CandleResolution,
+        cutoff: i64,
+        group: &InstrumentGroup,
+    ) -> Result<(), ClickhouseError> {
+        let database = self.connection.get_database();
+        let condition = format!(
+            "time < {cutoff} AND {group}
+                AND (instrument_uid, {bucket}) NOT IN
+                    (SELECT instrument_uid, time FROM {db}.{target} WHERE time < {cutoff})",
+            cutoff = cutoff,
+            group = group.condition(),
+            bucket = target.bucket_expr("time"),
+            db = database,
+            target = target.table()
+        );
+        let query = format!(
+            "INSERT INTO {}.{}
+                (instrument_uid, time, open, high, low, close, volume, candles)
+            {}",
+            database,
+            target.table(),
+            rollup_select(database, target, &condition)
+        );
+
+        info!(
+            "Downsampling 1min candles before {} into {}",
+            cutoff,
+            target.table()
+        );
+        self.connection.get_client().query(&query).execute().await
+    }
+
+    /// Удаляет строки таблицы разрешения старше `cutoff` (секунды)
+    pub async fn delete_before(
+        &self,
+        resolution: CandleResolution,
+        cutoff: i64,
+        group: &InstrumentGroup,
+    ) -> Result<(), ClickhouseError> {
+        let query = format!(
+            "DELETE FROM {}.{} WHERE time < {} AND {}",
+            self.connection.get_database(),
+            resolution.table(),
+            cutoff,
+            group.condition()
+        );
+
+        info!(
+            "Deleting candles before {} from {}",
+            cutoff,
+            resolution.table()
+        );
+        self.connection.get_client().query(&query).execute().await
+    }
+}
@@ -0,0 +1,225 @@
+use std::sync::Arc;
+
+use clickhouse::error::Error as ClickhouseError;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::repository_retention::InstrumentGroup;
+use crate::db::clickhouse::connection::ClickhouseConnection;
+use crate::env_config::models::retention::CandleResolution;
+
+/// SELECT, сворачивающий минутные свечи в свечи `target`; колонки совпадают с таблицами свёрток
+///
+/// Повторяет запрос материализованных представлений из миграции 0003, но читает
+/// минутные свечи через FINAL: незамёрженные дубли не удваивают объём
+pub(crate) fn rollup_select(database: &str, target: CandleResolution, condition: &str) -> String {
+    format!(
+        "SELECT
+            uid AS instrument_uid,
+            bucket AS time,
+            argMinState(o, ts),
+            maxState(h),
+            minState(l),
+            argMaxState(c, ts),
+            sum(v),
+            count()
+        FROM (
+            SELECT
+                instrument_uid AS uid,
+                {bucket} AS bucket,
+                toInt64(time) AS ts,
+                (open_units, open_nano) AS o,
+                (high_units, high_nano) AS h,
+                (low_units, low_nano) AS l,
+                (close_units, close_nano) AS c,
+                volume AS v
+            FROM {database}.tinkoff_candles_1min FINAL
+            WHERE {condition}
+        )
+        GROUP BY uid, bucket",
+        bucket = target.bucket_expr("time"),
+        database = database,
+        condition = condition
+    )
+}
+
+/// Свеча свёртки, расходящаяся с минутными свечами
+#[derive(Debug, Clone, Serialize, Deserialize, clickhouse::Row)]
+pub struct DbRollupMismatch {
+    pub instrument_uid: String,
+    pub time: i64, // Начало свечи свёртки, секунды
+    pub raw_candles: u64,
+    pub rollup_candles: u64,
+    pub raw_volume: i64,
+    pub rollup_volume: i64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, clickhouse::Row)]
+pub struct DbRollupCheckSummary {
+    pub buckets: u64,
+    pub mismatches: u64,
+}
+
+/// Таблицы свёрток 5min, 1hour и 1day, которые заполняют материализованные представления
+pub struct RollupRepository {
+    connection: Arc<ClickhouseConnection>,
+}
+
+impl RollupRepository {
+    pub fn new(connection: Arc<ClickhouseConnection>) -> Self {
+        Self { connection }
+    }
+
+    /// Время первой и последней минутной свечи (секунды); None, если таблица пуста
+    pub async fn raw_range(
+        &self,
+        group: &InstrumentGroup,
+    ) -> Result<Option<(i64, i64)>, ClickhouseError> {
+        let query = format!(
+            "SELECT count(), toInt64(min(time)), toInt64(max(time))
+            FROM {}.tinkoff_candles_1min
+            WHERE {}",
+            self.connection.get_database(),
+            group.condition()
+        );
+
+        let (rows, first, last) = self
+            .connection
+            .get_client()
+            .query(&query)
+            .fetch_one::<(u64, i64, i64)>()
+            .await?;
+        Ok((rows > 0).then_some((first, last)))
+    }
+
+    /// Пересчитывает свечи `target` за период [from, to) из минутных свечей
+    ///
+    /// Старые строки свёртки удаляются, затем вставляются заново, поэтому
+    /// повторный запуск не удваивает объёмы. Границы должны быть выровнены
+    /// по свече `target`, а период не должен захватывать загружаемые сейчас минуты
+    pub async fn rebuild(
+        &self,
+        target: CandleResolution,
+        from: i64,
+        to: i64,
+        group: &InstrumentGroup,
+    ) -> Result<(), ClickhouseError> {
+        let database = self.connection.get_database();
+        let condition = format!(
+            "time >= {} AND time < {} AND {}",
+            from,
+            to,
+            group.condition()
+        );
+        let client = self.connection.get_client();
+
+        client
+            .query(&format!(
+                "DELETE FROM {}.{} WHERE {}",
+                database,
+                target.table(),
+                condition
+            ))
+            .execute()
+            .await?;
+
+        info!(
+            "Rebuilding {} for [{}, {}) from 1min candles",
+            target.table(),
+            from,
+            to
+        );
+        client
+            .query(&format!(
+                "INSERT INTO {}.{}
+                    (instrument_uid, time, open, high, low, close, volume, candles)
+                {}",
+                database,
+                target.table(),
+                rollup_select(database, target, &condition)
+            ))
+            .execute()
+            .await
+    }
+
+    fn check_join(
+        &self,
+        target: CandleResolution,
+        from: i64,
+        to: i64,
+        group: &InstrumentGroup,
+    ) -> String {
+        format!(
+            "(
+                SELECT instrument_uid, {bucket} AS bucket,
+                    count() AS raw_candles, toInt64(sum(volume)) AS raw_volume
+                FROM {db}.tinkoff_candles_1min FINAL
+                WHERE time >= {from} AND time < {to} AND {group}
+                GROUP BY instrument_uid, bucket
+            ) AS raw
+            FULL OUTER JOIN (
+                SELECT instrument_uid, time AS bucket,
+                    toUInt64(sum(candles)) AS rollup_candles, toInt64(sum(volume)) AS rollup_volume
+                FROM {db}.{target}
+                WHERE time >= {from} AND time < {to} AND {group}
+                GROUP BY instrument_uid, bucket
+            ) AS rollup USING (instrument_uid, bucket)",
+            bucket = target.bucket_expr("time"),
+            db = self.connection.get_database(),
+            target = target.table(),
+            from = from,
+            to = to,
+            group = group.condition()
+        )
+    }
+
+    /// Сколько свечей `target` за период [from, to) проверено и сколько расходится
+    /// с минутными свечами по числу минут или объёму
+    pub async fn check_summary(
+        &self,
+        target: CandleResolution,
+        from: i64,
+        to: i64,
+        group: &InstrumentGroup,
+    ) -> Result<DbRollupCheckSummary, ClickhouseError> {
+        let query = format!(
+            "SELECT count() AS buckets,
+                countIf(raw_candles != rollup_candles OR raw_volume != rollup_volume) AS mismatches
+            FROM {}",
+            self.check_join(target, from, to, group)
+        );
+
+        self.connection
+            .get_client()
+            .query(&query)
+            .fetch_one::<DbRollupCheckSummary>()
+            .await
+    }
+
+    /// Первые `limit` расхождений свёртки `target` с минутными свечами за период [from, to)
+    pub async fn find_mismatches(
+        &self,
+        target: CandleResolution,
+        from: i64,
+        to: i64,
+        group: &InstrumentGroup,
+        limit: u64,
+    ) -> Result<Vec<DbRollupMismatch>, ClickhouseError> {
+        let query = format!(
+            "SELECT instrument_uid, toInt64(bucket) AS time,
+                raw_candles, rollup_candles, raw_volume, rollup_volume
+            FROM {}
+            WHERE raw_candles != rollup_candles OR raw_volume != rollup_volume
+            ORDER BY instrument_uid, bucket
+            LIMIT {}",
+            self.check_join(target, from, to, group),
+            limit
+        );
+
+        self.connection
+            .get_client()
+            .query(&query)
+            .fetch_all::<DbRollupMismatch>()
+            .await
+    }
+}
@@ -1,7 +1,10 @@
 use super::helper;
 use crate::{
-    db::clickhouse::{connection::ClickhouseConnection, models::db_liquid_shares::DbLiquidShares},
-    generate::tinkoff_public_invest_api_contract_v1::Share,
+    db::clickhouse::{
+        connection::ClickhouseConnection,
+        models::{db_liquid_shares::DbLiquidShares, share_listing::DbShareListing},
+    },
+    generate::tinkoff_public_invest_api_contract_v1::{RealExchange, Share},
 };
 
 use chrono::{FixedOffset, TimeZone, Utc};
@@ -13,101 +16,34 @@ use tracing::{debug, error, info, warn};
 
 pub struct ShareRepository {
     connection: Arc<ClickhouseConnection>,
-    // Список FIGI, которые следует пропускать при обработке
-    problematic_figis: HashSet<String>,
 }
 
 impl ShareRepository {
     pub fn new(connection: Arc<ClickhouseConnection>) -> Self {
-        // Инициализируем список проблемных FIGI
-        let mut problematic_figis = HashSet::new();
-        // Добавляем известные проблемные FIGI
-        problematic_figis.insert("BBG000BC26P7".to_string());
-        // Можно добавить больше проблемных FIGI по мере их обнаружения
-
-        Self {
-            connection,
-            problematic_figis,
-        }
-    }
-    pub async fn delete_all_shares(&self) -> Result<u64, ClickhouseError> {
-        let client = self.connection.get_client();
-
-        // Get the fully qualified table name
-        let table_name = format!("{}.{}", self.connection.get_database(), "tinkoff_shares");
-
-        // Create the delete query
-        let sql = format!("TRUNCATE TABLE {}", table_name);
-
-        info!("Truncating shares table before update");
-
-        // Execute the query
-        match client.query(&sql).execute().await {
-            Ok(_) => {
-                info!("Successfully cleared shares table for fresh update");
-                Ok(1) // Return 1 to indicate success
-            }
-            Err(e) => {
-                error!("Failed to clear shares table: {}", e);
-                Err(e)
-            }
-        }
-    }
-    // Метод для проверки, является ли FIGI проблемным
-    fn is_problematic_figi(&self, figi: &str) -> bool {
-        self.problematic_figis.contains(figi)
+        Self { connection }
     }
 
-    pub async fn insert_shares(
-        &self,
-        shares: &[Share],
-        clean_first: bool,
-    ) -> Result<u64, ClickhouseError> {
+    /// Вставка актуального среза каталога в tinkoff_shares
+    ///
+    /// Таблица tinkoff_shares — ReplacingMergeTree по uid, поэтому повторная
+    /// вставка заменяет прежние строки без TRUNCATE и читатели никогда не видят
+    /// пустую таблицу. Читать её нужно через FINAL; акции, пропавшие из каталога,
+    /// удаляет `remove_delisted`.
+    /// Исключённые инструменты должны быть отфильтрованы вызывающим кодом
+    pub async fn insert_shares(&self, shares: &[&Share]) -> Result<u64, ClickhouseError> {
         if shares.is_empty() {
             debug!("No shares to insert");
             return Ok(0);
         }
 
-        // Optionally clean the table before inserting
-        if clean_first {
-            match self.delete_all_shares().await {
-                Ok(_) => info!("Preparing for fresh data insert"),
-                Err(e) => {
-                    error!("Failed to clean shares table before insert: {}", e);
-                    // Continue with insert even if cleanup fails
-                }
-            }
-        }
-
         let client = self.connection.get_client();
         let total_count = shares.len();
 
         info!("Starting insertion of {} shares", total_count);
 
-        // Фильтруем проблемные FIGI при создании списка
-        let filtered_shares: Vec<&Share> = shares
-            .iter()
-            .filter(|share| {
-                let is_problematic = self.is_problematic_figi(&share.figi);
-                if is_problematic {
-                    info!(
-                        "Skipping known problematic share: FIGI={}, Name='{}', Ticker='{}'",
-                        share.figi, share.name, share.ticker
-                    );
-                }
-                !is_problematic
-            })
-            .collect();
-
-        let filtered_count = filtered_shares.len();
-        if filtered_count == 0 {
-            info!("No valid shares to insert after filtering");
-            return Ok(0);
-        }
-
         // Формируем части VALUES для SQL запроса вставки
-        let mut values_parts = Vec::with_capacity(filtered_count);
-        for share in &filtered_shares {
+        let mut values_parts = Vec::with_capacity(total_count);
+        for share in shares {
             debug!(
                 "Preparing share: FIGI={}, Name='{}', Ticker='{}'",
                 share.figi, share.name, share.ticker
@@ -128,15 +64,9 @@ impl ShareRepository {
         // Выполняем вставку
         match client.query(&sql).execute().await {
             Ok(_) => {
-                info!(
-                    "Successfully inserted {} shares (out of {} total)",
-                    filtered_count, total_count
-                );
+                info!("Successfully inserted {} shares", total_count);
 
-                // After inserting shares, now update the liquid_shares table
-                self.update_liquid_shares().await?;
-
-                Ok(filtered_count as u64)
+                Ok(total_count as u64)
             }
             Err(e) => {
                 error!("Insertion failed: {}", e);
@@ -145,113 +75,151 @@ impl ShareRepository {
         }
     }
 
-    // Updated method to match the new table structure
-    async fn update_liquid_shares(&self) -> Result<u64, ClickhouseError> {
+    /// Удаляет из tinkoff_shares акции, которых нет в актуальном каталоге
+    ///
+    /// Делистинг остаётся в tinkoff_shares_history, где версия закрывается valid_to.
+    /// Пустой каталог считается сбоем API и ничего не удаляет
+    pub async fn remove_delisted(&self, listed: &[&Share]) -> Result<u64, ClickhouseError> {
+        if listed.is_empty() {
+            warn!("Share catalog is empty, delisted shares are not removed");
+            return Ok(0);
+        }
+
         let client = self.connection.get_client();
         let database = self.connection.get_database();
+        let uids: Vec<String> = listed
+            .iter()
+            .map(|share| format!("'{}'", helper::escape_string_max(&share.uid)))
+            .collect();
+        let condition = format!("uid NOT IN ({})", uids.join(","));
+
+        let delisted = client
+            .query(&format!(
+                "SELECT count() FROM {}.tinkoff_shares FINAL WHERE {}",
+                database, condition
+            ))
+            .fetch_one::<u64>()
+            .await?;
+        if delisted == 0 {
+            debug!("No delisted shares in tinkoff_shares");
+            return Ok(0);
+        }
 
-        // 1. Insert currently liquid shares with is_liquid_now=true
-        let insert_query = format!(
-            r#"
-        INSERT INTO {0}.liquid_shares (uid, is_liquid_now)
-        SELECT 
-            uid,
-            true
-        FROM {0}.tinkoff_shares
-        WHERE buy_available_flag = 1
-          AND sell_available_flag = 1
-          AND first_1min_candle_date IS NOT NULL
-        "#,
-            database
-        );
+        match client
+            .query(&format!(
+                "DELETE FROM {}.tinkoff_shares WHERE {}",
+                database, condition
+            ))
+            .execute()
+            .await
+        {
+            Ok(_) => {
+                info!("Removed {} delisted shares from tinkoff_shares", delisted);
+                Ok(delisted)
+            }
+            Err(e) => {
+                error!("Failed to remove delisted shares: {}", e);
+                Err(e)
+            }
+        }
+    }
 
-        info!("Inserting liquid shares (ReplacingMergeTree engine will handle duplicates)");
+    /// Обновляет liquid_shares по набору uid, отобранному правилами вселенной
+    ///
+    /// uid из набора записываются с is_liquid_now=true, а ранее ликвидные uid,
+    /// не попавшие в набор, — с is_liquid_now=false (ReplacingMergeTree оставит последнюю запись)
+    pub async fn update_liquid_shares(
+        &self,
+        universe_uids: &[String],
+    ) -> Result<u64, ClickhouseError> {
+        let client = self.connection.get_client();
+        let database = self.connection.get_database();
 
-        match client.query(&insert_query).execute().await {
-            Ok(_) => {
-                info!("Successfully inserted currently liquid shares");
-
-                // 2. Get a list of UIDs that are currently in liquid_shares table but are not in current liquid shares
-                let get_old_uids_query = format!(
-                    r#"
-                SELECT uid 
-                FROM {0}.liquid_shares
-                WHERE uid NOT IN (
-                    SELECT uid
-                    FROM {0}.tinkoff_shares
-                    WHERE buy_available_flag = 1
-                      AND sell_available_flag = 1
-                      AND first_1min_candle_date IS NOT NULL
-                )
-                "#,
-                    database
-                );
-
-                info!("Finding shares that are no longer liquid");
-
-                // Execute the query to get a list of UIDs that need to be updated to is_liquid_now=false
-                #[derive(Debug, serde::Deserialize, clickhouse::Row)]
-                struct UidRecord {
-                    uid: String,
-                }
+        // 1. Insert currently liquid shares with is_liquid_now=true
+        if !universe_uids.is_empty() {
+            let values: Vec<String> = universe_uids
+                .iter()
+                .map(|uid| format!("('{}', true)", helper::escape_string_max(uid)))
+                .collect();
+            let insert_query = format!(
+                "INSERT INTO {}.liquid_shares (uid, is_liquid_now) VALUES {}",
+                database,
+                values.join(",")
+            );
 
-                let old_uids = match client
-                    .query(&get_old_uids_query)
-                    .fetch_all::<UidRecord>()
-                    .await
-                {
-                    Ok(uids) => {
-                        info!("Found {} shares that are no longer liquid", uids.len());
-                        uids
-                    }
-                    Err(e) => {
-                        error!("Failed to find shares that are no longer liquid: {}", e);
-                        return Err(e);
-                    }
-                };
-
-                // 3. For each UID that is no longer liquid, insert a record with is_liquid_now=false
-                for uid_record in old_uids {
-                    let update_query = format!(
-                        r#"
-                    INSERT INTO {0}.liquid_shares (uid, is_liquid_now)
-                    VALUES ('{1}', false)
-                    "#,
-                        database, uid_record.uid
-                    );
-
-                    match client.query(&update_query).execute().await {
-                        Ok(_) => {
-                            debug!("Updated share {} to is_liquid_now=false", uid_record.uid);
-                        }
-                        Err(e) => {
-                            error!(
-                                "Failed to update share {} to is_liquid_now=false: {}",
-                                uid_record.uid, e
-                            );
-                            // Continue with other UIDs instead of failing the entire process
-                        }
-                    }
-                }
+            info!(
+                "Inserting {} liquid shares (ReplacingMergeTree engine will handle duplicates)",
+                universe_uids.len()
+            );
 
-                info!("Completed updating shares that are no longer liquid");
+            if let Err(e) = client.query(&insert_query).execute().await {
+                error!("Failed to insert liquid shares: {}", e);
+                return Err(e);
+            }
+        }
 
-                // 4. Force a merge to ensure the latest versions are visible
-                let optimize_query = format!("OPTIMIZE TABLE {0}.liquid_shares FINAL", database);
-                info!("Optimizing table to ensure latest versions are visible");
+        // 2. Find shares that are marked liquid but are no longer in the universe
+        #[derive(Debug, serde::Deserialize, clickhouse::Row)]
+        struct UidRecord {
+            uid: String,
+        }
 
-                match client.query(&optimize_query).execute().await {
-                    Ok(_) => info!("Successfully optimized liquid_shares table"),
-                    Err(e) => warn!("Failed to optimize table: {}", e),
-                }
+        let get_liquid_query = format!(
+            "SELECT uid FROM {}.liquid_shares FINAL WHERE is_liquid_now = true",
+            database
+        );
 
-                Ok(1) // Return 1 to indicate success
-            }
+        info!("Finding shares that are no longer liquid");
+
+        let universe: HashSet<&str> = universe_uids.iter().map(|uid| uid.as_str()).collect();
+        let old_uids: Vec<String> = match client
+            .query(&get_liquid_query)
+            .fetch_all::<UidRecord>()
+            .await
+        {
+            Ok(records) => records
+                .into_iter()
+                .map(|record| record.uid)
+                .filter(|uid| !universe.contains(uid.as_str()))
+                .collect(),
             Err(e) => {
-                error!("Failed to insert liquid shares: {}", e);
-                Err(e)
+                error!("Failed to find shares that are no longer liquid: {}", e);
+                return Err(e);
+            }
+        };
+        info!("Found {} shares that are no longer liquid", old_uids.len());
+
+        // 3. Insert a record with is_liquid_now=false for each of them
+        if !old_uids.is_empty() {
+            let values: Vec<String> = old_uids
+                .iter()
+                .map(|uid| format!("('{}', false)", helper::escape_string_max(uid)))
+                .collect();
+            let update_query = format!(
+                "INSERT INTO {}.liquid_shares (uid, is_liquid_now) VALUES {}",
+                database,
+                values.join(",")
+            );
+
+            match client.query(&update_query).execute().await {
+                Ok(_) => debug!("Updated {} shares to is_liquid_now=false", old_uids.len()),
+                Err(e) => {
+                    error!("Failed to update shares to is_liquid_now=false: {}", e);
+                    return Err(e);
+                }
             }
         }
+
+        // 4. Force a merge to ensure the latest versions are visible
+        let optimize_query = format!("OPTIMIZE TABLE {0}.liquid_shares FINAL", database);
+        info!("Optimizing table to ensure latest versions are visible");
+
+        match client.query(&optimize_query).execute().await {
+            Ok(_) => info!("Successfully optimized liquid_shares table"),
+            Err(e) => warn!("Failed to optimize table: {}", e),
+        }
+
+        Ok(universe_uids.len() as u64)
     }
 
     pub async fn get_liquid_shares(&self) -> Result<Vec<DbLiquidShares>, ClickhouseError> {
@@ -297,4 +265,21 @@ impl ShareRepository {
 
         Ok(result)
     }
+
+    /// Акции Московской биржи из актуального каталога: тикер, режим торгов и лот
+    pub async fn get_moex_listings(&self) -> Result<Vec<DbShareListing>, ClickhouseError> {
+        let query = format!(
+            "SELECT uid, ticker, class_code, lot
+            FROM {}.tinkoff_shares FINAL
+            WHERE real_exchange = {} AND ticker != '' AND class_code != ''",
+            self.connection.get_database(),
+            RealExchange::Moex as i32
+        );
+
+        self.connection
+            .get_client()
+            .query(&query)
+            .fetch_all::<DbShareListing>()
+            .await
+    }
 }
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use clickhouse::error::Error as ClickhouseError;
+use tracing::{debug, error, info};
+
+use super::helper;
+use crate::db::clickhouse::{
+    connection::ClickhouseConnection, models::share_version::DbShareVersion,
+};
+
+/// Колонки таблицы tinkoff_shares_history в порядке полей DbShareVersion
+const HISTORY_COLUMNS: &str = "uid, figi, ticker, class_code, name, exchange, currency,
+    lot, trading_status, short_enabled_flag, otc_flag,
+    buy_available_flag, sell_available_flag, div_yield_flag,
+    api_trade_available_flag, for_iis_flag, for_qual_investor_flag,
+    weekend_flag, blocked_tca_flag, liquidity_flag,
+    min_price_increment_units, min_price_increment_nano,
+    valid_from, valid_to";
+
+/// История каталога акций (SCD type 2)
+///
+/// Таблица tinkoff_shares_history — ReplacingMergeTree по (uid, valid_from)
+/// с версией updated_at: закрытие версии записывается повторной вставкой
+/// той же строки с заполненным valid_to, поэтому чтение идёт через FINAL
+pub struct ShareHistoryRepository {
+    connection: Arc<ClickhouseConnection>,
+}
+
+impl ShareHistoryRepository {
+    pub fn new(connection: Arc<ClickhouseConnection>) -> Self {
+        Self { connection }
+    }
+
+    /// Актуальные версии всех инструментов каталога
+    pub async fn get_current_versions(&self) -> Result<Vec<DbShareVersion>, ClickhouseError> {
+        let client = self.connection.get_client();
+        let query = format!(
+            "SELECT {} FROM {}.tinkoff_shares_history FINAL WHERE valid_to IS NULL",
+            HISTORY_COLUMNS,
+            self.connection.get_database()
+        );
+
+        client.query(&query).fetch_all::<DbShareVersion>().await
+    }
+
+    /// Состояние каталога на момент `timestamp` (секунды)
+    pub async fn get_catalog_as_of(
+        &self,
+        timestamp: i64,
+    ) -> Result<Vec<DbShareVersion>, ClickhouseError> {
+        let client = self.connection.get_client();
+        let query = format!(
+            "SELECT {0} FROM {1}.tinkoff_shares_history FINAL
+            WHERE valid_from <= {2} AND (valid_to IS NULL OR valid_to > {2})
+            ORDER BY ticker",
+            HISTORY_COLUMNS,
+            self.connection.get_database(),
+            timestamp
+        );
+
+        info!("Fetching share catalog as of {}", timestamp);
+        client.query(&query).fetch_all::<DbShareVersion>().await
+    }
+
+    /// Вставка новых и закрытых версий
+    pub async fn insert_versions(
+        &self,
+        versions: &[DbShareVersion],
+    ) -> Result<u64, ClickhouseError> {
+        if versions.is_empty() {
+            debug!("No share versions to insert");
+            return Ok(0);
+        }
+
+        let client = self.connection.get_client();
+        let table_name = format!(
+            "{}.{}",
+            self.connection.get_database(),
+            "tinkoff_shares_history"
+        );
+
+        fn flag(value: bool) -> u8 {
+            if value { 1 } else { 0 }
+        }
+
+        let values_parts: Vec<String> = versions
+            .iter()
+            .map(|v| {
+                format!(
+                    "('{}', '{}', '{}', '{}', '{}', '{}', '{}',
+                    {}, '{}', {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {},
+                    {}, {}, {}, {})",
+                    helper::escape_string_max(&v.uid),
+                    helper::escape_string_max(&v.figi),
+                    helper::escape_string_max(&v.ticker),
+                    helper::escape_string_max(&v.class_code),
+                    helper::escape_string_max(&v.name),
+                    helper::escape_string_max(&v.exchange),
+                    helper::escape_string_max(&v.currency),
+                    v.lot,
+                    helper::escape_string_max(&v.trading_status),
+                    flag(v.short_enabled_flag),
+                    flag(v.otc_flag),
+                    flag(v.buy_available_flag),
+                    flag(v.sell_available_flag),
+                    flag(v.div_yield_flag),
+                    flag(v.api_trade_available_flag),
+                    flag(v.for_iis_flag),
+                    flag(v.for_qual_investor_flag),
+                    flag(v.weekend_flag),
+                    flag(v.blocked_tca_flag),
+                    flag(v.liquidity_flag),
+                    v.min_price_increment_units,
+                    v.min_price_increment_nano,
+                    v.valid_from,
+                    v.valid_to
+                        .map_or_else(|| "NULL".to_string(), |t| t.to_string()),
+                )
+            })
+            .collect();
+
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES {}",
+            table_name,
+            HISTORY_COLUMNS,
+            values_parts.join(",")
+        );
+
+        match client.query(&sql).execute().await {
+            Ok(_) => {
+                info!("Inserted {} share catalog versions", versions.len());
+                Ok(versions.len() as u64)
+            }
+            Err(e) => {
+                error!("Failed to insert share catalog versions: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use clickhouse::error::Error as ClickhouseError;
+use tracing::{debug, info};
+
+use super::helper;
+use crate::db::clickhouse::{
+    connection::ClickhouseConnection, models::source_divergence::DbSourceDivergence,
+};
+
+const DIVERGENCE_COLUMNS: &str = "run_id, instrument_uid, ticker, resolution, time, fields, \
+     tinkoff_open, tinkoff_high, tinkoff_low, tinkoff_close, tinkoff_volume, \
+     iss_open, iss_high, iss_low, iss_close, iss_volume, detected_at";
+
+fn opt<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "NULL".to_string(), |v| v.to_string())
+}
+
+/// Отчёт сравнения с MOEX ISS: таблица source_divergences
+pub struct SourceDivergenceRepository {
+    connection: Arc<ClickhouseConnection>,
+}
+
+impl SourceDivergenceRepository {
+    pub fn new(connection: Arc<ClickhouseConnection>) -> Self {
+        Self { connection }
+    }
+
+    pub async fn insert_divergences(
+        &self,
+        divergences: &[DbSourceDivergence],
+    ) -> Result<u64, ClickhouseError> {
+        if divergences.is_empty() {
+            debug!("No source divergences to insert");
+            return Ok(0);
+        }
+
+        let values_parts: Vec<String> = divergences
+            .iter()
+            .map(|d| {
+                format!(
+                    "('{}', '{}', '{}', '{}', {}, '{}', {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {})",
+                    d.run_id,
+                    helper::escape_string_max(&d.instrument_uid),
+                    helper::escape_string_max(&d.ticker),
+                    d.resolution,
+                    d.time,
+                    d.fields,
+                    opt(d.tinkoff_open),
+                    opt(d.tinkoff_high),
+                    opt(d.tinkoff_low),
+                    opt(d.tinkoff_close),
+                    opt(d.tinkoff_volume),
+                    opt(d.iss_open),
+                    opt(d.iss_high),
+                    opt(d.iss_low),
+                    opt(d.iss_close),
+                    opt(d.iss_volume),
+                    d.detected_at,
+                )
+            })
+            .collect();
+
+        let sql = format!(
+            "INSERT INTO {}.source_divergences ({}) VALUES {}",
+            self.connection.get_database(),
+            DIVERGENCE_COLUMNS,
+            values_parts.join(",")
+        );
+
+        self.connection.get_client().query(&sql).execute().await?;
+        info!("Recorded {} source divergences", divergences.len());
+        Ok(divergences.len() as u64)
+    }
+
+    /// Расхождения свечей в периоде [from, to] (секунды); по каждой свече
+    /// инструмента — только результат последнего сравнения
+    pub async fn get_divergences(
+        &self,
+        from: i64,
+        to: i64,
+        instrument_uid: Option<&str>,
+    ) -> Result<Vec<DbSourceDivergence>, ClickhouseError> {
+        let uid_filter = if instrument_uid.is_some() {
+            "AND instrument_uid = ?"
+        } else {
+            ""
+        };
+        let query = format!(
+            "SELECT run_id, instrument_uid, ticker, resolution, toInt64(time), fields,
+                tinkoff_open, tinkoff_high, tinkoff_low, tinkoff_close, tinkoff_volume,
+                iss_open, iss_high, iss_low, iss_close, iss_volume, toInt64(detected_at)
+            FROM {}.source_divergences
+            WHERE time >= {} AND time <= {} {}
+            ORDER BY time, instrument_uid, resolution, detected_at DESC
+            LIMIT 1 BY time, instrument_uid, resolution",
+            self.connection.get_database(),
+            from,
+            to,
+            uid_filter
+        );
+
+        let mut query = self.connection.get_client().query(&query);
+        if let Some(uid) = instrument_uid {
+            query = query.bind(uid);
+        }
+        query.fetch_all::<DbSourceDivergence>().await
+    }
+}
@@ -0,0 +1,507 @@
+use clickhouse::{Client, error::Error as ClickhouseError};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use tracing::info;
+
+/// Миграция схемы ClickHouse, встроенная в бинарник
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// Все миграции по возрастанию версии; применённую миграцию менять нельзя,
+/// изменения схемы оформляются новым файлом
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial",
+        sql: include_str!("../../../migrations/clickhouse/0001_initial.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "candle_rollups",
+        sql: include_str!("../../../migrations/clickhouse/0002_candle_rollups.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "rollup_views",
+        sql: include_str!("../../../migrations/clickhouse/0003_rollup_views.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "candle_reconciliation",
+        sql: include_str!("../../../migrations/clickhouse/0004_candle_reconciliation.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "source_divergences",
+        sql: include_str!("../../../migrations/clickhouse/0005_source_divergences.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "shares_replacing",
+        sql: include_str!("../../../migrations/clickhouse/0006_shares_replacing.sql"),
+    },
+];
+
+/// Колонки, на которые опирается код; проверяются при запуске
+const REQUIRED_COLUMNS: &[(&str, &[&str])] = &[
+    (
+        "tinkoff_candles_1min",
+        &[
+            "instrument_uid",
+            "time",
+            "open_units",
+            "open_nano",
+            "high_units",
+            "high_nano",
+            "low_units",
+            "low_nano",
+            "close_units",
+            "close_nano",
+            "volume",
+        ],
+    ),
+    (
+        "tinkoff_candles_quarantine",
+        &["instrument_uid", "time", "volume", "reason", "detected_at"],
+    ),
+    (
+        "tinkoff_shares",
+        &[
+            "uid",
+            "figi",
+            "ticker",
+            "first_1min_candle_date",
+            "first_1day_candle_date",
+        ],
+    ),
+    (
+        "tinkoff_shares_history",
+        &["uid", "figi", "ticker", "valid_from", "valid_to"],
+    ),
+    ("liquid_shares", &["uid", "is_liquid_now"]),
+    (
+        "instrument_candle_info",
+        &[
+            "uid",
+            "first_1min_candle_date",
+            "last_1min_candle_date",
+            "is_active",
+        ],
+    ),
+    (
+        "universe_changes",
+        &["change_time", "uid", "ticker", "action"],
+    ),
+    (
+        "instrument_events",
+        &[
+            "event_time",
+            "event_type",
+            "uid",
+            "figi",
+            "ticker",
+            "old_value",
+            "new_value",
+        ],
+    ),
+    (
+        "instrument_exclusions",
+        &[
+            "key_type",
+            "key_value",
+            "reason",
+            "expires_at",
+            "is_deleted",
+            "updated_at",
+        ],
+    ),
+    (
+        "tinkoff_orderbooks",
+        &[
+            "instrument_uid",
+            "time",
+            "depth",
+            "is_consistent",
+            "bids_price_units",
+            "asks_price_units",
+        ],
+    ),
+    (
+        "tinkoff_candles_5min",
+        &[
+            "instrument_uid",
+            "time",
+            "open",
+            "high",
+            "low",
+            "close",
+            "volume",
+            "candles",
+        ],
+    ),
+    (
+        "tinkoff_candles_1hour",
+        &[
+            "instrument_uid",
+            "time",
+            "open",
+            "high",
+            "low",
+            "close",
+            "volume",
+            "candles",
+        ],
+    ),
+    (
+        "tinkoff_candles_1day",
+        &[
+            "instrument_uid",
+            "time",
+            "open",
+            "high",
+            "low",
+            "close",
+            "volume",
+            "candles",
+        ],
+    ),
+    (
+        "candle_reconciliation",
+        &[
+            "run_id",
+            "instrument_uid",
+            "day",
+            "fields",
+            "official_close",
+            "stored_close",
+            "refetch_queued",
+            "detected_at",
+        ],
+    ),
+    (
+        "source_divergences",
+        &[
+            "run_id",
+            "instrument_uid",
+            "resolution",
+            "time",
+            "fields",
+            "tinkoff_close",
+            "iss_close",
+            "detected_at",
+        ],
+    ),
+];
+
+/// Таблицы, которые код читает через FINAL
+///
+/// Такие таблицы могли существовать до миграций с другим движком,
+/// а CREATE TABLE IF NOT EXISTS их не меняет; их пересоздают миграции
+const REPLACING_TABLES: &[&str] = &["tinkoff_shares"];
+
+const REPLACING_ENGINE: &str = "ReplacingMergeTree";
+
+const MIGRATIONS_TABLE: &str = "schema_migrations";
+
+/// Что делать со схемой при подключении
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaMode {
+    /// Применить недостающие миграции, затем проверить схему
+    Migrate,
+    /// Только проверить: недостающие миграции — ошибка запуска
+    Verify,
+}
+
+#[derive(Debug, Deserialize, clickhouse::Row)]
+struct AppliedMigration {
+    version: u32,
+    checksum: String,
+}
+
+#[derive(Debug, Deserialize, clickhouse::Row)]
+struct TableColumn {
+    table: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize, clickhouse::Row)]
+struct TableEngine {
+    name: String,
+    engine: String,
+}
+
+fn checksum(sql: &str) -> String {
+    format!("{:x}", Sha256::digest(sql.as_bytes()))
+}
+
+fn schema_error(message: String) -> ClickhouseError {
+    ClickhouseError::Other(message.into())
+}
+
+/// Разбивает файл миграции на отдельные запросы: HTTP-интерфейс ClickHouse
+/// выполняет один запрос за раз. Строки-комментарии отбрасываются
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let without_comments: String = sql
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("--"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    without_comments
+        .split(';')
+        .map(str::trim)
+        .filter(|statement| !statement.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Таблицы из REPLACING_TABLES с другим движком и их движки
+async fn misconfigured_engines(client: &Client) -> Result<Vec<TableEngine>, ClickhouseError> {
+    let engines = client
+        .query(
+            "SELECT name, engine FROM system.tables \
+             WHERE database = currentDatabase() AND has(?, name)",
+        )
+        .bind(REPLACING_TABLES)
+        .fetch_all::<TableEngine>()
+        .await?;
+    Ok(engines
+        .into_iter()
+        // Replicated and Shared variants keep the same semantics
+        .filter(|table| !table.engine.ends_with(REPLACING_ENGINE))
+        .collect())
+}
+
+/// Создаёт базу данных, если её ещё нет
+///
+/// `server` — клиент без выбранной базы: запрос к несуществующей базе завершится ошибкой
+pub async fn ensure_database(server: &Client, database: &str) -> Result<(), ClickhouseError> {
+    let exists = server
+        .query("SELECT count() FROM system.databases WHERE name = ?")
+        .bind(database)
+        .fetch_one::<u64>()
+        .await?
+        > 0;
+    if !exists {
+        info!("Creating ClickHouse database {}", database);
+        server
+            .query(&format!("CREATE DATABASE IF NOT EXISTS {}", database))
+            .execute()
+            .await?;
+    }
+    Ok(())
+}
+
+/// Применяет недостающие миграции (в режиме Migrate) и проверяет схему
+pub async fn prepare(
+    client: &Client,
+    database: &str,
+    mode: SchemaMode,
+) -> Result<(), ClickhouseError> {
+    if mode == SchemaMode::Migrate {
+        migrate(client).await?;
+    }
+    verify(client, database).await
+}
+
+/// Применяет миграции, которых ещё нет в schema_migrations
+///
+/// Запросы миграций можно выполнить повторно, поэтому прерванная миграция
+/// применяется заново при следующем запуске. 0006 подменяет tinkoff_shares,
+/// поэтому её лучше применить одной репликой: `t-candles migrate` до выкатки
+pub async fn migrate(client: &Client) -> Result<usize, ClickhouseError> {
+    client
+        .query(&format!(
+            "CREATE TABLE IF NOT EXISTS {}
+            (
+                version UInt32,
+                name String,
+                checksum String,
+                applied_at DateTime64(3) DEFAULT now64(3)
+            )
+            ENGINE = MergeTree
+            ORDER BY version",
+            MIGRATIONS_TABLE
+        ))
+        .execute()
+        .await?;
+
+    let applied = applied_migrations(client).await?;
+    let mut count = 0;
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|m| !applied.contains_key(&m.version))
+    {
+        info!(
+            "Applying ClickHouse migration {:04}_{}",
+            migration.version, migration.name
+        );
+        for statement in split_statements(migration.sql) {
+            client.query(&statement).execute().await?;
+        }
+
+        client
+            .query(&format!(
+                "INSERT INTO {} (version, name, checksum) VALUES (?, ?, ?)",
+                MIGRATIONS_TABLE
+            ))
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(checksum(migration.sql))
+            .execute()
+            .await?;
+        count += 1;
+    }
+
+    if count > 0 {
+        info!("Applied {} ClickHouse migrations", count);
+    }
+    Ok(count)
+}
+
+async fn applied_migrations(client: &Client) -> Result<HashMap<u32, String>, ClickhouseError> {
+    let rows = client
+        .query(&format!(
+            "SELECT version, any(checksum) AS checksum FROM {} GROUP BY version",
+            MIGRATIONS_TABLE
+        ))
+        .fetch_all::<AppliedMigration>()
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.version, row.checksum))
+        .collect())
+}
+
+/// Проверяет, что все миграции применены без изменений и нужные колонки на месте
+pub async fn verify(client: &Client, database: &str) -> Result<(), ClickhouseError> {
+    let columns = client
+        .query("SELECT table, name FROM system.columns WHERE database = ?")
+        .bind(database)
+        .fetch_all::<TableColumn>()
+        .await?;
+
+    let has_migrations_table = columns.iter().any(|c| c.table == MIGRATIONS_TABLE);
+    let applied = if has_migrations_table {
+        applied_migrations(client).await?
+    } else {
+        HashMap::new()
+    };
+
+    let pending: Vec<u32> = MIGRATIONS
+        .iter()
+        .filter(|m| !applied.contains_key(&m.version))
+        .map(|m| m.version)
+        .collect();
+    if !pending.is_empty() {
+        return Err(schema_error(format!(
+            "{} ClickHouse migrations are not applied (versions {:?}); \
+             run `t-candles migrate` or set clickhouse.auto_migrate = true",
+            pending.len(),
+            pending
+        )));
+    }
+
+    let changed: Vec<u32> = MIGRATIONS
+        .iter()
+        .filter(|m| applied.get(&m.version) != Some(&checksum(m.sql)))
+        .map(|m| m.version)
+        .collect();
+    if !changed.is_empty() {
+        return Err(schema_error(format!(
+            "ClickHouse migrations {:?} were changed after they had been applied",
+            changed
+        )));
+    }
+
+    let existing: HashSet<(&str, &str)> = columns
+        .iter()
+        .map(|c| (c.table.as_str(), c.name.as_str()))
+        .collect();
+    let missing: Vec<String> = REQUIRED_COLUMNS
+        .iter()
+        .flat_map(|(table, names)| {
+            names
+                .iter()
+                .filter(|name| !existing.contains(&(*table, **name)))
+                .map(move |name| format!("{}.{}", table, name))
+        })
+        .collect();
+    if !missing.is_empty() {
+        return Err(schema_error(format!(
+            "ClickHouse schema in {} is missing columns: {}",
+            database,
+            missing.join(", ")
+        )));
+    }
+
+    let engines = misconfigured_engines(client).await?;
+    if !engines.is_empty() {
+        let tables: Vec<String> = engines
+            .iter()
+            .map(|table| format!("{} ({})", table.name, table.engine))
+            .collect();
+        return Err(schema_error(format!(
+            "ClickHouse tables {} must use {}; they were changed after \
+             migration 0006_shares_replacing rebuilt them",
+            tables.join(", "),
+            REPLACING_ENGINE
+        )));
+    }
+
+    info!(
+        "ClickHouse schema verified: {} migrations applied",
+        MIGRATIONS.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_create_every_required_table() {
+        let statements: Vec<String> = MIGRATIONS
+            .iter()
+            .flat_map(|m| split_statements(m.sql))
+            .collect();
+        assert!(statements.iter().all(|s| !s.contains("--")));
+
+        for (table, columns) in REQUIRED_COLUMNS {
+            let create = statements
+                .iter()
+                .find(|s| s.starts_with(&format!("CREATE TABLE IF NOT EXISTS {}\n", table)))
+                .unwrap_or_else(|| panic!("no migration creates {}", table));
+            for column in *columns {
+                assert!(
+                    create.contains(&format!("    {} ", column)),
+                    "{}.{} is not created",
+                    table,
+                    column
+                );
+            }
+        }
+
+        // The rebuilt table gets the columns and engine of the initial one
+        let rebuild = split_statements(MIGRATIONS[5].sql);
+        for table in REPLACING_TABLES {
+            let create = statements
+                .iter()
+                .find(|s| s.starts_with(&format!("CREATE TABLE IF NOT EXISTS {}\n", table)))
+                .unwrap();
+            let columns = &create[create.find('(').unwrap()..];
+            assert!(columns.contains(&format!("ENGINE = {}(", REPLACING_ENGINE)));
+            let rebuilt = format!("CREATE TABLE {}_rebuild\n{}", table, columns);
+            assert!(rebuild.contains(&rebuilt));
+            assert!(rebuild.contains(&format!("EXCHANGE TABLES {0} AND {0}_rebuild", table)));
+        }
+
+        let versions: Vec<u32> = MIGRATIONS.iter().map(|m| m.version).collect();
+        assert!(versions.windows(2).all(|w| w[0] < w[1]));
+    }
+}
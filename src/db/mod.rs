@@ -1,2 +1,4 @@
 pub mod clickhouse;
 
+pub mod postgres;
+pub mod storage;
@@ -0,0 +1,53 @@
+use crate::env_config::models::app_setting::AppSettings;
+use sqlx::{
+    PgPool,
+    postgres::{PgConnectOptions, PgPoolOptions},
+};
+use std::{sync::Arc, time::Duration};
+use tracing::{debug, error, info};
+
+#[derive(Clone)]
+pub struct PostgresConnection {
+    pool: PgPool,
+}
+
+impl PostgresConnection {
+    pub async fn new(settings: Arc<AppSettings>) -> Result<Self, sqlx::Error> {
+        info!("Initializing PostgreSQL connection pool...");
+
+        let env = &settings.app_env;
+        let config = &settings.app_config.postgres;
+
+        // POSTGRES_HOST may include the port: "host" or "host:port"
+        let mut options = PgConnectOptions::new()
+            .username(&env.postgres_user)
+            .password(&env.postgres_password)
+            .database(&env.postgres_database);
+        options = match env.postgres_host.rsplit_once(':') {
+            Some((host, port)) => options.host(host).port(port.parse().unwrap_or(5432)),
+            None => options.host(&env.postgres_host),
+        };
+
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .acquire_timeout(Duration::from_secs(config.timeout))
+            .max_lifetime(Duration::from_secs(config.max_lifetime))
+            .idle_timeout(Duration::from_secs(config.idle_timeout))
+            .connect_with(options)
+            .await
+            .inspect_err(|e| error!("Failed to connect to PostgreSQL: {}", e))?;
+
+        // Test connection
+        let test_query = "SELECT 1";
+        debug!("Executing test query: {}", test_query);
+        sqlx::query(test_query).execute(&pool).await?;
+        info!("PostgreSQL connection successful");
+
+        Ok(Self { pool })
+    }
+
+    pub fn get_pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
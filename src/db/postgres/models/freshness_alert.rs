@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Открытый алерт о свежести свечей инструмента в таблице freshness_alerts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct DbFreshnessAlert {
+    pub instrument_uid: String,
+    pub sla: String,
+    pub opened_at: i64,
+    pub last_notified_at: i64,
+    pub last_candle_time: i64, // 0 — свечей нет
+    pub lag_trading_days: i64,
+}
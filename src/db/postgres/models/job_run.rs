@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Запуск задачи планировщика в таблице job_runs
+///
+/// При старте пишется строка со статусом RUNNING, по завершении она
+/// обновляется итоговыми счётчиками и статусом
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DbJobRun {
+    pub run_id: String,
+    pub job: String,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+    pub status: String, // RUNNING, SUCCESS, FAILED или CANCELLED
+    pub instruments_total: i64,
+    pub instruments_failed: i64,
+    pub candles_received: i64,
+    pub candles_inserted: i64,
+    pub error: String,
+}
+
+impl DbJobRun {
+    pub fn start(job: &str) -> Self {
+        Self {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            job: job.to_string(),
+            started_at: chrono::Utc::now().timestamp(),
+            finished_at: None,
+            status: "RUNNING".to_string(),
+            instruments_total: 0,
+            instruments_failed: 0,
+            candles_received: 0,
+            candles_inserted: 0,
+            error: String::new(),
+        }
+    }
+
+    /// Добавляет итог по инструменту к счётчикам запуска
+    pub fn add(&mut self, status: &DbLoadStatus) {
+        self.instruments_total += 1;
+        if !status.error.is_empty() {
+            self.instruments_failed += 1;
+        }
+        self.candles_received += status.candles_received;
+        self.candles_inserted += status.candles_inserted;
+    }
+
+    pub fn finish(&mut self, error: Option<String>) {
+        self.finished_at = Some(chrono::Utc::now().timestamp());
+        match error {
+            Some(error) => {
+                self.status = "FAILED".to_string();
+                self.error = error;
+            }
+            None => self.status = "SUCCESS".to_string(),
+        }
+    }
+
+    /// Запуск прерван остановкой приложения после записи текущего чекпойнта
+    pub fn cancel(&mut self) {
+        self.finished_at = Some(chrono::Utc::now().timestamp());
+        self.status = "CANCELLED".to_string();
+    }
+}
+
+/// Результат загрузки одного инструмента в рамках запуска, таблица job_run_instruments
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DbLoadStatus {
+    pub run_id: String,
+    pub instrument_uid: String,
+    pub ticker: String,
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub range_from: i64, // Запрошенный диапазон, секунды
+    pub range_to: i64,
+    pub candles_received: i64,
+    pub candles_inserted: i64,
+    pub candles_quarantined: i64,
+    pub error: String,
+}
+
+impl DbLoadStatus {
+    pub fn start(run_id: &str, instrument_uid: &str, ticker: &str) -> Self {
+        let now = chrono::Utc::now().timestamp();
+        Self {
+            run_id: run_id.to_string(),
+            instrument_uid: instrument_uid.to_string(),
+            ticker: ticker.to_string(),
+            started_at: now,
+            finished_at: now,
+            range_from: 0,
+            range_to: 0,
+            candles_received: 0,
+            candles_inserted: 0,
+            candles_quarantined: 0,
+            error: String::new(),
+        }
+    }
+
+    pub fn finish(&mut self, error: Option<String>) {
+        self.finished_at = chrono::Utc::now().timestamp();
+        self.error = error.unwrap_or_default();
+    }
+}
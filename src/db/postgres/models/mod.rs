@@ -0,0 +1,5 @@
+pub mod api_key;
+pub mod freshness_alert;
+pub mod job_run;
+pub mod refetch;
+pub mod watchlist;
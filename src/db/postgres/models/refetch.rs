@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Заявка на повторную загрузку суток минутных свечей из таблицы refetch_queue
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DbRefetchRequest {
+    pub instrument_uid: String,
+    pub day: i64, // Начало суток UTC, секунды
+    pub reason: String,
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Инструмент списка загрузки свечей вместе с контрольной точкой
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DbWatchlistInstrument {
+    pub uid: String,
+    pub first_1min_candle_date: i64, // С какой даты грузить историю, секунды
+    pub last_1min_candle_date: i64,  // Последняя сохранённая свеча; 0 — загрузки ещё не было
+    pub is_active: bool,
+    pub origin: String, // WatchlistOrigin::as_str
+}
+
+/// Откуда инструмент попал в список загрузки
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchlistOrigin {
+    /// Добавлен через API; синхронизация вселенной его не выключает
+    Manual,
+    /// Добавлен синхронизацией вселенной
+    Universe,
+}
+
+impl WatchlistOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Universe => "universe",
+        }
+    }
+
+    /// Неизвестное значение считается ручным, чтобы синхронизация его не трогала
+    pub fn parse(value: &str) -> Self {
+        if value == Self::Universe.as_str() {
+            Self::Universe
+        } else {
+            Self::Manual
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::db::postgres::connection::PostgresConnection;
+
+use crate::env_config::models::app_setting::AppSettings;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::repository::repository_api_key::ApiKeyRepository;
+use super::repository::repository_freshness_alert::FreshnessAlertRepository;
+use super::repository::repository_job_run::JobRunRepository;
+use super::repository::repository_refetch::RefetchRepository;
+use super::repository::repository_watchlist::WatchlistRepository;
+
+/// Миграции схемы метаданных, встроенные в бинарник при сборке
+static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations/postgres");
+
+pub struct PostgresService {
+    // Connections
+    pub connection: Arc<PostgresConnection>,
+
+    pub repository_watchlist: Arc<WatchlistRepository>,
+    pub repository_job_run: Arc<JobRunRepository>,
+    pub repository_api_key: Arc<ApiKeyRepository>,
+    pub repository_refetch: Arc<RefetchRepository>,
+    pub repository_freshness_alert: Arc<FreshnessAlertRepository>,
+}
+
+impl PostgresService {
+    pub async fn new(settings: &Arc<AppSettings>) -> Result<Self, Box<dyn std::error::Error>> {
+        info!("Creating PostgreSQL connection");
+        let postgres_connection = match PostgresConnection::new(settings.clone()).await {
+            Ok(conn) => Arc::new(conn),
+            Err(e) => {
+                error!("Failed to establish PostgreSQL connection: {}", e);
+                return Err(Box::new(e));
+            }
+        };
+
+        // Several replicas may start at once: sqlx serializes migrations with an advisory lock
+        info!("Applying PostgreSQL migrations");
+        if let Err(e) = MIGRATOR.run(postgres_connection.get_pool()).await {
+            error!("Failed to apply PostgreSQL migrations: {}", e);
+            return Err(Box::new(e));
+        }
+
+        // Initialize operational repositories (PostgreSQL)
+        info!("Initialize repositories (PostgreSQL)");
+        let repository_watchlist = Arc::new(WatchlistRepository::new(postgres_connection.clone()));
+
+        let repository_job_run = Arc::new(JobRunRepository::new(postgres_connection.clone()));
+
+        let repository_api_key = Arc::new(ApiKeyRepository::new(postgres_connection.clone()));
+
+        let repository_refetch = Arc::new(RefetchRepository::new(postgres_connection.clone()));
+
+        let repository_freshness_alert =
+            Arc::new(FreshnessAlertRepository::new(postgres_connection.clone()));
+
+        info!("PostgreSQL service initialized successfully");
+        Ok(Self {
+            connection: postgres_connection,
+
+            repository_watchlist,
+            repository_job_run,
+            repository_api_key,
+            repository_refetch,
+            repository_freshness_alert,
+        })
+    }
+}
@@ -0,0 +1,6 @@
+pub mod repository_api_key;
+pub mod repository_candle;
+pub mod repository_freshness_alert;
+pub mod repository_job_run;
+pub mod repository_refetch;
+pub mod repository_watchlist;
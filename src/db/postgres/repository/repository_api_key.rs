@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use crate::db::postgres::{connection::PostgresConnection, models::api_key::DbApiKey};
+
+const API_KEY_COLUMNS: &str = "id, name, created_at, last_used_at, revoked_at";
+
+/// Ключи доступа к API, таблица api_keys
+pub struct ApiKeyRepository {
+    connection: Arc<PostgresConnection>,
+}
+
+impl ApiKeyRepository {
+    pub fn new(connection: Arc<PostgresConnection>) -> Self {
+        Self { connection }
+    }
+
+    pub async fn list(&self) -> Result<Vec<DbApiKey>, sqlx::Error> {
+        let sql = format!("SELECT {} FROM api_keys ORDER BY id", API_KEY_COLUMNS);
+        sqlx::query_as::<_, DbApiKey>(&sql)
+            .fetch_all(self.connection.get_pool())
+            .await
+    }
+
+    /// Находит действующий ключ по хешу и отмечает время его использования
+    pub async fn authenticate(&self, key_hash: &str) -> Result<Option<DbApiKey>, sqlx::Error> {
+        let sql = format!(
+            "UPDATE api_keys SET last_used_at = now()
+            WHERE key_hash = $1 AND revoked_at IS NULL
+            RETURNING {}",
+            API_KEY_COLUMNS
+        );
+        sqlx::query_as::<_, DbApiKey>(&sql)
+            .bind(key_hash)
+            .fetch_optional(self.connection.get_pool())
+            .await
+    }
+
+    /// Создаёт ключ; имя должно быть уникальным
+    pub async fn create(&self, name: &str, key_hash: &str) -> Result<DbApiKey, sqlx::Error> {
+        let sql = format!(
+            "INSERT INTO api_keys (name, key_hash) VALUES ($1, $2) RETURNING {}",
+            API_KEY_COLUMNS
+        );
+        sqlx::query_as::<_, DbApiKey>(&sql)
+            .bind(name)
+            .bind(key_hash)
+            .fetch_one(self.connection.get_pool())
+            .await
+    }
+
+    /// Создаёт или заменяет ключ с заданным именем, снимая отзыв
+    pub async fn upsert(&self, name: &str, key_hash: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO api_keys (name, key_hash) VALUES ($1, $2)
+            ON CONFLICT (name) DO UPDATE SET key_hash = EXCLUDED.key_hash, revoked_at = NULL",
+        )
+        .bind(name)
+        .bind(key_hash)
+        .execute(self.connection.get_pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Отзывает ключ; false — действующего ключа с таким именем нет
+    pub async fn revoke(&self, name: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE api_keys SET revoked_at = now() WHERE name = $1 AND revoked_at IS NULL",
+        )
+        .bind(name)
+        .execute(self.connection.get_pool())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
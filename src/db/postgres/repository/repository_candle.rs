@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use sqlx::{Postgres, QueryBuilder};
+use std::sync::Arc;
+use tracing::debug;
+
+use super::repository_watchlist::WatchlistRepository;
+use crate::db::postgres::connection::PostgresConnection;
+use crate::db::storage::{
+    candle_repository::{CandleRepository, StorageError},
+    stored_candle::StoredCandle,
+};
+use crate::generate::tinkoff_public_invest_api_contract_v1::HistoricCandle;
+
+const CANDLE_COLUMNS: &str = "instrument_uid, time, open_units, open_nano, high_units, high_nano, \
+     low_units, low_nano, close_units, close_nano, volume";
+
+// 11 bound parameters per row, PostgreSQL allows up to 65535 per statement
+const BATCH_SIZE: usize = 1000;
+
+/// Хранилище свечей в PostgreSQL (или TimescaleDB): таблица candles_1min
+///
+/// Контрольные точки общие с ClickHouse-бэкендом — таблица instrument_checkpoints
+pub struct PostgresCandleRepository {
+    connection: Arc<PostgresConnection>,
+    watchlist: Arc<WatchlistRepository>,
+}
+
+impl PostgresCandleRepository {
+    pub fn new(connection: Arc<PostgresConnection>, watchlist: Arc<WatchlistRepository>) -> Self {
+        Self {
+            connection,
+            watchlist,
+        }
+    }
+}
+
+#[async_trait]
+impl CandleRepository for PostgresCandleRepository {
+    async fn insert_candles(
+        &self,
+        candles: Vec<HistoricCandle>,
+        instrument_uid: &str,
+    ) -> Result<u64, StorageError> {
+        let rows = StoredCandle::from_historic_batch(instrument_uid, &candles);
+        let mut inserted = 0;
+
+        for batch in rows.chunks(BATCH_SIZE) {
+            let mut query: QueryBuilder<Postgres> =
+                QueryBuilder::new(format!("INSERT INTO candles_1min ({}) ", CANDLE_COLUMNS));
+            query.push_values(batch, |mut row, c| {
+                row.push_bind(&c.instrument_uid)
+                    .push_bind(c.time)
+                    .push_bind(c.open_units)
+                    .push_bind(c.open_nano)
+                    .push_bind(c.high_units)
+                    .push_bind(c.high_nano)
+                    .push_bind(c.low_units)
+                    .push_bind(c.low_nano)
+                    .push_bind(c.close_units)
+                    .push_bind(c.close_nano)
+                    .push_bind(c.volume);
+            });
+            // Reloading a day overwrites it instead of duplicating rows
+            query.push(
+                " ON CONFLICT (instrument_uid, time) DO UPDATE SET \
+                 open_units = EXCLUDED.open_units, open_nano = EXCLUDED.open_nano, \
+                 high_units = EXCLUDED.high_units, high_nano = EXCLUDED.high_nano, \
+                 low_units = EXCLUDED.low_units, low_nano = EXCLUDED.low_nano, \
+                 close_units = EXCLUDED.close_units, close_nano = EXCLUDED.close_nano, \
+                 volume = EXCLUDED.volume",
+            );
+            inserted += query
+                .build()
+                .execute(self.connection.get_pool())
+                .await?
+                .rows_affected();
+        }
+
+        debug!(
+            "Inserted {} candles for instrument_uid={} into PostgreSQL",
+            inserted, instrument_uid
+        );
+        Ok(inserted)
+    }
+
+    async fn get_candles(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<StoredCandle>, StorageError> {
+        let sql = format!(
+            "SELECT {} FROM candles_1min
+            WHERE instrument_uid = $1 AND time >= $2 AND time <= $3
+            ORDER BY time",
+            CANDLE_COLUMNS
+        );
+
+        Ok(sqlx::query_as::<_, StoredCandle>(&sql)
+            .bind(instrument_uid)
+            .bind(from)
+            .bind(to)
+            .fetch_all(self.connection.get_pool())
+            .await?)
+    }
+
+    async fn get_checkpoint(&self, instrument_uid: &str) -> Result<Option<i64>, StorageError> {
+        Ok(self.watchlist.get_checkpoint(instrument_uid).await?)
+    }
+
+    async fn save_checkpoint(
+        &self,
+        instrument_uid: &str,
+        last_candle_time: i64,
+    ) -> Result<(), StorageError> {
+        Ok(self
+            .watchlist
+            .update_checkpoint(instrument_uid, last_candle_time)
+            .await?)
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "postgres"
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::db::postgres::{
+    connection::PostgresConnection, models::freshness_alert::DbFreshnessAlert,
+};
+
+const ALERT_COLUMNS: &str =
+    "instrument_uid, sla, opened_at, last_notified_at, last_candle_time, lag_trading_days";
+
+/// Состояние алертов о свежести свечей: таблица freshness_alerts
+pub struct FreshnessAlertRepository {
+    connection: Arc<PostgresConnection>,
+}
+
+impl FreshnessAlertRepository {
+    pub fn new(connection: Arc<PostgresConnection>) -> Self {
+        Self { connection }
+    }
+
+    /// Открытые алерты, самые давние первыми
+    pub async fn get_open(&self) -> Result<Vec<DbFreshnessAlert>, sqlx::Error> {
+        let sql = format!(
+            "SELECT {} FROM freshness_alerts ORDER BY opened_at, instrument_uid",
+            ALERT_COLUMNS
+        );
+        sqlx::query_as::<_, DbFreshnessAlert>(&sql)
+            .fetch_all(self.connection.get_pool())
+            .await
+    }
+
+    /// Сохраняет открытые алерты, обновляя уже существующие
+    pub async fn save(&self, alerts: &[DbFreshnessAlert]) -> Result<(), sqlx::Error> {
+        if alerts.is_empty() {
+            return Ok(());
+        }
+
+        let mut query: QueryBuilder<Postgres> =
+            QueryBuilder::new(format!("INSERT INTO freshness_alerts ({}) ", ALERT_COLUMNS));
+        query.push_values(alerts, |mut row, alert| {
+            row.push_bind(&alert.instrument_uid)
+                .push_bind(&alert.sla)
+                .push_bind(alert.opened_at)
+                .push_bind(alert.last_notified_at)
+                .push_bind(alert.last_candle_time)
+                .push_bind(alert.lag_trading_days);
+        });
+        query.push(
+            " ON CONFLICT (instrument_uid) DO UPDATE SET
+                sla = EXCLUDED.sla,
+                last_notified_at = EXCLUDED.last_notified_at,
+                last_candle_time = EXCLUDED.last_candle_time,
+                lag_trading_days = EXCLUDED.lag_trading_days",
+        );
+        query.build().execute(self.connection.get_pool()).await?;
+        Ok(())
+    }
+
+    /// Закрывает алерты восстановившихся инструментов
+    pub async fn close(&self, instrument_uids: &[String]) -> Result<(), sqlx::Error> {
+        if instrument_uids.is_empty() {
+            return Ok(());
+        }
+        sqlx::query("DELETE FROM freshness_alerts WHERE instrument_uid = ANY($1)")
+            .bind(instrument_uids)
+            .execute(self.connection.get_pool())
+            .await
+            .map(|_| ())
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use tracing::{debug, error};
+
+use crate::db::postgres::{
+    connection::PostgresConnection,
+    models::job_run::{DbJobRun, DbLoadStatus},
+};
+
+const RUN_COLUMNS: &str = "run_id, job, started_at, finished_at, status, instruments_total, \
+     instruments_failed, candles_received, candles_inserted, error";
+
+const LOAD_STATUS_COLUMNS: &str = "run_id, instrument_uid, ticker, started_at, finished_at, \
+     range_from, range_to, candles_received, candles_inserted, candles_quarantined, error";
+
+/// Журнал запусков задач: таблицы job_runs и job_run_instruments
+pub struct JobRunRepository {
+    connection: Arc<PostgresConnection>,
+}
+
+impl JobRunRepository {
+    pub fn new(connection: Arc<PostgresConnection>) -> Self {
+        Self { connection }
+    }
+
+    /// Записывает текущее состояние запуска, обновляя строку с тем же run_id
+    pub async fn save_run(&self, run: &DbJobRun) -> Result<(), sqlx::Error> {
+        let sql = format!(
+            "INSERT INTO job_runs ({})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (run_id) DO UPDATE SET
+                finished_at = EXCLUDED.finished_at,
+                status = EXCLUDED.status,
+                instruments_total = EXCLUDED.instruments_total,
+                instruments_failed = EXCLUDED.instruments_failed,
+                candles_received = EXCLUDED.candles_received,
+                candles_inserted = EXCLUDED.candles_inserted,
+                error = EXCLUDED.error,
+                updated_at = now()",
+            RUN_COLUMNS
+        );
+
+        debug!(
+            "Saving job run {} ({}): {}",
+            run.run_id, run.job, run.status
+        );
+        sqlx::query(&sql)
+            .bind(&run.run_id)
+            .bind(&run.job)
+            .bind(run.started_at)
+            .bind(run.finished_at)
+            .bind(&run.status)
+            .bind(run.instruments_total)
+            .bind(run.instruments_failed)
+            .bind(run.candles_received)
+            .bind(run.candles_inserted)
+            .bind(&run.error)
+            .execute(self.connection.get_pool())
+            .await
+            .map(|_| ())
+            .inspect_err(|e| error!("Failed to save job run {}: {}", run.run_id, e))
+    }
+
+    pub async fn insert_load_status(&self, status: &DbLoadStatus) -> Result<(), sqlx::Error> {
+        let sql = format!(
+            "INSERT INTO job_run_instruments ({})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            LOAD_STATUS_COLUMNS
+        );
+
+        sqlx::query(&sql)
+            .bind(&status.run_id)
+            .bind(&status.instrument_uid)
+            .bind(&status.ticker)
+            .bind(status.started_at)
+            .bind(status.finished_at)
+            .bind(status.range_from)
+            .bind(status.range_to)
+            .bind(status.candles_received)
+            .bind(status.candles_inserted)
+            .bind(status.candles_quarantined)
+            .bind(&status.error)
+            .execute(self.connection.get_pool())
+            .await
+            .map(|_| ())
+            .inspect_err(|e| {
+                error!(
+                    "Failed to save load status for {} in run {}: {}",
+                    status.instrument_uid, status.run_id, e
+                );
+            })
+    }
+
+    /// Запуски, начавшиеся в период [from, to] (секунды), опционально по одной задаче
+    pub async fn get_runs(
+        &self,
+        from: i64,
+        to: i64,
+        job: Option<&str>,
+    ) -> Result<Vec<DbJobRun>, sqlx::Error> {
+        let sql = format!(
+            "SELECT {} FROM job_runs
+            WHERE started_at >= $1 AND started_at <= $2 AND ($3::TEXT IS NULL OR job = $3)
+            ORDER BY started_at DESC",
+            RUN_COLUMNS
+        );
+
+        sqlx::query_as::<_, DbJobRun>(&sql)
+            .bind(from)
+            .bind(to)
+            .bind(job)
+            .fetch_all(self.connection.get_pool())
+            .await
+    }
+
+    /// Время начала последнего запуска задачи (секунды), если он был
+    pub async fn get_last_run_start(&self, job: &str) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar("SELECT max(started_at) FROM job_runs WHERE job = $1")
+            .bind(job)
+            .fetch_one(self.connection.get_pool())
+            .await
+    }
+
+    /// Результаты по инструментам за период [from, to] (секунды)
+    ///
+    /// `instrument` сравнивается и с uid, и с тикером (без учёта регистра)
+    pub async fn get_load_statuses(
+        &self,
+        from: i64,
+        to: i64,
+        instrument: Option<&str>,
+        run_id: Option<&str>,
+    ) -> Result<Vec<DbLoadStatus>, sqlx::Error> {
+        let sql = format!(
+            "SELECT {} FROM job_run_instruments
+            WHERE started_at >= $1 AND started_at <= $2
+                AND ($3::TEXT IS NULL OR instrument_uid = $3 OR upper(ticker) = upper($3))
+                AND ($4::TEXT IS NULL OR run_id = $4)
+            ORDER BY started_at",
+            LOAD_STATUS_COLUMNS
+        );
+
+        sqlx::query_as::<_, DbLoadStatus>(&sql)
+            .bind(from)
+            .bind(to)
+            .bind(instrument)
+            .bind(run_id)
+            .fetch_all(self.connection.get_pool())
+            .await
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use sqlx::{Postgres, QueryBuilder};
+use tracing::info;
+
+use crate::db::postgres::{connection::PostgresConnection, models::refetch::DbRefetchRequest};
+
+/// Очередь повторной загрузки суток: таблица refetch_queue
+pub struct RefetchRepository {
+    connection: Arc<PostgresConnection>,
+}
+
+impl RefetchRepository {
+    pub fn new(connection: Arc<PostgresConnection>) -> Self {
+        Self { connection }
+    }
+
+    /// Ставит сутки в очередь; уже выполненная заявка на те же сутки открывается заново
+    pub async fn enqueue(&self, requests: &[DbRefetchRequest]) -> Result<(), sqlx::Error> {
+        if requests.is_empty() {
+            return Ok(());
+        }
+
+        let mut query: QueryBuilder<Postgres> =
+            QueryBuilder::new("INSERT INTO refetch_queue (instrument_uid, day, reason) ");
+        query.push_values(requests, |mut row, request| {
+            row.push_bind(&request.instrument_uid)
+                .push_bind(request.day)
+                .push_bind(&request.reason);
+        });
+        query.push(
+            " ON CONFLICT (instrument_uid, day) DO UPDATE SET
+                reason = EXCLUDED.reason, queued_at = now(), done_at = NULL",
+        );
+
+        info!("Queueing {} days for refetch", requests.len());
+        query.build().execute(self.connection.get_pool()).await?;
+        Ok(())
+    }
+
+    /// Невыполненные заявки в порядке постановки
+    pub async fn get_pending(&self, limit: i64) -> Result<Vec<DbRefetchRequest>, sqlx::Error> {
+        sqlx::query_as::<_, DbRefetchRequest>(
+            "SELECT instrument_uid, day, reason FROM refetch_queue
+            WHERE done_at IS NULL
+            ORDER BY queued_at, instrument_uid, day
+            LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(self.connection.get_pool())
+        .await
+    }
+
+    pub async fn mark_done(&self, instrument_uid: &str, day: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE refetch_queue SET done_at = now() WHERE instrument_uid = $1 AND day = $2",
+        )
+        .bind(instrument_uid)
+        .bind(day)
+        .execute(self.connection.get_pool())
+        .await
+        .map(|_| ())
+    }
+}
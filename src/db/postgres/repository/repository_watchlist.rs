@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use sqlx::{Postgres, QueryBuilder};
+use tracing::{debug, info};
+
+use crate::db::postgres::{
+    connection::PostgresConnection,
+    models::watchlist::{DbWatchlistInstrument, WatchlistOrigin},
+};
+
+const WATCHLIST_QUERY: &str = "SELECT
+        w.instrument_uid AS uid,
+        w.first_1min_candle_date,
+        COALESCE(c.last_1min_candle_date, 0) AS last_1min_candle_date,
+        w.is_active,
+        w.origin
+    FROM watchlist w
+    LEFT JOIN instrument_checkpoints c ON c.instrument_uid = w.instrument_uid";
+
+/// Список загрузки свечей и контрольные точки: таблицы watchlist и instrument_checkpoints
+pub struct WatchlistRepository {
+    connection: Arc<PostgresConnection>,
+}
+
+impl WatchlistRepository {
+    pub fn new(connection: Arc<PostgresConnection>) -> Self {
+        Self { connection }
+    }
+
+    /// Все инструменты списка, включая выключенные
+    pub async fn get_all(&self) -> Result<Vec<DbWatchlistInstrument>, sqlx::Error> {
+        let sql = format!("{} ORDER BY w.added_at", WATCHLIST_QUERY);
+        sqlx::query_as::<_, DbWatchlistInstrument>(&sql)
+            .fetch_all(self.connection.get_pool())
+            .await
+    }
+
+    /// Инструменты, по которым нужно загружать свечи
+    pub async fn get_active(&self) -> Result<Vec<DbWatchlistInstrument>, sqlx::Error> {
+        let sql = format!("{} WHERE w.is_active ORDER BY w.added_at", WATCHLIST_QUERY);
+        sqlx::query_as::<_, DbWatchlistInstrument>(&sql)
+            .fetch_all(self.connection.get_pool())
+            .await
+    }
+
+    /// Все инструменты списка вместе с признаком активности и происхождением
+    pub async fn get_backfill_state(
+        &self,
+    ) -> Result<Vec<(String, bool, WatchlistOrigin)>, sqlx::Error> {
+        let rows: Vec<(String, bool, String)> =
+            sqlx::query_as("SELECT instrument_uid, is_active, origin FROM watchlist")
+                .fetch_all(self.connection.get_pool())
+                .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(uid, is_active, origin)| (uid, is_active, WatchlistOrigin::parse(&origin)))
+            .collect())
+    }
+
+    /// Добавляет инструменты в список, история начнётся с first_1min_candle_date
+    ///
+    /// Уже известные инструменты включаются обратно; дата начала и контрольная точка
+    /// у них сохраняются. Ручное добавление закрепляет инструмент: после него
+    /// синхронизация вселенной его не выключает
+    pub async fn add_instruments(
+        &self,
+        instruments: &[(String, i64)],
+        origin: WatchlistOrigin,
+    ) -> Result<(), sqlx::Error> {
+        if instruments.is_empty() {
+            return Ok(());
+        }
+
+        let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO watchlist (instrument_uid, first_1min_candle_date, origin) ",
+        );
+        query.push_values(instruments, |mut row, (uid, first_date)| {
+            row.push_bind(uid)
+                .push_bind(first_date)
+                .push_bind(origin.as_str());
+        });
+        query.push(
+            " ON CONFLICT (instrument_uid) DO UPDATE SET is_active = TRUE, updated_at = now(),
+            origin = CASE WHEN EXCLUDED.origin = 'manual' THEN 'manual' ELSE watchlist.origin END",
+        );
+
+        info!(
+            "Adding {} instruments to the backfill list",
+            instruments.len()
+        );
+        query.build().execute(self.connection.get_pool()).await?;
+        Ok(())
+    }
+
+    /// Включает или выключает загрузку свечей для инструментов, сохраняя контрольную точку
+    ///
+    /// Возвращает число изменённых инструментов
+    pub async fn set_active(&self, uids: &[String], is_active: bool) -> Result<u64, sqlx::Error> {
+        if uids.is_empty() {
+            return Ok(0);
+        }
+
+        info!(
+            "Setting is_active={} for {} instruments in the backfill list",
+            is_active,
+            uids.len()
+        );
+        let result = sqlx::query(
+            "UPDATE watchlist SET is_active = $1, updated_at = now()
+            WHERE instrument_uid = ANY($2)",
+        )
+        .bind(is_active)
+        .bind(uids)
+        .execute(self.connection.get_pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Выключает инструмент по запросу пользователя; контрольная точка сохраняется
+    ///
+    /// Инструмент становится ручным, поэтому синхронизация вселенной не включит его обратно
+    pub async fn remove(&self, uid: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE watchlist SET is_active = FALSE, origin = $1, updated_at = now()
+            WHERE instrument_uid = $2",
+        )
+        .bind(WatchlistOrigin::Manual.as_str())
+        .bind(uid)
+        .execute(self.connection.get_pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Сохраняет контрольную точку: время последней записанной свечи инструмента
+    pub async fn update_checkpoint(&self, uid: &str, last_date: i64) -> Result<(), sqlx::Error> {
+        debug!("Updating checkpoint for instrument {}: {}", uid, last_date);
+        sqlx::query(
+            "INSERT INTO instrument_checkpoints (instrument_uid, last_1min_candle_date)
+            VALUES ($1, $2)
+            ON CONFLICT (instrument_uid) DO UPDATE SET
+                last_1min_candle_date = EXCLUDED.last_1min_candle_date,
+                updated_at = now()",
+        )
+        .bind(uid)
+        .bind(last_date)
+        .execute(self.connection.get_pool())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_checkpoint(&self, uid: &str) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT last_1min_candle_date FROM instrument_checkpoints WHERE instrument_uid = $1",
+        )
+        .bind(uid)
+        .fetch_optional(self.connection.get_pool())
+        .await
+    }
+
+    pub async fn is_empty(&self) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT NOT EXISTS (SELECT 1 FROM watchlist)")
+            .fetch_one(self.connection.get_pool())
+            .await
+    }
+
+    /// Переносит список вместе с контрольными точками одной транзакцией
+    pub async fn import(&self, instruments: &[DbWatchlistInstrument]) -> Result<(), sqlx::Error> {
+        if instruments.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.connection.get_pool().begin().await?;
+
+        let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO watchlist (instrument_uid, first_1min_candle_date, is_active) ",
+        );
+        query.push_values(instruments, |mut row, instrument| {
+            row.push_bind(&instrument.uid)
+                .push_bind(instrument.first_1min_candle_date)
+                .push_bind(instrument.is_active);
+        });
+        query.push(" ON CONFLICT (instrument_uid) DO NOTHING");
+        query.build().execute(&mut *tx).await?;
+
+        let checkpoints: Vec<&DbWatchlistInstrument> = instruments
+            .iter()
+            .filter(|instrument| instrument.last_1min_candle_date > 0)
+            .collect();
+        if !checkpoints.is_empty() {
+            let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+                "INSERT INTO instrument_checkpoints (instrument_uid, last_1min_candle_date) ",
+            );
+            query.push_values(checkpoints, |mut row, instrument| {
+                row.push_bind(&instrument.uid)
+                    .push_bind(instrument.last_1min_candle_date);
+            });
+            query.push(" ON CONFLICT (instrument_uid) DO NOTHING");
+            query.build().execute(&mut *tx).await?;
+        }
+
+        tx.commit().await
+    }
+}
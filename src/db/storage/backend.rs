@@ -0,0 +1,43 @@
+use std::sync::Arc;
+use tracing::info;
+
+use super::candle_repository::{CandleRepository, StorageError};
+use super::memory::MemoryCandleRepository;
+use super::sqlite::SqliteCandleRepository;
+use crate::db::clickhouse::{
+    clickhouse_service::ClickhouseService,
+    repository::candle_repository::ClickhouseCandleRepository,
+};
+use crate::db::postgres::{
+    postgres_service::PostgresService, repository::repository_candle::PostgresCandleRepository,
+};
+use crate::env_config::models::app_config::{StorageBackend, StorageConfig};
+
+/// Создаёт хранилище свечей, выбранное в секции [storage]
+///
+/// sqlite и memory обходятся без баз данных, поэтому сервисы передаются как есть,
+/// даже если подключиться к ним не удалось
+pub async fn create_candle_repository(
+    config: &StorageConfig,
+    clickhouse_service: Option<&ClickhouseService>,
+    postgres_service: Option<&PostgresService>,
+) -> Result<Arc<dyn CandleRepository + Send + Sync>, StorageError> {
+    let clickhouse = || clickhouse_service.ok_or(StorageError::Unavailable("ClickHouse"));
+    let postgres = || postgres_service.ok_or(StorageError::Unavailable("PostgreSQL"));
+
+    let repository: Arc<dyn CandleRepository + Send + Sync> = match config.backend {
+        StorageBackend::Clickhouse => Arc::new(ClickhouseCandleRepository::new(
+            clickhouse()?.connection.clone(),
+            postgres()?.repository_watchlist.clone(),
+        )),
+        StorageBackend::Postgres => Arc::new(PostgresCandleRepository::new(
+            postgres()?.connection.clone(),
+            postgres()?.repository_watchlist.clone(),
+        )),
+        StorageBackend::Sqlite => Arc::new(SqliteCandleRepository::new(&config.sqlite_path).await?),
+        StorageBackend::Memory => Arc::new(MemoryCandleRepository::new()),
+    };
+
+    info!("Candle storage backend: {}", repository.backend_name());
+    Ok(repository)
+}
@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use std::fmt;
+
+use super::stored_candle::StoredCandle;
+use crate::env_config::models::retention::CandleResolution;
+use crate::generate::tinkoff_public_invest_api_contract_v1::HistoricCandle;
+
+/// Хранилище минутных свечей; реализация выбирается в секции [storage] конфигурации
+#[async_trait]
+pub trait CandleRepository {
+    /// Вставка исторических свечей
+    ///
+    /// # Параметры
+    /// * `candles` - Вектор свечей для вставки
+    /// * `instrument_uid` - Идентификатор инструмента
+    ///
+    /// # Возвращает
+    /// * `Result<u64, StorageError>` - Количество успешно вставленных свечей или ошибку
+    async fn insert_candles(
+        &self,
+        candles: Vec<HistoricCandle>,
+        instrument_uid: &str,
+    ) -> Result<u64, StorageError>;
+
+    /// Свечи инструмента за период [from, to] (секунды) по возрастанию времени
+    async fn get_candles(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<StoredCandle>, StorageError>;
+
+    /// Свечи `resolution` за период: от свечи, содержащей `from`, до `to` включительно
+    ///
+    /// По умолчанию минутные свечи сворачиваются в памяти; ClickHouse читает
+    /// готовые таблицы свёрток
+    async fn get_candles_at(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+        resolution: CandleResolution,
+    ) -> Result<Vec<StoredCandle>, StorageError> {
+        let candles = self
+            .get_candles(instrument_uid, resolution.bucket_start(from), to)
+            .await?;
+        if resolution == CandleResolution::Minute {
+            return Ok(candles);
+        }
+        Ok(StoredCandle::rollup(&candles, resolution))
+    }
+
+    /// Контрольная точка загрузки: время последней сохранённой свечи инструмента
+    async fn get_checkpoint(&self, instrument_uid: &str) -> Result<Option<i64>, StorageError>;
+
+    async fn save_checkpoint(
+        &self,
+        instrument_uid: &str,
+        last_candle_time: i64,
+    ) -> Result<(), StorageError>;
+
+    /// Имя бэкенда для логов
+    fn backend_name(&self) -> &'static str;
+}
+
+/// Ошибка хранилища свечей независимо от бэкенда
+#[derive(Debug)]
+pub enum StorageError {
+    Clickhouse(clickhouse::error::Error),
+    Sql(sqlx::Error),
+    Io(std::io::Error),
+    Archive(Box<dyn std::error::Error + Send + Sync>), // Копия пачки не записана в Parquet-архив
+    Unavailable(&'static str),                         // База, которой нужен бэкенд, не подключена
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Clickhouse(e) => write!(f, "ClickHouse error: {}", e),
+            StorageError::Sql(e) => write!(f, "SQL error: {}", e),
+            StorageError::Io(e) => write!(f, "I/O error: {}", e),
+            StorageError::Archive(e) => write!(f, "Archive error: {}", e),
+            StorageError::Unavailable(database) => write!(f, "{} is not connected", database),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::Clickhouse(e) => Some(e),
+            StorageError::Sql(e) => Some(e),
+            StorageError::Io(e) => Some(e),
+            StorageError::Archive(e) => Some(e.as_ref()),
+            StorageError::Unavailable(_) => None,
+        }
+    }
+}
+
+impl From<clickhouse::error::Error> for StorageError {
+    fn from(e: clickhouse::error::Error) -> Self {
+        StorageError::Clickhouse(e)
+    }
+}
+
+impl From<sqlx::Error> for StorageError {
+    fn from(e: sqlx::Error) -> Self {
+        StorageError::Sql(e)
+    }
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(e: std::io::Error) -> Self {
+        StorageError::Io(e)
+    }
+}
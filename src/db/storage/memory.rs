@@ -0,0 +1,141 @@
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::RwLock;
+
+use super::candle_repository::{CandleRepository, StorageError};
+use super::stored_candle::StoredCandle;
+use crate::generate::tinkoff_public_invest_api_contract_v1::HistoricCandle;
+
+/// Хранилище свечей в памяти процесса: для тестов и пробных запусков
+///
+/// Свечи с тем же временем перезаписываются, данные теряются при остановке
+#[derive(Default)]
+pub struct MemoryCandleRepository {
+    candles: RwLock<HashMap<String, BTreeMap<i64, StoredCandle>>>,
+    checkpoints: RwLock<HashMap<String, i64>>,
+}
+
+impl MemoryCandleRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CandleRepository for MemoryCandleRepository {
+    async fn insert_candles(
+        &self,
+        candles: Vec<HistoricCandle>,
+        instrument_uid: &str,
+    ) -> Result<u64, StorageError> {
+        let rows = StoredCandle::from_historic_batch(instrument_uid, &candles);
+        let inserted = rows.len() as u64;
+
+        let mut stored = self.candles.write().await;
+        let series = stored.entry(instrument_uid.to_string()).or_default();
+        for row in rows {
+            series.insert(row.time, row);
+        }
+
+        Ok(inserted)
+    }
+
+    async fn get_candles(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<StoredCandle>, StorageError> {
+        if from > to {
+            return Ok(Vec::new());
+        }
+
+        let stored = self.candles.read().await;
+        Ok(stored
+            .get(instrument_uid)
+            .map(|series| series.range(from..=to).map(|(_, c)| c.clone()).collect())
+            .unwrap_or_default())
+    }
+
+    async fn get_checkpoint(&self, instrument_uid: &str) -> Result<Option<i64>, StorageError> {
+        Ok(self.checkpoints.read().await.get(instrument_uid).copied())
+    }
+
+    async fn save_checkpoint(
+        &self,
+        instrument_uid: &str,
+        last_candle_time: i64,
+    ) -> Result<(), StorageError> {
+        self.checkpoints
+            .write()
+            .await
+            .insert(instrument_uid.to_string(), last_candle_time);
+        Ok(())
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "memory"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::tinkoff_public_invest_api_contract_v1::Quotation;
+
+    fn candle(time: i64, close: i64) -> HistoricCandle {
+        let price = |units| Some(Quotation { units, nano: 0 });
+        HistoricCandle {
+            open: price(close),
+            high: price(close),
+            low: price(close),
+            close: price(close),
+            volume: 10,
+            time: Some(prost_types::Timestamp {
+                seconds: time,
+                nanos: 0,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn range_reads_are_ordered_and_rewrites_replace() {
+        let repository = MemoryCandleRepository::new();
+        let batch = vec![candle(180, 3), candle(60, 1), candle(120, 2)];
+        assert_eq!(repository.insert_candles(batch, "uid").await.unwrap(), 3);
+        repository
+            .insert_candles(vec![candle(120, 5)], "uid")
+            .await
+            .unwrap();
+
+        let candles = repository.get_candles("uid", 60, 120).await.unwrap();
+        let times: Vec<(i64, i64)> = candles.iter().map(|c| (c.time, c.close_units)).collect();
+        assert_eq!(times, vec![(60, 1), (120, 5)]);
+        assert!(
+            repository
+                .get_candles("other", 0, 1000)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn candles_without_time_are_skipped_and_checkpoint_is_kept() {
+        let repository = MemoryCandleRepository::new();
+        let mut broken = candle(0, 1);
+        broken.time = None;
+        assert_eq!(
+            repository
+                .insert_candles(vec![broken], "uid")
+                .await
+                .unwrap(),
+            0
+        );
+
+        assert_eq!(repository.get_checkpoint("uid").await.unwrap(), None);
+        repository.save_checkpoint("uid", 180).await.unwrap();
+        assert_eq!(repository.get_checkpoint("uid").await.unwrap(), Some(180));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod backend;
+pub mod candle_repository;
+pub mod memory;
+pub mod sqlite;
+pub mod stored_candle;
@@ -0,0 +1,218 @@
+use async_trait::async_trait;
+use sqlx::{
+    QueryBuilder, Sqlite, SqlitePool,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+};
+use std::path::Path;
+use tracing::{debug, info};
+
+use super::candle_repository::{CandleRepository, StorageError};
+use super::stored_candle::StoredCandle;
+use crate::generate::tinkoff_public_invest_api_contract_v1::HistoricCandle;
+
+const CANDLE_COLUMNS: &str = "instrument_uid, time, open_units, open_nano, high_units, high_nano, \
+     low_units, low_nano, close_units, close_nano, volume";
+
+// SQLite limits bound parameters per statement, 11 columns x 500 rows stays well below it
+const BATCH_SIZE: usize = 500;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS candles_1min (
+        instrument_uid TEXT NOT NULL,
+        time INTEGER NOT NULL,
+        open_units INTEGER NOT NULL,
+        open_nano INTEGER NOT NULL,
+        high_units INTEGER NOT NULL,
+        high_nano INTEGER NOT NULL,
+        low_units INTEGER NOT NULL,
+        low_nano INTEGER NOT NULL,
+        close_units INTEGER NOT NULL,
+        close_nano INTEGER NOT NULL,
+        volume INTEGER NOT NULL,
+        PRIMARY KEY (instrument_uid, time)
+    ) WITHOUT ROWID;
+
+    CREATE TABLE IF NOT EXISTS candle_checkpoints (
+        instrument_uid TEXT PRIMARY KEY,
+        last_1min_candle_date INTEGER NOT NULL
+    );
+";
+
+/// Хранилище свечей во встроенном файле SQLite: для ноутбука и небольших установок
+pub struct SqliteCandleRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteCandleRepository {
+    /// Открывает (или создаёт) файл базы и таблицы в нём
+    pub async fn new(path: &str) -> Result<Self, StorageError> {
+        if let Some(parent) = Path::new(path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(options)
+            .await?;
+
+        Self::with_pool(pool).await.inspect(|_| {
+            info!("SQLite candle storage opened at {}", path);
+        })
+    }
+
+    /// Создаёт таблицы в уже открытой базе; используется и для базы в памяти
+    pub async fn with_pool(pool: SqlitePool) -> Result<Self, StorageError> {
+        sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl CandleRepository for SqliteCandleRepository {
+    async fn insert_candles(
+        &self,
+        candles: Vec<HistoricCandle>,
+        instrument_uid: &str,
+    ) -> Result<u64, StorageError> {
+        let rows = StoredCandle::from_historic_batch(instrument_uid, &candles);
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        // One transaction per call: SQLite commits are the expensive part
+        let mut tx = self.pool.begin().await?;
+        for batch in rows.chunks(BATCH_SIZE) {
+            let mut query: QueryBuilder<Sqlite> =
+                QueryBuilder::new(format!("INSERT INTO candles_1min ({}) ", CANDLE_COLUMNS));
+            query.push_values(batch, |mut row, c| {
+                row.push_bind(&c.instrument_uid)
+                    .push_bind(c.time)
+                    .push_bind(c.open_units)
+                    .push_bind(c.open_nano)
+                    .push_bind(c.high_units)
+                    .push_bind(c.high_nano)
+                    .push_bind(c.low_units)
+                    .push_bind(c.low_nano)
+                    .push_bind(c.close_units)
+                    .push_bind(c.close_nano)
+                    .push_bind(c.volume);
+            });
+            query.push(" ON CONFLICT (instrument_uid, time) DO UPDATE SET ");
+            query.push(
+                "open_units = excluded.open_units, open_nano = excluded.open_nano, \
+                 high_units = excluded.high_units, high_nano = excluded.high_nano, \
+                 low_units = excluded.low_units, low_nano = excluded.low_nano, \
+                 close_units = excluded.close_units, close_nano = excluded.close_nano, \
+                 volume = excluded.volume",
+            );
+            query.build().execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+
+        debug!(
+            "Inserted {} candles for instrument_uid={} into SQLite",
+            rows.len(),
+            instrument_uid
+        );
+        Ok(rows.len() as u64)
+    }
+
+    async fn get_candles(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<StoredCandle>, StorageError> {
+        let sql = format!(
+            "SELECT {} FROM candles_1min
+            WHERE instrument_uid = ? AND time >= ? AND time <= ?
+            ORDER BY time",
+            CANDLE_COLUMNS
+        );
+
+        Ok(sqlx::query_as::<_, StoredCandle>(&sql)
+            .bind(instrument_uid)
+            .bind(from)
+            .bind(to)
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
+    async fn get_checkpoint(&self, instrument_uid: &str) -> Result<Option<i64>, StorageError> {
+        Ok(sqlx::query_scalar(
+            "SELECT last_1min_candle_date FROM candle_checkpoints WHERE instrument_uid = ?",
+        )
+        .bind(instrument_uid)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    async fn save_checkpoint(
+        &self,
+        instrument_uid: &str,
+        last_candle_time: i64,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            "INSERT INTO candle_checkpoints (instrument_uid, last_1min_candle_date) VALUES (?, ?)
+            ON CONFLICT (instrument_uid) DO UPDATE SET
+                last_1min_candle_date = excluded.last_1min_candle_date",
+        )
+        .bind(instrument_uid)
+        .bind(last_candle_time)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "sqlite"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(time: i64, volume: i64) -> HistoricCandle {
+        HistoricCandle {
+            volume,
+            time: Some(prost_types::Timestamp {
+                seconds: time,
+                nanos: 0,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn upserts_candles_and_keeps_checkpoints() {
+        // A single connection: every in-memory SQLite connection is a separate database
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repository = SqliteCandleRepository::with_pool(pool).await.unwrap();
+
+        let batch = vec![candle(60, 1), candle(120, 2), candle(180, 3)];
+        assert_eq!(repository.insert_candles(batch, "uid").await.unwrap(), 3);
+        repository
+            .insert_candles(vec![candle(120, 20)], "uid")
+            .await
+            .unwrap();
+
+        let candles = repository.get_candles("uid", 100, 200).await.unwrap();
+        let volumes: Vec<(i64, i64)> = candles.iter().map(|c| (c.time, c.volume)).collect();
+        assert_eq!(volumes, vec![(120, 20), (180, 3)]);
+
+        assert_eq!(repository.get_checkpoint("uid").await.unwrap(), None);
+        repository.save_checkpoint("uid", 120).await.unwrap();
+        repository.save_checkpoint("uid", 180).await.unwrap();
+        assert_eq!(repository.get_checkpoint("uid").await.unwrap(), Some(180));
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+use crate::env_config::models::retention::CandleResolution;
+use crate::generate::tinkoff_public_invest_api_contract_v1::{HistoricCandle, Quotation};
+
+/// Минутная свеча в том виде, в каком её хранят все бэкенды
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, clickhouse::Row, sqlx::FromRow)]
+pub struct StoredCandle {
+    pub instrument_uid: String,
+    pub time: i64, // Начало свечи, секунды
+    pub open_units: i64,
+    pub open_nano: i32,
+    pub high_units: i64,
+    pub high_nano: i32,
+    pub low_units: i64,
+    pub low_nano: i32,
+    pub close_units: i64,
+    pub close_nano: i32,
+    pub volume: i64,
+}
+
+impl StoredCandle {
+    /// Свеча без времени не может быть сохранена, для неё возвращается None
+    pub fn from_historic(instrument_uid: &str, candle: &HistoricCandle) -> Option<Self> {
+        let time = candle.time.as_ref()?.seconds;
+        Some(Self {
+            instrument_uid: instrument_uid.to_string(),
+            time,
+            open_units: candle.open.as_ref().map_or(0, |q| q.units),
+            open_nano: candle.open.as_ref().map_or(0, |q| q.nano),
+            high_units: candle.high.as_ref().map_or(0, |q| q.units),
+            high_nano: candle.high.as_ref().map_or(0, |q| q.nano),
+            low_units: candle.low.as_ref().map_or(0, |q| q.units),
+            low_nano: candle.low.as_ref().map_or(0, |q| q.nano),
+            close_units: candle.close.as_ref().map_or(0, |q| q.units),
+            close_nano: candle.close.as_ref().map_or(0, |q| q.nano),
+            volume: candle.volume,
+        })
+    }
+
+    pub fn from_historic_batch(instrument_uid: &str, candles: &[HistoricCandle]) -> Vec<Self> {
+        candles
+            .iter()
+            .filter_map(|candle| Self::from_historic(instrument_uid, candle))
+            .collect()
+    }
+
+    /// Обратное преобразование, например при перезаливке из архива
+    pub fn to_historic(&self) -> HistoricCandle {
+        let price = |units, nano| Some(Quotation { units, nano });
+        HistoricCandle {
+            open: price(self.open_units, self.open_nano),
+            high: price(self.high_units, self.high_nano),
+            low: price(self.low_units, self.low_nano),
+            close: price(self.close_units, self.close_nano),
+            volume: self.volume,
+            time: Some(prost_types::Timestamp {
+                seconds: self.time,
+                nanos: 0,
+            }),
+            is_complete: true,
+        }
+    }
+
+    /// Сворачивает отсортированные по времени минутные свечи в свечи `resolution`
+    ///
+    /// Так же, как таблицы свёрток ClickHouse: open первой минуты, close последней,
+    /// границы свечей по UTC
+    pub fn rollup(candles: &[StoredCandle], resolution: CandleResolution) -> Vec<StoredCandle> {
+        let mut result: Vec<StoredCandle> = Vec::new();
+        for candle in candles {
+            let bucket = resolution.bucket_start(candle.time);
+            match result.last_mut() {
+                Some(current)
+                    if current.time == bucket
+                        && current.instrument_uid == candle.instrument_uid =>
+                {
+                    if (candle.high_units, candle.high_nano)
+                        > (current.high_units, current.high_nano)
+                    {
+                        current.high_units = candle.high_units;
+                        current.high_nano = candle.high_nano;
+                    }
+                    if (candle.low_units, candle.low_nano) < (current.low_units, current.low_nano) {
+                        current.low_units = candle.low_units;
+                        current.low_nano = candle.low_nano;
+                    }
+                    current.close_units = candle.close_units;
+                    current.close_nano = candle.close_nano;
+                    current.volume += candle.volume;
+                }
+                _ => result.push(StoredCandle {
+                    time: bucket,
+                    ..candle.clone()
+                }),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(time: i64, open: i64, high: i64, low: i64, close: i64) -> StoredCandle {
+        StoredCandle {
+            instrument_uid: "uid".to_string(),
+            time,
+            open_units: open,
+            open_nano: 0,
+            high_units: high,
+            high_nano: 0,
+            low_units: low,
+            low_nano: 0,
+            close_units: close,
+            close_nano: 0,
+            volume: 10,
+        }
+    }
+
+    #[test]
+    fn rolls_minutes_into_utc_buckets() {
+        let minutes = [
+            candle(3600, 100, 105, 99, 101),
+            candle(3660, 101, 110, 100, 108),
+            candle(3900, 108, 109, 95, 96),
+            candle(7200, 96, 97, 96, 97),
+        ];
+
+        let hours = StoredCandle::rollup(&minutes, CandleResolution::Hour);
+        assert_eq!(hours.len(), 2);
+        assert_eq!(
+            hours[0],
+            StoredCandle {
+                volume: 30,
+                ..candle(3600, 100, 110, 95, 96)
+            }
+        );
+        assert_eq!(hours[1], candle(7200, 96, 97, 96, 97));
+
+        let five = StoredCandle::rollup(&minutes, CandleResolution::FiveMinutes);
+        assert_eq!(
+            five.iter().map(|c| c.time).collect::<Vec<_>>(),
+            vec![3600, 3900, 7200]
+        );
+    }
+}
@@ -16,6 +16,7 @@ impl AppConfig {
 
         let content = fs::read_to_string(path)?;
         let config: AppConfig = toml::from_str(&content)?;
+        config.validate()?;
 
         Ok(config)
     }
@@ -7,10 +7,10 @@ impl AppEnv {
         let env = get_env_var("ENV");
         let server_port = get_env_var("SERVER_PORT");
         let server_address = get_env_var("SERVER_ADDRESS");
-        let clickhouse_url = get_env_var("CLICKHOUSE_HOST");
-        let clickhouse_user = get_env_var("CLICKHOUSE_USER");
-        let clickhouse_password = get_env_var("CLICKHOUSE_PASSWORD");
-        let clickhouse_database = get_env_var("CLICKHOUSE_DATABASE");
+        let clickhouse_url = get_optional_env_var("CLICKHOUSE_HOST");
+        let clickhouse_user = get_optional_env_var("CLICKHOUSE_USER");
+        let clickhouse_password = get_optional_env_var("CLICKHOUSE_PASSWORD");
+        let clickhouse_database = get_optional_env_var("CLICKHOUSE_DATABASE");
         let tinkoff_token = get_env_var("TINKOFF_TOKEN");
 
         AppEnv {
@@ -22,10 +22,11 @@ impl AppEnv {
             clickhouse_password,
             clickhouse_database,
             tinkoff_token,
-            postgres_host: get_env_var("POSTGRES_HOST"),
-            postgres_user: get_env_var("POSTGRES_USER"),
-            postgres_password: get_env_var("POSTGRES_PASSWORD"),
-            postgres_database: get_env_var("POSTGRES_DATABASE"),
+            api_admin_key: env::var("API_ADMIN_KEY").ok().filter(|key| !key.is_empty()),
+            postgres_host: get_optional_env_var("POSTGRES_HOST"),
+            postgres_user: get_optional_env_var("POSTGRES_USER"),
+            postgres_password: get_optional_env_var("POSTGRES_PASSWORD"),
+            postgres_database: get_optional_env_var("POSTGRES_DATABASE"),
         }
     }
 }
@@ -39,3 +40,8 @@ impl Default for AppEnv {
 fn get_env_var(name: &str) -> String {
     env::var(name).unwrap_or_else(|_| panic!("ENV -> {} is not set", name))
 }
+
+// Database settings may be absent when the storage backend does not use that database
+fn get_optional_env_var(name: &str) -> String {
+    env::var(name).unwrap_or_default()
+}
@@ -1,21 +1,105 @@
-use chrono::NaiveTime;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
+use super::freshness::FreshnessConfig;
+use super::operation_window::OperationWindowConfig;
+use super::reconciliation::ReconciliationConfig;
+use super::retention::RetentionConfig;
+use super::schedule::{JobSchedule, ScheduleConfig};
+use super::source_comparison::SourceComparisonConfig;
+
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
     pub log: LogConfig,
+    pub postgres: PostgresConfig,
     pub clickhouse: ClickhouseConfig,
     pub tinkoff_api: TinkoffApiConfig,
     pub shares_scheduler: InstrumentsScheduler,
     pub candles_scheduler: CandlesScheduler,
+    pub candle_validation: CandleValidationConfig,
+    pub storage: StorageConfig,
+    pub archive: ArchiveConfig,
+    pub retention: RetentionConfig,
+    pub reconciliation: ReconciliationConfig,
+    pub source_comparison: SourceComparisonConfig,
+    pub freshness: FreshnessConfig,
+    pub orderbook_recorder: OrderBookRecorderConfig,
+    pub instrument_events: InstrumentEventsConfig,
+    pub universe: UniverseConfig,
+    pub exclusions: Vec<ExclusionConfig>,
+    pub shutdown: ShutdownConfig,
+    pub leader_election: LeaderElectionConfig,
+    pub api_auth: ApiAuthConfig,
+}
+impl AppConfig {
+    /// Проверяет настройки, ошибки в которых иначе проявились бы только во время работы
+    pub fn validate(&self) -> Result<(), String> {
+        JobSchedule::from_config(&self.shares_scheduler.schedule)
+            .map_err(|e| format!("shares_scheduler: {}", e))?;
+        JobSchedule::from_config(&self.candles_scheduler.schedule)
+            .map_err(|e| format!("candles_scheduler: {}", e))?;
+        JobSchedule::from_config(&self.archive.compaction)
+            .map_err(|e| format!("archive.compaction: {}", e))?;
+        JobSchedule::from_config(&self.retention.schedule)
+            .map_err(|e| format!("retention: {}", e))?;
+        self.retention
+            .validate()
+            .map_err(|e| format!("retention: {}", e))?;
+        JobSchedule::from_config(&self.reconciliation.schedule)
+            .map_err(|e| format!("reconciliation: {}", e))?;
+        self.reconciliation
+            .validate()
+            .map_err(|e| format!("reconciliation: {}", e))?;
+        JobSchedule::from_config(&self.source_comparison.schedule)
+            .map_err(|e| format!("source_comparison: {}", e))?;
+        self.source_comparison
+            .validate()
+            .map_err(|e| format!("source_comparison: {}", e))?;
+        JobSchedule::from_config(&self.freshness.schedule)
+            .map_err(|e| format!("freshness: {}", e))?;
+        self.freshness
+            .validate()
+            .map_err(|e| format!("freshness: {}", e))?;
+        self.orderbook_recorder
+            .validate()
+            .map_err(|e| format!("orderbook_recorder: {}", e))?;
+        self.tinkoff_api
+            .traffic
+            .validate()
+            .map_err(|e| format!("tinkoff_api.traffic: {}", e))?;
+        self.shares_scheduler
+            .operation_window
+            .validate()
+            .map_err(|e| format!("shares_scheduler.operation_window: {}", e))?;
+        self.candles_scheduler
+            .operation_window
+            .validate()
+            .map_err(|e| format!("candles_scheduler.operation_window: {}", e))?;
+        Ok(())
+    }
+
+    /// Нужен ли ClickHouse: без него не работает только одноимённое хранилище свечей
+    pub fn requires_clickhouse(&self) -> bool {
+        self.storage.backend == StorageBackend::Clickhouse
+    }
+
+    /// Нужен ли PostgreSQL: кроме хранилищ он держит блокировки задач и ключи API
+    pub fn requires_postgres(&self) -> bool {
+        matches!(
+            self.storage.backend,
+            StorageBackend::Clickhouse | StorageBackend::Postgres
+        ) || self.leader_election.enabled
+            || self.api_auth.enabled
+    }
 }
+
 #[derive(Debug, Deserialize)]
 pub struct InstrumentsScheduler {
     pub enabled: bool,
     pub initial_run: bool,
-    pub interval_seconds: u64,
-    pub start_time: String, // Start time in UTC, format: "HH:MM:SS"
-    pub end_time: String,   // End time in UTC, format: "HH:MM:SS"
+    #[serde(flatten)]
+    pub schedule: ScheduleConfig,
+    pub operation_window: OperationWindowConfig,
 }
 
 #[derive(Debug, Deserialize)]
@@ -24,18 +108,65 @@ pub struct LogConfig {
     pub format: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PostgresConfig {
+    pub timeout: u64, // seconds
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub max_lifetime: u64, // seconds
+    pub idle_timeout: u64, // seconds
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ClickhouseConfig {
     pub timeout: u64,
+    pub auto_migrate: bool, // Apply pending migrations on startup instead of only verifying the schema
 }
 
-
 #[derive(Debug, Deserialize)]
 pub struct TinkoffApiConfig {
     pub base_url: String,
     pub domain: String,
     pub timeout: u64,
     pub keepalive: u64,
+    pub fallback_urls: Vec<String>, // Used in order while base_url is unreachable, e.g. the sandbox
+    pub ca_cert: Option<String>, // PEM file with the CA of an https stand-in; http:// URLs skip TLS
+    pub health_check_seconds: u64, // How often every URL is probed for /ready; 0 disables probes
+    pub token_selection: TokenSelection,
+    pub traffic: TrafficConfig,
+}
+
+/// Выбор токена для очередного запроса, если в TINKOFF_TOKEN их несколько через запятую
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenSelection {
+    RoundRobin, // По очереди
+    LeastUsed,  // Токен с наименьшим числом запросов за текущую минуту
+}
+
+/// Запись и воспроизведение ответов Tinkoff API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrafficMode {
+    Off,    // Обычная работа с сетью
+    Record, // Ответы перечисленных методов сохраняются в dir
+    Replay, // Ответы берутся из dir, сеть не используется
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrafficConfig {
+    pub mode: TrafficMode,
+    pub dir: String,
+    pub methods: Vec<String>, // Имена методов gRPC, например GetCandles
+}
+
+impl TrafficConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.mode != TrafficMode::Off && (self.dir.is_empty() || self.methods.is_empty()) {
+            return Err("dir and methods are required to record or replay traffic".to_string());
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -43,10 +174,146 @@ pub struct CandlesScheduler {
     pub enabled: bool,
     pub initial_run: bool,
     pub request_delay_ms: u64,
+    #[serde(flatten)]
+    pub schedule: ScheduleConfig,
+    pub operation_window: OperationWindowConfig,
+}
+
+/// Per-job Postgres advisory locks, so only one replica runs each job
+#[derive(Debug, Deserialize)]
+pub struct LeaderElectionConfig {
+    pub enabled: bool,
+}
+
+/// Where minute candles are stored
+#[derive(Debug, Deserialize)]
+pub struct StorageConfig {
+    pub backend: StorageBackend,
+    pub sqlite_path: String,      // Used by the sqlite backend only
+    pub instruments: Vec<String>, // Loaded without PostgreSQL, when there is no watchlist
+}
 
-    pub start_time: String, // Start time in UTC, format: "HH:MM:SS"
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    Clickhouse,
+    Postgres, // Also TimescaleDB: the table becomes a hypertable when the extension is installed
+    Sqlite,
+    Memory, // Lost on restart, for tests and dry runs
+}
+
+/// Parquet copy of the candle history, independent of the storage backend
+#[derive(Debug, Deserialize)]
+pub struct ArchiveConfig {
+    pub enabled: bool,
+    pub path: String, // Root of the interval=/instrument_uid=/year=/month= tree
+    pub compaction: ScheduleConfig,
+}
 
-    pub end_time: String, // End time in UTC, format: "HH:MM:SS"
+/// API keys for mutating endpoints; read-only requests stay open
+#[derive(Debug, Deserialize)]
+pub struct ApiAuthConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShutdownConfig {
+    pub timeout_seconds: u64, // How long background tasks may take to finish after SIGTERM
+}
+
+/// Checks applied to candles before they are stored
+#[derive(Debug, Deserialize)]
+pub struct CandleValidationConfig {
+    pub max_volume: i64, // Candles with a larger volume (in lots) are quarantined
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderBookRecorderConfig {
+    pub enabled: bool,
+    pub depth: i32,                    // Order book depth: 1, 10, 20, 30, 40 or 50
+    pub instruments: Vec<String>,      // Instrument uids to subscribe to
+    pub sample_interval_ms: u64,       // How often the latest book is sampled
+    pub flush_interval_seconds: u64,   // How often sampled snapshots are written
+    pub max_buffered_snapshots: usize, // Oldest snapshots are dropped while ClickHouse is unavailable
+}
+
+impl OrderBookRecorderConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        // MarketDataStream accepts only these depths
+        if ![1, 10, 20, 30, 40, 50].contains(&self.depth) {
+            return Err("depth must be 1, 10, 20, 30, 40 or 50".to_string());
+        }
+        if self.sample_interval_ms == 0 || self.flush_interval_seconds == 0 {
+            return Err(
+                "sample_interval_ms and flush_interval_seconds must be positive".to_string(),
+            );
+        }
+        if self.max_buffered_snapshots == 0 {
+            return Err("max_buffered_snapshots must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InstrumentEventsConfig {
+    pub webhook_url: Option<String>, // Events are POSTed here as JSON when set
+    pub webhook_timeout_seconds: u64,
+}
+
+/// Rules that define which shares make up the instrument universe.
+/// Empty lists mean "no filter" for that attribute
+#[derive(Debug, Deserialize)]
+pub struct UniverseConfig {
+    pub sync_backfill: bool, // Keep instrument_candle_info in sync with the universe
+    pub exchanges: Vec<String>, // Matches `exchange` or `real_exchange`, e.g. "REAL_EXCHANGE_MOEX"
+    pub currencies: Vec<String>, // e.g. "rub"
+    pub sectors: Vec<String>, // e.g. "energy", "financial"
+    pub share_types: Vec<String>, // e.g. "SHARE_TYPE_COMMON"
+    pub include_qualified: bool, // Include shares only available to qualified investors
+    pub min_history_days: i64, // Minimum days since the first 1-minute candle
+    pub require_liquidity: bool, // Buy and sell available and a first 1-minute candle exists
+}
+
+/// Identifier an exclusion is keyed by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, serde::Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ExclusionKeyType {
+    Figi,
+    Uid,
+    Ticker,
+}
+
+impl ExclusionKeyType {
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Figi => "FIGI",
+            Self::Uid => "UID",
+            Self::Ticker => "TICKER",
+        }
+    }
+}
+
+impl std::str::FromStr for ExclusionKeyType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "FIGI" => Ok(Self::Figi),
+            "UID" => Ok(Self::Uid),
+            "TICKER" => Ok(Self::Ticker),
+            _ => Err(format!("Unknown exclusion key type: {}", s)),
+        }
+    }
+}
+
+/// Instrument excluded from share refresh, the universe and candle backfill
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExclusionConfig {
+    pub key_type: ExclusionKeyType,
+    pub key_value: String,
+    pub reason: String,
+    pub expires_at: Option<DateTime<Utc>>, // RFC 3339; the exclusion is ignored after this moment
 }
 
 // For CandlesScheduler
@@ -55,12 +322,8 @@ impl OperationWindow for CandlesScheduler {
         self.enabled
     }
 
-    fn start_time(&self) -> &str {
-        &self.start_time
-    }
-
-    fn end_time(&self) -> &str {
-        &self.end_time
+    fn operation_window(&self) -> &OperationWindowConfig {
+        &self.operation_window
     }
 }
 impl OperationWindow for InstrumentsScheduler {
@@ -68,12 +331,8 @@ impl OperationWindow for InstrumentsScheduler {
         self.enabled
     }
 
-    fn start_time(&self) -> &str {
-        &self.start_time
-    }
-
-    fn end_time(&self) -> &str {
-        &self.end_time
+    fn operation_window(&self) -> &OperationWindowConfig {
+        &self.operation_window
     }
 }
 
@@ -82,11 +341,8 @@ pub trait OperationWindow {
     /// Check if scheduler is enabled
     fn is_enabled(&self) -> bool;
 
-    /// Get the start time string
-    fn start_time(&self) -> &str;
-
-    /// Get the end time string
-    fn end_time(&self) -> &str;
+    /// Get the operation windows (validated when the configuration is loaded)
+    fn operation_window(&self) -> &OperationWindowConfig;
 
     /// Check if operation is allowed based on enabled flag and time window
     fn is_operation_allowed(&self) -> bool {
@@ -95,32 +351,6 @@ pub trait OperationWindow {
             return false;
         }
 
-        // Get current UTC time
-        let now = chrono::Utc::now().time();
-
-        // Parse start and end times
-        let (start_str, end_str) = (self.start_time(), self.end_time());
-        if let (Ok(start), Ok(end)) = (
-            NaiveTime::parse_from_str(start_str, "%H:%M:%S"),
-            NaiveTime::parse_from_str(end_str, "%H:%M:%S"),
-        ) {
-            // Check if current time is within the operation window
-            if start <= end {
-                // Simple case: start time is before end time
-                return start <= now && now <= end;
-            } else {
-                // Case where operation window crosses midnight
-                // e.g., start=21:00:00, end=04:00:00
-                return start <= now || now <= end;
-            }
-        }
-
-        // If parsing fails, log the error and default to not allowing operation
-        tracing::error!(
-            "Failed to parse time window: start='{}', end='{}'",
-            start_str,
-            end_str
-        );
-        false
+        self.operation_window().contains(chrono::Utc::now())
     }
 }
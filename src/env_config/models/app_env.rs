@@ -15,7 +15,8 @@ pub struct AppEnv {
     pub postgres_password: String,
     pub postgres_database: String,
     //
-    pub tinkoff_token: String,
+    pub tinkoff_token: String, // Один или несколько токенов через запятую
+    pub api_admin_key: Option<String>, // Ключ администратора API, заносится в api_keys при старте
     //
     pub server_port: u16,
     pub server_address: String,
@@ -30,6 +31,16 @@ impl AppEnv {
     pub fn is_local(&self) -> bool {
         matches!(self.env, Env::Local)
     }
+
+    /// Токены Tinkoff из TINKOFF_TOKEN: один или несколько через запятую
+    pub fn tinkoff_tokens(&self) -> Vec<String> {
+        self.tinkoff_token
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
 }
 impl FromStr for Env {
     type Err = String;
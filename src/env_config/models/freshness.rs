@@ -0,0 +1,83 @@
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+use super::schedule::ScheduleConfig;
+
+/// Body of the alert request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertFormat {
+    /// Structured JSON with every breached and recovered instrument
+    Json,
+    /// Slack incoming webhook: {"text": ...}
+    Slack,
+    /// Telegram Bot API sendMessage: {"chat_id": ..., "text": ...}
+    Telegram,
+}
+
+/// Maximum age of the last stored candle for a group of instruments
+#[derive(Debug, Clone, Deserialize)]
+pub struct FreshnessSla {
+    pub name: String,
+    #[serde(default)]
+    pub instruments: Vec<String>, // Instrument uids; empty = every active instrument without its own SLA
+    pub max_lag_trading_days: u32, // Finished trading days after the last candle's date; today is not counted
+}
+
+/// Periodic check of candle freshness with webhook alerts
+#[derive(Debug, Deserialize)]
+pub struct FreshnessConfig {
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub schedule: ScheduleConfig,
+    pub timezone: Tz, // Trading days are counted in this time zone
+    #[serde(default)]
+    pub holidays: Vec<NaiveDate>, // Weekdays without trading, format: "YYYY-MM-DD"
+    pub webhook_url: Option<String>,
+    pub webhook_timeout_seconds: u64,
+    pub format: AlertFormat,
+    pub telegram_chat_id: Option<String>, // Required for format = "telegram"
+    pub repeat_after_minutes: i64, // A still breached instrument is reported again after this time
+    #[serde(default)]
+    pub slas: Vec<FreshnessSla>,
+}
+
+impl FreshnessConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.repeat_after_minutes <= 0 {
+            return Err("repeat_after_minutes must be positive".to_string());
+        }
+        if self.format == AlertFormat::Telegram && self.telegram_chat_id.is_none() {
+            return Err("telegram_chat_id is required for format = \"telegram\"".to_string());
+        }
+        for (index, sla) in self.slas.iter().enumerate() {
+            let others = &self.slas[..index];
+            if others.iter().any(|other| other.name == sla.name) {
+                return Err(format!("SLA {} is defined twice", sla.name));
+            }
+            let overlaps = others.iter().any(|other| {
+                (other.instruments.is_empty() && sla.instruments.is_empty())
+                    || other
+                        .instruments
+                        .iter()
+                        .any(|uid| sla.instruments.contains(uid))
+            });
+            if overlaps {
+                return Err(format!(
+                    "SLA {}: its instruments already belong to another SLA",
+                    sla.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// SLA of an instrument: its own, otherwise the default one without instruments
+    pub fn sla_for(&self, instrument_uid: &str) -> Option<&FreshnessSla> {
+        self.slas
+            .iter()
+            .find(|sla| sla.instruments.iter().any(|uid| uid == instrument_uid))
+            .or_else(|| self.slas.iter().find(|sla| sla.instruments.is_empty()))
+    }
+}
@@ -1,3 +1,9 @@
 pub mod app_config;
 pub mod app_env;
 pub mod app_setting;
+pub mod freshness;
+pub mod operation_window;
+pub mod reconciliation;
+pub mod retention;
+pub mod schedule;
+pub mod source_comparison;
@@ -0,0 +1,153 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+/// One working interval, applied on the listed weekdays.
+/// If `end` is not after `start`, the window runs past midnight into the next day
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimeWindow {
+    pub days: Vec<Weekday>, // Day the window starts on: "mon", "tue", ...
+    pub start: NaiveTime,   // Local time, format: "HH:MM:SS"
+    pub end: NaiveTime,     // Local time, format: "HH:MM:SS"
+}
+
+/// When a job may run, in the local time of a named time zone
+#[derive(Debug, Clone, Deserialize)]
+pub struct OperationWindowConfig {
+    pub timezone: Tz,             // IANA name, e.g. "Europe/Moscow"
+    pub windows: Vec<TimeWindow>, // Several windows per day are allowed
+    pub holidays: Vec<NaiveDate>, // Local dates, format: "YYYY-MM-DD"; windows starting on them are skipped
+}
+
+impl TimeWindow {
+    fn crosses_midnight(&self) -> bool {
+        self.end <= self.start
+    }
+}
+
+impl OperationWindowConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.windows.is_empty() {
+            return Err("at least one window is required".to_string());
+        }
+        for window in &self.windows {
+            if window.days.is_empty() {
+                return Err(format!(
+                    "window {}-{} has no days",
+                    window.start, window.end
+                ));
+            }
+            if window.start == window.end {
+                return Err(format!(
+                    "window {}-{} is empty, use 00:00:00-23:59:59 for a whole day",
+                    window.start, window.end
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Проверяет, попадает ли момент в одно из окон
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let local = at.with_timezone(&self.timezone).naive_local();
+        let (date, time) = (local.date(), local.time());
+        let previous_date = date - Duration::days(1);
+
+        let is_working_day = |day: NaiveDate, window: &TimeWindow| {
+            window.days.contains(&day.weekday()) && !self.holidays.contains(&day)
+        };
+
+        self.windows.iter().any(|window| {
+            if window.crosses_midnight() {
+                // Evening part belongs to today, the part after midnight to the previous day
+                (time >= window.start && is_working_day(date, window))
+                    || (time <= window.end && is_working_day(previous_date, window))
+            } else {
+                time >= window.start && time <= window.end && is_working_day(date, window)
+            }
+        })
+    }
+
+    /// Описание окон для логов
+    pub fn describe(&self) -> String {
+        let windows: Vec<String> = self
+            .windows
+            .iter()
+            .map(|w| {
+                let days: Vec<String> = w.days.iter().map(|d| d.to_string()).collect();
+                format!("{} {}-{}", days.join(","), w.start, w.end)
+            })
+            .collect();
+        format!(
+            "{} ({}; {} holidays)",
+            windows.join("; "),
+            self.timezone,
+            self.holidays.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn config() -> OperationWindowConfig {
+        toml::from_str(
+            r#"
+            timezone = "Europe/Moscow"
+            holidays = ["2025-01-01"]
+
+            [[windows]]
+            days = ["mon", "tue", "wed", "thu", "fri"]
+            start = "07:00:00"
+            end = "00:00:00"
+
+            [[windows]]
+            days = ["sat"]
+            start = "10:00:00"
+            end = "12:00:00"
+            "#,
+        )
+        .unwrap()
+    }
+
+    /// Moscow is UTC+3 all year round
+    fn msk(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap() - Duration::hours(3)
+    }
+
+    #[test]
+    fn test_contains_respects_timezone_weekdays_and_holidays() {
+        let config = config();
+        assert!(config.validate().is_ok());
+
+        // Monday 2025-03-03
+        assert!(config.contains(msk(2025, 3, 3, 7, 0)));
+        assert!(!config.contains(msk(2025, 3, 3, 6, 59)));
+        // Overnight tail of Friday's window, but not of Sunday's
+        assert!(config.contains(msk(2025, 3, 8, 0, 0)));
+        assert!(!config.contains(msk(2025, 3, 10, 0, 0)));
+        // Second window on Saturday only
+        assert!(config.contains(msk(2025, 3, 8, 11, 0)));
+        assert!(!config.contains(msk(2025, 3, 9, 11, 0)));
+        // Wednesday 2025-01-01 is a holiday
+        assert!(!config.contains(msk(2025, 1, 1, 12, 0)));
+    }
+
+    #[test]
+    fn test_validate_rejects_misconfigured_windows() {
+        let mut config = config();
+        config.windows[0].end = config.windows[0].start;
+        assert!(config.validate().is_err());
+
+        let mut config = self::config();
+        config.windows[1].days.clear();
+        assert!(config.validate().is_err());
+
+        let bad_timezone = toml::from_str::<OperationWindowConfig>(
+            "timezone = \"Mars/Olympus\"\nholidays = []\nwindows = []",
+        );
+        assert!(bad_timezone.is_err());
+    }
+}
@@ -0,0 +1,28 @@
+use serde::Deserialize;
+
+use super::schedule::ScheduleConfig;
+
+/// Daily comparison of stored 1min candles with official daily candles
+#[derive(Debug, Deserialize)]
+pub struct ReconciliationConfig {
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub schedule: ScheduleConfig,
+    pub lookback_days: i64,   // How many finished days are checked on each run
+    pub price_tolerance: f64, // Allowed relative difference of open/high/low/close
+    pub volume_tolerance: f64, // Allowed relative difference of the daily volume
+    pub refetch: bool,        // Queue mismatched days for the candle loader to fetch again
+}
+
+impl ReconciliationConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        // A single GetCandles request returns at most a year of daily candles
+        if !(1..=365).contains(&self.lookback_days) {
+            return Err("lookback_days must be between 1 and 365".to_string());
+        }
+        if self.price_tolerance < 0.0 || self.volume_tolerance < 0.0 {
+            return Err("tolerances must not be negative".to_string());
+        }
+        Ok(())
+    }
+}
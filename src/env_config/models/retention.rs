@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+
+use super::schedule::ScheduleConfig;
+
+/// Resolution of a stored candle table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub enum CandleResolution {
+    #[serde(rename = "1min")]
+    Minute,
+    #[serde(rename = "5min")]
+    FiveMinutes,
+    #[serde(rename = "1hour")]
+    Hour,
+    #[serde(rename = "1day")]
+    Day,
+}
+
+impl CandleResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleResolution::Minute => "1min",
+            CandleResolution::FiveMinutes => "5min",
+            CandleResolution::Hour => "1hour",
+            CandleResolution::Day => "1day",
+        }
+    }
+
+    /// ClickHouse table holding candles of this resolution
+    pub fn table(&self) -> &'static str {
+        match self {
+            CandleResolution::Minute => "tinkoff_candles_1min",
+            CandleResolution::FiveMinutes => "tinkoff_candles_5min",
+            CandleResolution::Hour => "tinkoff_candles_1hour",
+            CandleResolution::Day => "tinkoff_candles_1day",
+        }
+    }
+
+    /// Candle length in seconds; buckets are aligned to UTC
+    pub fn seconds(&self) -> i64 {
+        match self {
+            CandleResolution::Minute => 60,
+            CandleResolution::FiveMinutes => 300,
+            CandleResolution::Hour => 3600,
+            CandleResolution::Day => 86400,
+        }
+    }
+
+    /// ClickHouse expression that maps `column` to the start of its bucket in UTC
+    pub fn bucket_expr(&self, column: &str) -> String {
+        let function = match self {
+            CandleResolution::Minute => "toStartOfMinute",
+            CandleResolution::FiveMinutes => "toStartOfFiveMinutes",
+            CandleResolution::Hour => "toStartOfHour",
+            CandleResolution::Day => "toStartOfDay",
+        };
+        format!("{}({}, 'UTC')", function, column)
+    }
+
+    /// Start of the bucket containing `time` (seconds)
+    pub fn bucket_start(&self, time: i64) -> i64 {
+        time - time.rem_euclid(self.seconds())
+    }
+}
+
+/// Retention of one resolution for a group of instruments
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetentionPolicy {
+    pub resolution: CandleResolution,
+    #[serde(default)]
+    pub instruments: Vec<String>, // Instrument uids; empty = every instrument without its own policy
+    pub keep_days: Option<i64>, // Candles older than this are dropped; unset = kept forever
+    pub downsample_to: Option<CandleResolution>, // Roll 1min candles into this table before dropping them
+}
+
+/// Retention policies and the schedule of the maintenance job
+#[derive(Debug, Deserialize)]
+pub struct RetentionConfig {
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub schedule: ScheduleConfig,
+    #[serde(default)]
+    pub policies: Vec<RetentionPolicy>,
+}
+
+impl RetentionPolicy {
+    /// Instrument group for logs and reports
+    pub fn describe_instruments(&self) -> String {
+        if self.instruments.is_empty() {
+            "default".to_string()
+        } else {
+            self.instruments.join(",")
+        }
+    }
+}
+
+impl RetentionConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for (index, policy) in self.policies.iter().enumerate() {
+            let name = format!("policy {} ({})", index + 1, policy.resolution.as_str());
+            if policy.keep_days.is_some_and(|days| days <= 0) {
+                return Err(format!("{}: keep_days must be positive", name));
+            }
+            if let Some(target) = policy.downsample_to {
+                if policy.resolution != CandleResolution::Minute {
+                    return Err(format!("{}: only 1min candles can be downsampled", name));
+                }
+                if target == CandleResolution::Minute {
+                    return Err(format!("{}: downsample_to must be coarser than 1min", name));
+                }
+                if policy.keep_days.is_none() {
+                    return Err(format!("{}: downsample_to requires keep_days", name));
+                }
+            }
+
+            // An instrument (or the default group) may have one policy per resolution
+            let overlaps = self.policies[..index].iter().any(|other| {
+                other.resolution == policy.resolution
+                    && ((other.instruments.is_empty() && policy.instruments.is_empty())
+                        || other
+                            .instruments
+                            .iter()
+                            .any(|uid| policy.instruments.contains(uid)))
+            });
+            if overlaps {
+                return Err(format!(
+                    "{}: instruments {} already have a policy for this resolution",
+                    name,
+                    policy.describe_instruments()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Instruments that have their own policy for the resolution; the default group excludes them
+    pub fn explicit_instruments(&self, resolution: CandleResolution) -> Vec<String> {
+        self.policies
+            .iter()
+            .filter(|policy| policy.resolution == resolution)
+            .flat_map(|policy| policy.instruments.iter().cloned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env_config::models::schedule::CatchUpPolicy;
+
+    fn policy(resolution: CandleResolution, instruments: &[&str]) -> RetentionPolicy {
+        RetentionPolicy {
+            resolution,
+            instruments: instruments.iter().map(|uid| uid.to_string()).collect(),
+            keep_days: Some(1095),
+            downsample_to: None,
+        }
+    }
+
+    fn config(policies: Vec<RetentionPolicy>) -> RetentionConfig {
+        RetentionConfig {
+            enabled: true,
+            schedule: ScheduleConfig {
+                cron: None,
+                run_times: vec!["02:00:00".to_string()],
+                catch_up: CatchUpPolicy::Skip,
+            },
+            policies,
+        }
+    }
+
+    #[test]
+    fn test_policy_validation() {
+        let mut minutes = policy(CandleResolution::Minute, &[]);
+        minutes.downsample_to = Some(CandleResolution::Hour);
+        let ok = config(vec![
+            minutes.clone(),
+            policy(CandleResolution::Minute, &["a", "b"]),
+            policy(CandleResolution::Hour, &[]),
+        ]);
+        assert!(ok.validate().is_ok());
+        assert_eq!(
+            ok.explicit_instruments(CandleResolution::Minute),
+            vec!["a", "b"]
+        );
+
+        let overlap = config(vec![
+            policy(CandleResolution::Minute, &["a"]),
+            policy(CandleResolution::Minute, &["b", "a"]),
+        ]);
+        assert!(overlap.validate().is_err());
+
+        let mut forever = minutes;
+        forever.keep_days = None;
+        assert!(config(vec![forever]).validate().is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Days, NaiveTime, Utc};
+use serde::Deserialize;
+
+/// What to do with runs that were due while the job was not running
+/// (the application was down or the previous run overran)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatchUpPolicy {
+    /// Missed runs are dropped, the job waits for the next scheduled time
+    Skip,
+    /// All missed runs are collapsed into a single immediate run
+    RunOnce,
+}
+
+/// Schedule settings shared by all scheduled jobs; exactly one of
+/// `cron` and `run_times` must be set
+#[derive(Debug, Deserialize)]
+pub struct ScheduleConfig {
+    pub cron: Option<String>, // "sec min hour day-of-month month day-of-week [year]", UTC
+    pub run_times: Vec<String>, // Daily run times in UTC, format: "HH:MM:SS"
+    pub catch_up: CatchUpPolicy,
+}
+
+/// Parsed job schedule
+#[derive(Debug, Clone)]
+pub enum JobSchedule {
+    Cron(Box<cron::Schedule>),
+    DailyTimes(Vec<NaiveTime>),
+}
+
+impl JobSchedule {
+    pub fn from_config(config: &ScheduleConfig) -> Result<Self, String> {
+        match (&config.cron, config.run_times.is_empty()) {
+            (Some(_), false) => Err("set either `cron` or `run_times`, not both".to_string()),
+            (None, true) => Err("either `cron` or `run_times` must be set".to_string()),
+            (Some(expression), true) => cron::Schedule::from_str(expression)
+                .map(|schedule| Self::Cron(Box::new(schedule)))
+                .map_err(|e| format!("invalid cron expression '{}': {}", expression, e)),
+            (None, false) => {
+                let mut times = config
+                    .run_times
+                    .iter()
+                    .map(|t| {
+                        NaiveTime::parse_from_str(t, "%H:%M:%S")
+                            .map_err(|e| format!("invalid run time '{}': {}", t, e))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                times.sort();
+                times.dedup();
+                Ok(Self::DailyTimes(times))
+            }
+        }
+    }
+
+    /// Первый запуск строго после `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Cron(schedule) => schedule.after(&after).next(),
+            Self::DailyTimes(times) => {
+                let today = after.date_naive();
+                let tomorrow = today.checked_add_days(Days::new(1))?;
+                times
+                    .iter()
+                    .map(|t| today.and_time(*t).and_utc())
+                    .find(|candidate| *candidate > after)
+                    .or_else(|| times.first().map(|t| tomorrow.and_time(*t).and_utc()))
+            }
+        }
+    }
+
+    /// Описание расписания для отчёта
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Cron(schedule) => format!("cron {}", schedule),
+            Self::DailyTimes(times) => {
+                let times: Vec<String> = times.iter().map(|t| t.to_string()).collect();
+                format!("daily at {} UTC", times.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn config(cron: Option<&str>, run_times: &[&str]) -> ScheduleConfig {
+        ScheduleConfig {
+            cron: cron.map(str::to_string),
+            run_times: run_times.iter().map(|t| t.to_string()).collect(),
+            catch_up: CatchUpPolicy::Skip,
+        }
+    }
+
+    #[test]
+    fn test_daily_times_next_run() {
+        let schedule = JobSchedule::from_config(&config(None, &["16:00:00", "04:00:00"])).unwrap();
+        let at = |d, h, m| Utc.with_ymd_and_hms(2025, 3, d, h, m, 0).unwrap();
+
+        assert_eq!(schedule.next_after(at(3, 1, 0)), Some(at(3, 4, 0)));
+        assert_eq!(schedule.next_after(at(3, 4, 0)), Some(at(3, 16, 0)));
+        assert_eq!(schedule.next_after(at(3, 20, 0)), Some(at(4, 4, 0)));
+    }
+
+    #[test]
+    fn test_cron_next_run() {
+        let schedule = JobSchedule::from_config(&config(Some("0 30 2 * * *"), &[])).unwrap();
+        let after = Utc.with_ymd_and_hms(2025, 3, 3, 2, 30, 0).unwrap();
+
+        assert_eq!(
+            schedule.next_after(after),
+            Some(Utc.with_ymd_and_hms(2025, 3, 4, 2, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_invalid_schedules_are_rejected() {
+        assert!(JobSchedule::from_config(&config(None, &[])).is_err());
+        assert!(JobSchedule::from_config(&config(Some("0 0 * * * *"), &["01:00:00"])).is_err());
+        assert!(JobSchedule::from_config(&config(Some("not a cron"), &[])).is_err());
+        assert!(JobSchedule::from_config(&config(None, &["25:00:00"])).is_err());
+    }
+}
@@ -0,0 +1,42 @@
+use serde::Deserialize;
+
+use super::retention::CandleResolution;
+use super::schedule::ScheduleConfig;
+
+/// MOEX ISS HTTP API, the second candle source
+#[derive(Debug, Deserialize)]
+pub struct MoexIssConfig {
+    pub base_url: String, // https://iss.moex.com
+    pub timeout: u64,     // seconds
+}
+
+/// Comparison of stored Tinkoff candles with MOEX ISS candles
+#[derive(Debug, Deserialize)]
+pub struct SourceComparisonConfig {
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub schedule: ScheduleConfig,
+    pub resolution: CandleResolution, // 1min, 1hour or 1day; ISS has no 5min candles
+    pub lookback_days: i64,           // How many finished days are compared on each run
+    pub price_tolerance: f64,         // Allowed relative difference of open/high/low/close
+    pub volume_tolerance: f64,        // Allowed relative difference of the volume in lots
+    pub moex_iss: MoexIssConfig,
+}
+
+impl SourceComparisonConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.resolution == CandleResolution::FiveMinutes {
+            return Err("MOEX ISS has no 5min candles".to_string());
+        }
+        if !(1..=31).contains(&self.lookback_days) {
+            return Err("lookback_days must be between 1 and 31".to_string());
+        }
+        if self.price_tolerance < 0.0 || self.volume_tolerance < 0.0 {
+            return Err("tolerances must not be negative".to_string());
+        }
+        if self.moex_iss.base_url.is_empty() {
+            return Err("moex_iss.base_url is required".to_string());
+        }
+        Ok(())
+    }
+}
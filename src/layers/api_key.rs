@@ -0,0 +1,82 @@
+use axum::{
+    extract::{Extension, Request},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use tracing::{debug, error, warn};
+
+use crate::{
+    app_state::models::AppState,
+    services::api_keys::{self, API_KEY_HEADER},
+};
+
+/// Управление ключами закрыто целиком, включая просмотр списка
+const KEY_MANAGEMENT_PATH: &str = "/api/keys";
+
+/// Проверяет ключ API у изменяющих запросов
+///
+/// GET, HEAD и OPTIONS проходят без ключа (кроме /api/keys); остальные методы
+/// требуют действующий ключ из таблицы api_keys в заголовке X-API-Key, иначе 401
+pub async fn require_api_key(
+    Extension(app_state): Extension<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let read_only = matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    );
+    let key_management = request.uri().path().starts_with(KEY_MANAGEMENT_PATH);
+    if (read_only && !key_management) || !app_state.settings.app_config.api_auth.enabled {
+        return Ok(next.run(request).await);
+    }
+
+    let Some(key) = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        warn!(
+            "Rejected {} {}: missing API key",
+            request.method(),
+            request.uri().path()
+        );
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+
+    // api_auth makes PostgreSQL required at startup, so it is connected here
+    let postgres = app_state
+        .postgres_service
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let api_key = postgres
+        .repository_api_key
+        .authenticate(&api_keys::hash_key(key))
+        .await
+        .map_err(|e| {
+            error!("Failed to check API key: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    match api_key {
+        Some(api_key) => {
+            debug!(
+                "{} {} authorized with API key {}",
+                request.method(),
+                request.uri().path(),
+                api_key.name
+            );
+            Ok(next.run(request).await)
+        }
+        None => {
+            warn!(
+                "Rejected {} {}: unknown or revoked API key",
+                request.method(),
+                request.uri().path()
+            );
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+}
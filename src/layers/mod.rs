@@ -1,2 +1,4 @@
+mod api_key;
 mod layer;
+pub use api_key::require_api_key;
 pub use layer::{create_cors, create_trace};
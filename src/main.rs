@@ -1,5 +1,6 @@
 mod api;
 mod app_state;
+mod cli;
 mod db;
 mod env_config;
 mod generate;
@@ -9,19 +10,42 @@ mod services;
 mod utils;
 
 use app_state::models::AppState;
-use axum::{Router, routing::get};
-use db::clickhouse::clickhouse_service::ClickhouseService;
+use axum::{
+    Router,
+    routing::{delete, get},
+};
+use cli::Command;
+use db::{
+    clickhouse::{
+        clickhouse_service::ClickhouseService, connection::ClickhouseConnection,
+        repository::candle_repository::ClickhouseCandleRepository, schema::SchemaMode,
+    },
+    postgres::postgres_service::PostgresService,
+    storage::{backend::create_candle_repository, candle_repository::CandleRepository},
+};
 use env_config::models::{app_config::AppConfig, app_env::AppEnv, app_setting::AppSettings};
-use layers::{create_cors, create_trace};
+use layers::{create_cors, create_trace, require_api_key};
 use services::{
+    archive::{
+        archive_compaction::ArchiveCompactionScheduler, parquet_archive::ParquetArchive,
+        rebuild::rebuild_clickhouse,
+    },
     candles::{client_candle::ClientCandle, scheduler_candles::SchedulerCandles},
-
+    freshness::freshness_checker::FreshnessScheduler,
+    orderbook::recorder_orderbook::RecorderOrderBook,
+    reconciliation::reconciliation_scheduler::ReconciliationScheduler,
+    retention::retention_scheduler::RetentionScheduler,
+    rollup::rollup_maintenance::RollupMaintenance,
     shares::shares_scheduler::InstrumentsScheduler,
+    source_comparison::comparison_scheduler::SourceComparisonScheduler,
+    sources::tinkoff_source::TinkoffCandleSource,
     tinkoff_client_grpc::TinkoffClient,
+    tinkoff_mock::{fixtures::MockFixtures, mock_server::MockTinkoff},
 };
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tokio::net::TcpListener;
-use tracing::{debug, error, info};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 
 /// Initializes the application's configuration and logging system
 ///
@@ -59,22 +83,75 @@ async fn initialize_application() -> AppSettings {
 }
 
 /// Establishes connections to databases
-async fn initialize_database_connections(settings: Arc<AppSettings>) -> ClickhouseService {
+///
+/// A database the configuration does not depend on is optional: when it is
+/// unreachable the features built on it are disabled instead of stopping the start
+async fn initialize_database_connections(
+    settings: Arc<AppSettings>,
+) -> (Option<ClickhouseService>, Option<PostgresService>) {
     info!("Initializing database connections...");
+    let config = &settings.app_config;
 
     // Initialize ClickHouse connection
     let clickhouse_service = match ClickhouseService::new(&settings).await {
         Ok(service) => {
             info!("ClickHouse connection established successfully");
-            service
+            Some(service)
         }
-        Err(err) => {
+        Err(err) if config.requires_clickhouse() => {
             error!("Failed to connect to ClickHouse: {}", err);
             panic!("Cannot continue without ClickHouse connection");
         }
+        Err(err) => {
+            warn!(
+                "ClickHouse is unavailable, features that need it are disabled: {}",
+                err
+            );
+            None
+        }
     };
 
-    clickhouse_service
+    // Initialize PostgreSQL connection and apply migrations
+    let postgres_service = match PostgresService::new(&settings).await {
+        Ok(service) => {
+            info!("PostgreSQL connection established successfully");
+            Some(service)
+        }
+        Err(err) if config.requires_postgres() => {
+            error!("Failed to connect to PostgreSQL: {}", err);
+            panic!("Cannot continue without PostgreSQL connection");
+        }
+        Err(err) => {
+            warn!(
+                "PostgreSQL is unavailable, features that need it are disabled: {}",
+                err
+            );
+            None
+        }
+    };
+
+    (clickhouse_service, postgres_service)
+}
+
+/// Opens the candle storage selected in the [storage] section
+async fn initialize_candle_storage(
+    settings: &AppSettings,
+    clickhouse_service: Option<&ClickhouseService>,
+    postgres_service: Option<&PostgresService>,
+) -> Arc<dyn CandleRepository + Send + Sync> {
+    match create_candle_repository(
+        &settings.app_config.storage,
+        clickhouse_service,
+        postgres_service,
+    )
+    .await
+    {
+        Ok(repository) => repository,
+        Err(err) => {
+            error!("Failed to open candle storage: {}", err);
+            panic!("Cannot continue without candle storage");
+        }
+    }
 }
 
 /// Creates the application router with all API endpoints and middleware
@@ -83,12 +160,97 @@ fn create_application_router(app_state: Arc<AppState>) -> Router {
         .layer(create_cors())
         .route("/api-health", get(api::health_api))
         .route("/db-health", get(api::health_db))
+        .route("/ready", get(api::get_readiness))
+        .route("/metrics", get(api::get_metrics))
+        .route("/api/orderbook", get(api::get_orderbook))
+        .route("/api/shares/catalog", get(api::get_share_catalog))
+        .route("/api/instruments/events", get(api::get_instrument_events))
+        .route("/api/candles", get(api::get_candles))
+        .route("/api/candles/quarantine", get(api::get_quarantine_report))
+        .route("/api/jobs/runs", get(api::get_job_runs))
+        .route("/api/jobs/instruments", get(api::get_job_instruments))
+        .route("/api/schedules", get(api::get_schedules))
+        .route("/api/retention/report", get(api::get_retention_report))
+        .route("/api/rollups/check", get(api::get_rollup_check))
+        .route(
+            "/api/reconciliation/report",
+            get(api::get_reconciliation_report),
+        )
+        .route("/api/sources/divergences", get(api::get_source_divergences))
+        .route("/api/freshness/alerts", get(api::get_freshness_alerts))
+        .route(
+            "/api/exclusions",
+            get(api::list_exclusions).post(api::add_exclusion),
+        )
+        .route(
+            "/api/exclusions/{key_type}/{key_value}",
+            delete(api::delete_exclusion),
+        )
+        .route(
+            "/api/watchlist",
+            get(api::get_watchlist).post(api::add_to_watchlist),
+        )
+        .route("/api/watchlist/{uid}", delete(api::remove_from_watchlist))
+        .route(
+            "/api/keys",
+            get(api::list_api_keys).post(api::create_api_key),
+        )
+        .route("/api/keys/{name}", delete(api::revoke_api_key))
+        .route_layer(axum::middleware::from_fn(require_api_key))
         .layer(axum::Extension(app_state.clone()))
         .layer(create_trace())
 }
 
-/// Starts the HTTP server on the specified address
-async fn start_http_server(app: Router, addr: SocketAddr) {
+/// Waits for SIGTERM or Ctrl+C and cancels the shutdown token
+async fn listen_for_shutdown_signal(shutdown: CancellationToken) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received Ctrl+C"),
+        _ = terminate => info!("Received SIGTERM"),
+    }
+
+    info!("Shutting down: no longer accepting requests, stopping background tasks");
+    shutdown.cancel();
+}
+
+/// Waits for background tasks to finish their in-flight work, up to the configured deadline
+async fn wait_for_background_tasks(app_state: &AppState) {
+    let timeout = Duration::from_secs(app_state.settings.app_config.shutdown.timeout_seconds);
+
+    app_state.tasks.close();
+    info!(
+        "Waiting up to {}s for {} background tasks to finish",
+        timeout.as_secs(),
+        app_state.tasks.len()
+    );
+
+    match tokio::time::timeout(timeout, app_state.tasks.wait()).await {
+        Ok(()) => info!("All background tasks finished"),
+        Err(_) => warn!(
+            "Shutdown deadline exceeded, {} background tasks are still running",
+            app_state.tasks.len()
+        ),
+    }
+}
+
+/// Starts the HTTP server on the specified address and serves until shutdown is requested
+async fn start_http_server(app: Router, addr: SocketAddr, shutdown: CancellationToken) {
     info!("Starting HTTP server on {}", addr);
 
     let listener = match TcpListener::bind(addr).await {
@@ -101,7 +263,10 @@ async fn start_http_server(app: Router, addr: SocketAddr) {
 
     info!("Server started successfully, now accepting connections");
 
-    if let Err(err) = axum::serve(listener, app).await {
+    if let Err(err) = axum::serve(listener, app)
+        .with_graceful_shutdown(async move { shutdown.cancelled().await })
+        .await
+    {
         error!("Server error: {}", err);
         panic!("Server failed: {}", err);
     }
@@ -116,20 +281,198 @@ async fn initialize_background_services(app_state: Arc<AppState>) {
     // Initialize the candles scheduler
     let candles_scheduler = SchedulerCandles::new(app_state.clone());
 
-    // Start both schedulers (they'll check their enabled status internally)
+    // Initialize the order book recorder
+    let orderbook_recorder = RecorderOrderBook::new(app_state.clone());
+
+    // Initialize the daily compaction of the Parquet archive
+    let archive_compaction = ArchiveCompactionScheduler::new(app_state.clone());
+
+    // Initialize the candle retention maintenance
+    let retention_scheduler = RetentionScheduler::new(app_state.clone());
+
+    // Initialize the daily reconciliation against official daily candles
+    let reconciliation_scheduler = ReconciliationScheduler::new(app_state.clone());
+
+    // Initialize the comparison with MOEX ISS candles
+    let source_comparison_scheduler = SourceComparisonScheduler::new(app_state.clone());
+
+    // Initialize the candle freshness monitoring
+    let freshness_scheduler = FreshnessScheduler::new(app_state.clone());
+
+    // Start all services (they'll check their enabled status internally)
     shares_scheduler.start().await;
     candles_scheduler.start().await;
+    orderbook_recorder.start().await;
+    archive_compaction.start().await;
+    retention_scheduler.start().await;
+    reconciliation_scheduler.start().await;
+    source_comparison_scheduler.start().await;
+    freshness_scheduler.start().await;
 
     info!("Background services initialization completed");
 }
 
+/// Applies pending ClickHouse and PostgreSQL migrations and exits
+async fn run_migrations(settings: Arc<AppSettings>) {
+    if let Err(err) = ClickhouseConnection::connect(settings.clone(), SchemaMode::Migrate).await {
+        error!("ClickHouse migration failed: {}", err);
+        std::process::exit(1);
+    }
+
+    // PostgresService applies its migrations while connecting
+    if let Err(err) = PostgresService::new(&settings).await {
+        error!("PostgreSQL migration failed: {}", err);
+        std::process::exit(1);
+    }
+
+    info!("Migrations applied");
+}
+
+/// Reloads ClickHouse from the Parquet archive and exits
+async fn run_archive_rebuild(
+    settings: &AppSettings,
+    clickhouse_service: &ClickhouseService,
+    postgres_service: &PostgresService,
+    instrument_uid: Option<&str>,
+) {
+    let archive = ParquetArchive::new(&settings.app_config.archive.path);
+    let repository = ClickhouseCandleRepository::new(
+        clickhouse_service.connection.clone(),
+        postgres_service.repository_watchlist.clone(),
+    );
+
+    info!(
+        "Rebuilding ClickHouse from the archive at {}",
+        settings.app_config.archive.path
+    );
+    match rebuild_clickhouse(&archive, &repository, instrument_uid).await {
+        Ok(count) => info!("Archive rebuild completed: {} candles written", count),
+        Err(err) => {
+            error!("Archive rebuild failed: {}", err);
+            std::process::exit(1);
+        }
+    }
+
+    // Re-inserted minutes went through the materialized views a second time
+    let tomorrow = (chrono::Utc::now() + chrono::Duration::days(1)).timestamp();
+    run_rollup_backfill(clickhouse_service, instrument_uid, None, Some(tomorrow)).await;
+}
+
+/// Recomputes the 5min, 1hour and 1day rollups from 1min candles and exits
+async fn run_rollup_backfill(
+    clickhouse_service: &ClickhouseService,
+    instrument_uid: Option<&str>,
+    from: Option<i64>,
+    to: Option<i64>,
+) {
+    let maintenance = RollupMaintenance::new(clickhouse_service.repository_rollup.clone());
+    match maintenance
+        .backfill(from, to, instrument_uid, chrono::Utc::now())
+        .await
+    {
+        Ok(report) => info!(
+            "Rollup backfill completed: [{}, {}) in {} chunks",
+            report.from, report.to, report.chunks
+        ),
+        Err(err) => {
+            error!("Rollup backfill failed: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Serves fixture data in place of the Tinkoff API until Ctrl+C
+async fn run_mock_tinkoff(fixtures: &std::path::Path, listen: SocketAddr) {
+    let fixtures = MockFixtures::load(fixtures).unwrap_or_else(|err| {
+        error!("{}", err);
+        std::process::exit(1);
+    });
+
+    let shutdown = CancellationToken::new();
+    let address = MockTinkoff::new(fixtures)
+        .spawn(listen, shutdown.clone())
+        .await
+        .unwrap_or_else(|err| {
+            error!("Failed to bind mock Tinkoff API to {}: {}", listen, err);
+            std::process::exit(1);
+        });
+    info!(
+        "Point tinkoff_api.base_url at http://{} to use the mock",
+        address
+    );
+
+    listen_for_shutdown_signal(shutdown).await;
+}
+
 #[tokio::main]
 async fn main() {
+    let command = Command::from_args(std::env::args()).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    });
+
     // Initialize application settings and logging
     let settings: Arc<AppSettings> = Arc::new(initialize_application().await);
 
+    if command == Command::Migrate {
+        run_migrations(settings).await;
+        return;
+    }
+
+    if let Command::MockTinkoff { fixtures, listen } = &command {
+        run_mock_tinkoff(fixtures, *listen).await;
+        return;
+    }
+
     // Connect to databases
-    let clickhouse_service = initialize_database_connections(settings.clone()).await;
+    let (clickhouse_service, postgres_service) =
+        initialize_database_connections(settings.clone()).await;
+
+    if let Command::ArchiveRebuild { instrument_uid } = &command {
+        let (Some(clickhouse_service), Some(postgres_service)) =
+            (&clickhouse_service, &postgres_service)
+        else {
+            error!("archive-rebuild needs both ClickHouse and PostgreSQL");
+            std::process::exit(1);
+        };
+        run_archive_rebuild(
+            &settings,
+            clickhouse_service,
+            postgres_service,
+            instrument_uid.as_deref(),
+        )
+        .await;
+        return;
+    }
+
+    if let Command::RollupBackfill {
+        instrument_uid,
+        from,
+        to,
+    } = &command
+    {
+        let Some(clickhouse_service) = &clickhouse_service else {
+            error!("rollup-backfill needs ClickHouse");
+            std::process::exit(1);
+        };
+        let day =
+            |date: &chrono::NaiveDate| date.and_time(chrono::NaiveTime::MIN).and_utc().timestamp();
+        run_rollup_backfill(
+            clickhouse_service,
+            instrument_uid.as_deref(),
+            from.as_ref().map(day),
+            to.as_ref().map(day),
+        )
+        .await;
+        return;
+    }
+
+    let candle_repository = initialize_candle_storage(
+        &settings,
+        clickhouse_service.as_ref(),
+        postgres_service.as_ref(),
+    )
+    .await;
 
     // Parse server address from configuration
     let server_address: SocketAddr = format!(
@@ -142,22 +485,32 @@ async fn main() {
     info!("Server will listen on: {}", server_address);
 
     // Initialize Tinkoff API client
-    let tinkoff_client = Arc::new(
-        TinkoffClient::new(settings.clone())
-            .await
-            .expect("Failed to initialize Tinkoff API client"),
-    );
+    // The connection is lazy: only configuration errors stop the start
+    let tinkoff_client = match TinkoffClient::new(settings.clone()).await {
+        Ok(client) => Arc::new(client),
+        Err(err) => {
+            error!("Failed to initialize Tinkoff API client: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let candle_source = Arc::new(TinkoffCandleSource::new(tinkoff_client.clone()));
 
     // Create application state with all services
     let app_state: Arc<AppState> = Arc::new(
         AppState::new(
             settings.clone(),
-            Arc::new(clickhouse_service),
+            clickhouse_service.map(Arc::new),
+            postgres_service.map(Arc::new),
+            candle_repository,
             tinkoff_client,
+            candle_source,
         )
         .await,
     );
 
+    // Cancel the shutdown token on SIGTERM / Ctrl+C
+    tokio::spawn(listen_for_shutdown_signal(app_state.shutdown.clone()));
+
     // Initialize and start background services
     initialize_background_services(app_state.clone()).await;
 
@@ -171,7 +524,10 @@ async fn main() {
     // }
 
     // Start HTTP server
-    start_http_server(app_router, server_address).await;
+    start_http_server(app_router, server_address, app_state.shutdown.clone()).await;
+
+    // Let in-flight batches finish and persist their checkpoints
+    wait_for_background_tasks(&app_state).await;
 
-    info!("Application started successfully!");
+    info!("Application stopped");
 }
@@ -0,0 +1,37 @@
+use sha2::{Digest, Sha256};
+
+/// Заголовок, в котором клиент передаёт ключ
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Имя ключа администратора из переменной API_ADMIN_KEY
+pub const ADMIN_KEY_NAME: &str = "admin";
+
+/// SHA-256 ключа в hex: в api_keys хранится только он
+pub fn hash_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+/// Новый случайный ключ; показывается один раз при создании
+pub fn generate_key() -> String {
+    format!(
+        "tc_{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_is_stable_hex_digest() {
+        let key = generate_key();
+        let hash = hash_key(&key);
+
+        assert_eq!(hash, hash_key(&key));
+        assert_eq!(hash.len(), 64);
+        assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(hash, hash_key(&generate_key()));
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::{
+    AppState,
+    services::{job_ledger::ARCHIVE_JOB, scheduling::scheduled_job::ScheduledJob},
+};
+
+/// Ежедневное уплотнение Parquet-архива свечей
+pub struct ArchiveCompactionScheduler {
+    app_state: Arc<AppState>,
+}
+
+impl ArchiveCompactionScheduler {
+    pub fn new(app_state: Arc<AppState>) -> Self {
+        Self { app_state }
+    }
+
+    pub async fn start(&self) {
+        if self.app_state.archive.is_none() {
+            info!("Candle archive is disabled in configuration");
+            return;
+        }
+
+        info!("Starting archive compaction scheduler");
+
+        // Compaction is not tied to exchange hours, every scheduled slot runs
+        ScheduledJob::new(
+            ARCHIVE_JOB,
+            &self.app_state.settings.app_config.archive.compaction,
+        )
+        .spawn(
+            self.app_state.clone(),
+            |_| true,
+            |app_state| async move {
+                let Some(archive) = app_state.archive.clone() else {
+                    return;
+                };
+
+                let ledger = app_state.job_ledger();
+                let mut run = ledger.start(ARCHIVE_JOB).await;
+                match archive.compact().await {
+                    Ok(report) => {
+                        info!(
+                            "Archive compaction: merged {} files into {} partitions ({} candles)",
+                            report.files_merged, report.partitions, report.candles
+                        );
+                        run.instruments_total = report.partitions as i64;
+                        ledger.finish(&mut run, None).await;
+                    }
+                    Err(e) => {
+                        error!("Archive compaction failed: {}", e);
+                        ledger.finish(&mut run, Some(e.to_string())).await;
+                    }
+                }
+            },
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing::error;
+
+use super::parquet_archive::{MINUTE_INTERVAL, ParquetArchive};
+use crate::db::storage::candle_repository::{CandleRepository, StorageError};
+use crate::db::storage::stored_candle::StoredCandle;
+use crate::env_config::models::retention::CandleResolution;
+use crate::generate::tinkoff_public_invest_api_contract_v1::HistoricCandle;
+
+/// Хранилище свечей, которое копирует каждую записанную пачку в Parquet-архив
+///
+/// Чтение и контрольные точки остаются за основным бэкендом. Ошибка записи в архив
+/// возвращается как ошибка пачки: контрольная точка не сдвигается, и следующий запуск
+/// загрузит сутки заново, иначе в архиве останется дыра
+pub struct ArchivingCandleRepository {
+    inner: Arc<dyn CandleRepository + Send + Sync>,
+    archive: Arc<ParquetArchive>,
+}
+
+impl ArchivingCandleRepository {
+    pub fn new(
+        inner: Arc<dyn CandleRepository + Send + Sync>,
+        archive: Arc<ParquetArchive>,
+    ) -> Self {
+        Self { inner, archive }
+    }
+}
+
+#[async_trait]
+impl CandleRepository for ArchivingCandleRepository {
+    async fn insert_candles(
+        &self,
+        candles: Vec<HistoricCandle>,
+        instrument_uid: &str,
+    ) -> Result<u64, StorageError> {
+        let rows = StoredCandle::from_historic_batch(instrument_uid, &candles);
+        let inserted = self.inner.insert_candles(candles, instrument_uid).await?;
+
+        // The backend replaces candles with the same time, so the retry does not duplicate them
+        if let Err(e) = self.archive.write_candles(MINUTE_INTERVAL, rows).await {
+            error!("Failed to archive candles for {}: {}", instrument_uid, e);
+            return Err(StorageError::Archive(e));
+        }
+        Ok(inserted)
+    }
+
+    async fn get_candles(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<StoredCandle>, StorageError> {
+        self.inner.get_candles(instrument_uid, from, to).await
+    }
+
+    async fn get_candles_at(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+        resolution: CandleResolution,
+    ) -> Result<Vec<StoredCandle>, StorageError> {
+        self.inner
+            .get_candles_at(instrument_uid, from, to, resolution)
+            .await
+    }
+
+    async fn get_checkpoint(&self, instrument_uid: &str) -> Result<Option<i64>, StorageError> {
+        self.inner.get_checkpoint(instrument_uid).await
+    }
+
+    async fn save_checkpoint(
+        &self,
+        instrument_uid: &str,
+        last_candle_time: i64,
+    ) -> Result<(), StorageError> {
+        self.inner
+            .save_checkpoint(instrument_uid, last_candle_time)
+            .await
+    }
+
+    fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::storage::memory::MemoryCandleRepository;
+    use crate::generate::tinkoff_public_invest_api_contract_v1::Quotation;
+
+    fn candle(time: i64) -> HistoricCandle {
+        let price = Some(Quotation { units: 1, nano: 0 });
+        HistoricCandle {
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 1,
+            time: Some(prost_types::Timestamp {
+                seconds: time,
+                nanos: 0,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn fails_the_batch_when_the_archive_is_not_written() {
+        // A regular file in place of the archive root makes every write fail
+        let root = std::env::temp_dir().join(format!("archive-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&root, b"").unwrap();
+        let repository = ArchivingCandleRepository::new(
+            Arc::new(MemoryCandleRepository::new()),
+            Arc::new(ParquetArchive::new(&root)),
+        );
+
+        let result = repository.insert_candles(vec![candle(60)], "uid").await;
+
+        assert!(matches!(result, Err(StorageError::Archive(_))));
+        std::fs::remove_file(&root).unwrap();
+    }
+}
@@ -0,0 +1,4 @@
+pub mod archive_compaction;
+pub mod archive_sink;
+pub mod parquet_archive;
+pub mod rebuild;
@@ -0,0 +1,467 @@
+use arrow_array::{Array, ArrayRef, Int32Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use chrono::{DateTime, Datelike};
+use parquet::{
+    arrow::{ArrowWriter, arrow_reader::ParquetRecordBatchReaderBuilder},
+    basic::{Compression, ZstdLevel},
+    file::properties::WriterProperties,
+};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::db::storage::stored_candle::StoredCandle;
+
+/// Ошибки архива передаются между потоками (запись идёт в spawn_blocking)
+pub type ArchiveError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Интервал минутных свечей в пути партиции
+pub const MINUTE_INTERVAL: &str = "1min";
+
+/// Итоговый файл партиции после ежедневного уплотнения
+const COMPACTED_FILE: &str = "data.parquet";
+
+/// Партиция архива: интервал, инструмент, год и месяц
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArchivePartition {
+    pub interval: String,
+    pub instrument_uid: String,
+    pub year: i32,
+    pub month: u32,
+}
+
+impl ArchivePartition {
+    fn of(interval: &str, candle: &StoredCandle) -> Self {
+        let time = DateTime::from_timestamp(candle.time, 0).unwrap_or_default();
+        Self {
+            interval: interval.to_string(),
+            instrument_uid: candle.instrument_uid.clone(),
+            year: time.year(),
+            month: time.month(),
+        }
+    }
+
+    /// interval=1min/instrument_uid=.../year=2024/month=03 — раскладка, которую
+    /// понимают DuckDB, Spark и прочие читатели Hive-партиций
+    fn dir(&self, root: &Path) -> PathBuf {
+        root.join(format!("interval={}", self.interval))
+            .join(format!("instrument_uid={}", self.instrument_uid))
+            .join(format!("year={}", self.year))
+            .join(format!("month={:02}", self.month))
+    }
+
+    /// Границы месяца партиции [from, to), секунды
+    pub fn time_range(&self) -> (i64, i64) {
+        let start = |year: i32, month: u32| {
+            chrono::NaiveDate::from_ymd_opt(year, month, 1)
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map_or(0, |d| d.and_utc().timestamp())
+        };
+        let (next_year, next_month) = if self.month == 12 {
+            (self.year + 1, 1)
+        } else {
+            (self.year, self.month + 1)
+        };
+        (start(self.year, self.month), start(next_year, next_month))
+    }
+}
+
+/// Итог уплотнения архива
+#[derive(Debug, Default)]
+pub struct CompactionReport {
+    pub partitions: usize,
+    pub files_merged: usize,
+    pub candles: usize,
+}
+
+/// Архив свечей в Parquet-файлах, независимый от ClickHouse
+///
+/// Каждая запись — новый файл part-*.parquet в своей партиции. Файл пишется во
+/// временный и переименовывается, поэтому читатели никогда не видят его частично.
+/// Раз в день части партиции сливаются в data.parquet
+pub struct ParquetArchive {
+    root: PathBuf,
+    // Writes and compaction of the same partition must not interleave
+    lock: Mutex<()>,
+}
+
+impl ParquetArchive {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Записывает свечи в архив, по одному файлу на затронутую партицию
+    pub async fn write_candles(
+        &self,
+        interval: &str,
+        candles: Vec<StoredCandle>,
+    ) -> Result<usize, ArchiveError> {
+        if candles.is_empty() {
+            return Ok(0);
+        }
+
+        let mut partitions: BTreeMap<ArchivePartition, Vec<StoredCandle>> = BTreeMap::new();
+        for candle in candles {
+            partitions
+                .entry(ArchivePartition::of(interval, &candle))
+                .or_default()
+                .push(candle);
+        }
+
+        let _guard = self.lock.lock().await;
+        let root = self.root.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut written = 0;
+            for (partition, candles) in partitions {
+                let file_name = format!(
+                    "part-{}-{}.parquet",
+                    chrono::Utc::now().timestamp_millis(),
+                    uuid::Uuid::new_v4().simple()
+                );
+                write_atomically(&partition.dir(&root), &file_name, &candles)?;
+                written += candles.len();
+            }
+            Ok(written)
+        })
+        .await?
+    }
+
+    /// Сливает части каждой партиции в один data.parquet без дублей по времени
+    pub async fn compact(&self) -> Result<CompactionReport, ArchiveError> {
+        let _guard = self.lock.lock().await;
+        let root = self.root.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut report = CompactionReport::default();
+            for partition in list_partitions(&root, None, None)? {
+                let dir = partition.dir(&root);
+                let files = list_parquet_files(&dir)?;
+                let has_parts = files
+                    .iter()
+                    .any(|f| f.file_name().is_some_and(|n| n != COMPACTED_FILE));
+                if !has_parts {
+                    continue;
+                }
+
+                let candles = read_files(&files)?;
+                write_atomically(&dir, COMPACTED_FILE, &candles)?;
+                for file in files
+                    .iter()
+                    .filter(|f| f.file_name().is_some_and(|n| n != COMPACTED_FILE))
+                {
+                    fs::remove_file(file)?;
+                }
+
+                debug!(
+                    "Compacted {} files ({} candles) in {}",
+                    files.len(),
+                    candles.len(),
+                    dir.display()
+                );
+                report.partitions += 1;
+                report.files_merged += files.len();
+                report.candles += candles.len();
+            }
+            Ok(report)
+        })
+        .await?
+    }
+
+    /// Партиции архива, опционально только одного инструмента
+    pub async fn partitions(
+        &self,
+        interval: &str,
+        instrument_uid: Option<&str>,
+    ) -> Result<Vec<ArchivePartition>, ArchiveError> {
+        let root = self.root.clone();
+        let interval = interval.to_string();
+        let instrument_uid = instrument_uid.map(str::to_string);
+        tokio::task::spawn_blocking(move || {
+            list_partitions(&root, Some(&interval), instrument_uid.as_deref())
+        })
+        .await?
+    }
+
+    /// Свечи одной партиции по возрастанию времени
+    pub async fn read_partition(
+        &self,
+        partition: &ArchivePartition,
+    ) -> Result<Vec<StoredCandle>, ArchiveError> {
+        let dir = partition.dir(&self.root);
+        tokio::task::spawn_blocking(move || read_files(&list_parquet_files(&dir)?)).await?
+    }
+}
+
+fn schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("instrument_uid", DataType::Utf8, false),
+        Field::new("time", DataType::Int64, false),
+        Field::new("open_units", DataType::Int64, false),
+        Field::new("open_nano", DataType::Int32, false),
+        Field::new("high_units", DataType::Int64, false),
+        Field::new("high_nano", DataType::Int32, false),
+        Field::new("low_units", DataType::Int64, false),
+        Field::new("low_nano", DataType::Int32, false),
+        Field::new("close_units", DataType::Int64, false),
+        Field::new("close_nano", DataType::Int32, false),
+        Field::new("volume", DataType::Int64, false),
+    ]))
+}
+
+fn to_record_batch(candles: &[StoredCandle]) -> Result<RecordBatch, ArchiveError> {
+    let i64_column = |f: fn(&StoredCandle) -> i64| -> ArrayRef {
+        Arc::new(Int64Array::from_iter_values(candles.iter().map(f)))
+    };
+    let i32_column = |f: fn(&StoredCandle) -> i32| -> ArrayRef {
+        Arc::new(Int32Array::from_iter_values(candles.iter().map(f)))
+    };
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            candles.iter().map(|c| c.instrument_uid.as_str()),
+        )),
+        i64_column(|c| c.time),
+        i64_column(|c| c.open_units),
+        i32_column(|c| c.open_nano),
+        i64_column(|c| c.high_units),
+        i32_column(|c| c.high_nano),
+        i64_column(|c| c.low_units),
+        i32_column(|c| c.low_nano),
+        i64_column(|c| c.close_units),
+        i32_column(|c| c.close_nano),
+        i64_column(|c| c.volume),
+    ];
+    Ok(RecordBatch::try_new(schema(), columns)?)
+}
+
+/// Пишет файл во временный рядом с целевым и переименовывает его
+fn write_atomically(
+    dir: &Path,
+    file_name: &str,
+    candles: &[StoredCandle],
+) -> Result<(), ArchiveError> {
+    fs::create_dir_all(dir)?;
+    let target = dir.join(file_name);
+    let tmp = dir.join(format!(".{}.tmp", file_name));
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::ZSTD(ZstdLevel::default()))
+        .build();
+    let file = File::create(&tmp)?;
+    let mut writer = ArrowWriter::try_new(file, schema(), Some(properties))?;
+    writer.write(&to_record_batch(candles)?)?;
+    let file = writer.into_inner()?;
+    file.sync_all()?;
+
+    fs::rename(&tmp, &target)?;
+    // Persist the rename itself; not every platform allows syncing a directory
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Parquet-файлы партиции: сначала data.parquet, затем части в порядке записи
+fn list_parquet_files(dir: &Path) -> Result<Vec<PathBuf>, ArchiveError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with(".parquet") && !n.starts_with('.'))
+        })
+        .collect();
+    // Part names start with the write time in milliseconds, so name order is write order
+    files.sort_by_key(|path| {
+        (
+            path.file_name().is_some_and(|n| n != COMPACTED_FILE),
+            path.clone(),
+        )
+    });
+    Ok(files)
+}
+
+/// Читает файлы партиции; при повторе времени побеждает более поздняя запись
+fn read_files(files: &[PathBuf]) -> Result<Vec<StoredCandle>, ArchiveError> {
+    let mut candles: BTreeMap<(String, i64), StoredCandle> = BTreeMap::new();
+    for path in files {
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?.build()?;
+        for batch in reader {
+            for candle in from_record_batch(&batch?)? {
+                candles.insert((candle.instrument_uid.clone(), candle.time), candle);
+            }
+        }
+    }
+    Ok(candles.into_values().collect())
+}
+
+fn from_record_batch(batch: &RecordBatch) -> Result<Vec<StoredCandle>, ArchiveError> {
+    fn column<'a, T: Array + 'static>(
+        batch: &'a RecordBatch,
+        name: &str,
+    ) -> Result<&'a T, ArchiveError> {
+        batch
+            .column_by_name(name)
+            .and_then(|c| c.as_any().downcast_ref::<T>())
+            .ok_or_else(|| format!("archive file has no valid column {}", name).into())
+    }
+
+    let uid = column::<StringArray>(batch, "instrument_uid")?;
+    let time = column::<Int64Array>(batch, "time")?;
+    let open_units = column::<Int64Array>(batch, "open_units")?;
+    let open_nano = column::<Int32Array>(batch, "open_nano")?;
+    let high_units = column::<Int64Array>(batch, "high_units")?;
+    let high_nano = column::<Int32Array>(batch, "high_nano")?;
+    let low_units = column::<Int64Array>(batch, "low_units")?;
+    let low_nano = column::<Int32Array>(batch, "low_nano")?;
+    let close_units = column::<Int64Array>(batch, "close_units")?;
+    let close_nano = column::<Int32Array>(batch, "close_nano")?;
+    let volume = column::<Int64Array>(batch, "volume")?;
+
+    Ok((0..batch.num_rows())
+        .map(|i| StoredCandle {
+            instrument_uid: uid.value(i).to_string(),
+            time: time.value(i),
+            open_units: open_units.value(i),
+            open_nano: open_nano.value(i),
+            high_units: high_units.value(i),
+            high_nano: high_nano.value(i),
+            low_units: low_units.value(i),
+            low_nano: low_nano.value(i),
+            close_units: close_units.value(i),
+            close_nano: close_nano.value(i),
+            volume: volume.value(i),
+        })
+        .collect())
+}
+
+/// Обходит каталоги key=value архива; пропускает всё, что не похоже на партицию
+fn list_partitions(
+    root: &Path,
+    interval: Option<&str>,
+    instrument_uid: Option<&str>,
+) -> Result<Vec<ArchivePartition>, ArchiveError> {
+    fn children(dir: &Path, key: &str) -> Result<Vec<(String, PathBuf)>, ArchiveError> {
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let prefix = format!("{}=", key);
+        let mut result = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let value = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix(&prefix))
+                .map(str::to_string);
+            if let (true, Some(value)) = (path.is_dir(), value) {
+                result.push((value, path));
+            }
+        }
+        Ok(result)
+    }
+
+    let mut partitions = Vec::new();
+    for (interval_value, interval_dir) in children(root, "interval")? {
+        if interval.is_some_and(|i| i != interval_value) {
+            continue;
+        }
+        for (uid, uid_dir) in children(&interval_dir, "instrument_uid")? {
+            if instrument_uid.is_some_and(|u| u != uid) {
+                continue;
+            }
+            for (year, year_dir) in children(&uid_dir, "year")? {
+                for (month, _) in children(&year_dir, "month")? {
+                    if let (Ok(year), Ok(month)) = (year.parse(), month.parse()) {
+                        partitions.push(ArchivePartition {
+                            interval: interval_value.clone(),
+                            instrument_uid: uid.clone(),
+                            year,
+                            month,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    partitions.sort();
+    debug!(
+        "Found {} archive partitions in {}",
+        partitions.len(),
+        root.display()
+    );
+    Ok(partitions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(uid: &str, time: i64, volume: i64) -> StoredCandle {
+        StoredCandle {
+            instrument_uid: uid.to_string(),
+            time,
+            open_units: 1,
+            open_nano: 0,
+            high_units: 1,
+            high_nano: 0,
+            low_units: 1,
+            low_nano: 0,
+            close_units: 1,
+            close_nano: 0,
+            volume,
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_partitions_and_compacts_without_duplicates() {
+        let root = std::env::temp_dir().join(format!("archive-{}", uuid::Uuid::new_v4()));
+        let archive = ParquetArchive::new(&root);
+
+        // 2024-01-31 23:59 and 2024-02-01 00:00 land in different months
+        let batch = vec![candle("uid", 1706745540, 1), candle("uid", 1706745600, 2)];
+        assert_eq!(
+            archive.write_candles(MINUTE_INTERVAL, batch).await.unwrap(),
+            2
+        );
+        archive
+            .write_candles(MINUTE_INTERVAL, vec![candle("uid", 1706745600, 5)])
+            .await
+            .unwrap();
+
+        let partitions = archive
+            .partitions(MINUTE_INTERVAL, Some("uid"))
+            .await
+            .unwrap();
+        assert_eq!(
+            partitions
+                .iter()
+                .map(|p| (p.year, p.month))
+                .collect::<Vec<_>>(),
+            vec![(2024, 1), (2024, 2)]
+        );
+        assert_eq!(partitions[1].time_range(), (1706745600, 1709251200));
+
+        let report = archive.compact().await.unwrap();
+        assert_eq!((report.partitions, report.files_merged), (2, 3));
+        let february = archive.read_partition(&partitions[1]).await.unwrap();
+        assert_eq!(february, vec![candle("uid", 1706745600, 5)]);
+        let files = list_parquet_files(&partitions[1].dir(&root)).unwrap();
+        assert_eq!(files, vec![partitions[1].dir(&root).join(COMPACTED_FILE)]);
+
+        // Nothing new to merge on the next day
+        assert_eq!(archive.compact().await.unwrap().partitions, 0);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,54 @@
+use tracing::info;
+
+use super::parquet_archive::{ArchiveError, MINUTE_INTERVAL, ParquetArchive};
+use crate::db::clickhouse::repository::candle_repository::ClickhouseCandleRepository;
+use crate::db::storage::candle_repository::CandleRepository;
+use crate::generate::tinkoff_public_invest_api_contract_v1::HistoricCandle;
+
+/// Перезаливает tinkoff_candles_1min из архива
+///
+/// Для каждой партиции (инструмент и месяц) свечи этого месяца в ClickHouse
+/// удаляются и записываются заново, поэтому повторный запуск не создаёт дублей.
+/// Месяцы, которых нет в архиве, не затрагиваются
+///
+/// # Returns
+/// Число записанных свечей
+pub async fn rebuild_clickhouse(
+    archive: &ParquetArchive,
+    repository: &ClickhouseCandleRepository,
+    instrument_uid: Option<&str>,
+) -> Result<u64, ArchiveError> {
+    let partitions = archive.partitions(MINUTE_INTERVAL, instrument_uid).await?;
+    let total = partitions.len();
+    let mut inserted = 0;
+
+    for (index, partition) in partitions.iter().enumerate() {
+        let candles: Vec<HistoricCandle> = archive
+            .read_partition(partition)
+            .await?
+            .iter()
+            .map(|candle| candle.to_historic())
+            .collect();
+
+        let (from, to) = partition.time_range();
+        repository
+            .delete_candles(&partition.instrument_uid, from, to)
+            .await?;
+        let count = candles.len();
+        inserted += repository
+            .insert_candles(candles, &partition.instrument_uid)
+            .await?;
+
+        info!(
+            "Rebuilt {} {}-{:02}: {} candles ({}/{})",
+            partition.instrument_uid,
+            partition.year,
+            partition.month,
+            count,
+            index + 1,
+            total
+        );
+    }
+
+    Ok(inserted)
+}
@@ -1,35 +1,71 @@
 use crate::app_state::models::AppState;
 
 use crate::db::clickhouse::clickhouse_service::ClickhouseService;
-
+use crate::db::clickhouse::models::quarantined_candle::DbQuarantinedCandle;
+use crate::db::postgres::models::job_run::{DbJobRun, DbLoadStatus};
+use crate::db::postgres::models::watchlist::{DbWatchlistInstrument, WatchlistOrigin};
+use crate::db::postgres::postgres_service::PostgresService;
+use crate::db::storage::candle_repository::CandleRepository;
+
+use crate::db::clickhouse::repository::repository_retention::InstrumentGroup;
+use crate::db::postgres::models::refetch::DbRefetchRequest;
 use crate::env_config::models::app_config::AppConfig;
+use crate::env_config::models::app_config::StorageBackend;
 use crate::env_config::models::app_setting::AppSettings;
-use crate::generate::tinkoff_public_invest_api_contract_v1::{
-    CandleInterval, GetCandlesRequest, HistoricCandle,
-};
-use crate::services::tinkoff_client_grpc::TinkoffClient;
+use crate::env_config::models::retention::CandleResolution;
+use crate::generate::tinkoff_public_invest_api_contract_v1::{HistoricCandle, Share};
+use crate::services::candles::validation::{self, RejectReason};
+use crate::services::exclusions::exclusion_list::ExclusionList;
+use crate::services::job_ledger::{CANDLES_JOB, JobLedger};
+use crate::services::rollup::rollup_maintenance::ROLLUP_RESOLUTIONS;
+use crate::services::sources::candle_source::CandleSource;
 use crate::utils::utils_date_time;
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
+const MINUTE_SECONDS: i64 = 60;
+const DAY_SECONDS: i64 = 86_400;
+const REFETCH_BATCH: i64 = 200; // Заявок очереди повторной загрузки за один запуск
+
 /// Клиент для работы с API свечей Tinkoff
 /// Предоставляет функциональность для загрузки и сохранения свечей в БД
 pub struct ClientCandle {
-    clickhouse_service: Arc<ClickhouseService>,
-    grpc_tinkoff: Arc<TinkoffClient>,
+    clickhouse_service: Option<Arc<ClickhouseService>>, // Карантин и каталог; без него загрузка идёт без них
+    postgres_service: Option<Arc<PostgresService>>, // Список загрузки; без него берётся [storage] instruments
+    candle_repository: Arc<dyn CandleRepository + Send + Sync>,
+    candle_source: Arc<dyn CandleSource + Send + Sync>,
+    exclusions: Arc<ExclusionList>,
+    ledger: JobLedger,
+    shutdown: CancellationToken,
     settings: Arc<AppSettings>, // Store a reference to the existing Arc<AppSettings>
 }
 
 impl ClientCandle {
     pub fn new(
-        clickhouse_service: Arc<ClickhouseService>,
-        grpc_tinkoff: Arc<TinkoffClient>,
+        clickhouse_service: Option<Arc<ClickhouseService>>,
+        postgres_service: Option<Arc<PostgresService>>,
+        candle_repository: Arc<dyn CandleRepository + Send + Sync>,
+        candle_source: Arc<dyn CandleSource + Send + Sync>,
+        exclusions: Arc<ExclusionList>,
+        shutdown: CancellationToken,
         settings: Arc<AppSettings>,
     ) -> Self {
+        let ledger = JobLedger::new(
+            postgres_service
+                .as_ref()
+                .map(|postgres| postgres.repository_job_run.clone()),
+        );
         Self {
             clickhouse_service,
-            grpc_tinkoff,
+            postgres_service,
+            candle_repository,
+            candle_source,
+            exclusions,
+            ledger,
+            shutdown,
             settings,
         }
     }
@@ -46,32 +82,16 @@ impl ClientCandle {
             uid, from, to
         );
 
-        // Create request to Tinkoff API
-        let request = GetCandlesRequest {
-            from: Some(prost_types::Timestamp {
-                seconds: from,
-                nanos: 0,
-            }),
-            to: Some(prost_types::Timestamp {
-                seconds: to,
-                nanos: 0,
-            }),
-            instrument_id: uid.to_string(),
-            figi: "".to_string(),
-            interval: CandleInterval::CandleInterval1Min as i32,
-        };
-
-        // Execute request
-        let grpc_request = self.grpc_tinkoff.create_request(request)?;
-        let mut market_data_client = self.grpc_tinkoff.market_data.clone();
-
-        let response = market_data_client.get_candles(grpc_request).await?;
-        let candles_response = response.into_inner();
+        let candles = self
+            .candle_source
+            .get_candles(uid, from, to, CandleResolution::Minute)
+            .await?;
 
         info!(
-            "Received {} candles for {}",
-            candles_response.candles.len(),
-            uid
+            "Received {} candles for {} from {}",
+            candles.len(),
+            uid,
+            self.candle_source.source_name()
         );
 
         // Apply the configured delay after API call
@@ -81,7 +101,7 @@ impl ClientCandle {
             tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
         }
 
-        Ok(candles_response.candles)
+        Ok(candles)
     }
 
     /// Загружает и сохраняет свечи для одного инструмента
@@ -91,17 +111,16 @@ impl ClientCandle {
     /// * `first_candle_date` - Дата первой возможной свечи для инструмента
     /// * `index` - Индекс инструмента в общем списке
     /// * `total` - Общее количество инструментов в списке
-    ///
-    /// # Returns
-    /// Количество обработанных свечей или ошибку
+    /// * `status` - Запись журнала, куда складываются диапазон и счётчики свечей;
+    ///   заполняется и при ошибке, чтобы в журнале было видно, докуда дошла загрузка
     async fn process_instrument(
         &self,
         instrument_id: &str,
         first_1min_candle_date: i64,
-        last_1min_candle_date: i64,
         index: usize,
         total: usize,
-    ) -> Result<usize, Box<dyn std::error::Error>> {
+        status: &mut DbLoadStatus,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         info!(
             "Processing instrument {}/{}: {}",
             index + 1,
@@ -109,8 +128,16 @@ impl ClientCandle {
             instrument_id
         );
 
-        // Get current date and calculate yesterday's end
-        let (_, yesterday_end) = utils_date_time::get_yesterday_range(None);
+        // The checkpoint is kept by the storage backend, so a fresh backend starts from scratch
+        let last_1min_candle_date = self
+            .candle_repository
+            .get_checkpoint(instrument_id)
+            .await?
+            .unwrap_or(0);
+
+        // Get current date (the recording time when replaying) and calculate yesterday's end
+        let (_, yesterday_end) =
+            utils_date_time::get_yesterday_range(Some(self.candle_source.now()));
 
         // Check if we've already reached yesterday
         if last_1min_candle_date >= yesterday_end {
@@ -118,14 +145,18 @@ impl ClientCandle {
                 "Already up to date for {}, last update to {}",
                 instrument_id, last_1min_candle_date
             );
-            return Ok(0);
+            return Ok(());
         }
 
-        // Start from the last recorded date or the first possible date
-        let mut current_date = last_1min_candle_date;
-        if current_date == 0 {
+        // Start right after the checkpoint minute or from the first possible date.
+        // The checkpoint minute is already stored, and inserting it again would make
+        // the rollup materialized views count it twice
+        let mut current_date = last_1min_candle_date + MINUTE_SECONDS;
+        if last_1min_candle_date == 0 {
             current_date = first_1min_candle_date;
         }
+        status.range_from = current_date;
+        status.range_to = yesterday_end;
 
         // Process all days from last saved to yesterday
         let mut total_day_candles = 0;
@@ -155,6 +186,7 @@ impl ClientCandle {
 
                 let day_candles = vec_candles.len();
                 total_day_candles += day_candles;
+                status.candles_received += day_candles as i64;
 
                 // Save candles only if there are data
                 if !vec_candles.is_empty() {
@@ -165,16 +197,24 @@ impl ClientCandle {
                         }
                     }
 
-                    // Insert candles
-                    self.clickhouse_service
-                        .repository_candle
-                        .insert_candles(vec_candles, instrument_id)
-                        .await?;
+                    // Bad rows go to quarantine; the checkpoint still advances past them
+                    let (valid_candles, rejected) = validation::partition(
+                        vec_candles,
+                        MINUTE_SECONDS,
+                        self.settings.app_config.candle_validation.max_volume,
+                    );
+                    self.quarantine_candles(instrument_id, &rejected).await?;
+                    status.candles_quarantined += rejected.len() as i64;
 
-                    // Update the last candle date in the database after each successful batch
-                    self.clickhouse_service
-                        .repository_my_instrument
-                        .update_last_candle_date(instrument_id, latest_timestamp)
+                    // Insert candles
+                    status.candles_inserted += self
+                        .candle_repository
+                        .insert_candles(valid_candles, instrument_id)
+                        .await? as i64;
+
+                    // Update the checkpoint after each successful batch
+                    self.candle_repository
+                        .save_checkpoint(instrument_id, latest_timestamp)
                         .await?;
                 }
             } else {
@@ -188,6 +228,15 @@ impl ClientCandle {
             let (next_day_start, _) = utils_date_time::get_next_day_range(current_date);
             current_date = next_day_start;
             days_processed += 1;
+
+            // The checkpoint of the finished day is already stored, so it is safe to stop here
+            if self.shutdown.is_cancelled() {
+                info!(
+                    "Shutdown requested, stopping {} at checkpoint {}",
+                    instrument_id, latest_timestamp
+                );
+                break;
+            }
         }
 
         info!(
@@ -199,39 +248,274 @@ impl ClientCandle {
             total
         );
 
-        Ok(index + 1)
+        Ok(())
+    }
+
+    /// Загружает заново сутки из очереди refetch_queue, например после расхождения сверки
+    ///
+    /// Заявки по инструментам вне `instruments` остаются в очереди до их включения
+    async fn refetch_queued_days(
+        &self,
+        instruments: &[&str],
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        // The queue lives in PostgreSQL, so without it there is nothing to refetch
+        let Some(postgres_service) = &self.postgres_service else {
+            return Ok(0);
+        };
+        let pending = postgres_service
+            .repository_refetch
+            .get_pending(REFETCH_BATCH)
+            .await?;
+
+        let mut refetched = 0;
+        for request in pending
+            .iter()
+            .filter(|request| instruments.contains(&request.instrument_uid.as_str()))
+        {
+            if self.shutdown.is_cancelled() {
+                break;
+            }
+            // A boxed error is not Send, so it is turned into a string before the next await
+            let result = self.refetch_day(request).await.map_err(|e| e.to_string());
+            match result {
+                Ok(count) => {
+                    info!(
+                        "Refetched {} candles of {} for day {} ({})",
+                        count, request.instrument_uid, request.day, request.reason
+                    );
+                    postgres_service
+                        .repository_refetch
+                        .mark_done(&request.instrument_uid, request.day)
+                        .await?;
+                    refetched += 1;
+                }
+                Err(e) => error!(
+                    "Failed to refetch day {} of {}: {}",
+                    request.day, request.instrument_uid, e
+                ),
+            }
+        }
+        Ok(refetched)
+    }
+
+    async fn refetch_day(
+        &self,
+        request: &DbRefetchRequest,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let uid = request.instrument_uid.as_str();
+        let candles = self
+            .get_minute_candles(
+                uid,
+                request.day,
+                utils_date_time::get_end_of_day(request.day),
+            )
+            .await?;
+
+        let (valid_candles, rejected) = validation::partition(
+            candles,
+            MINUTE_SECONDS,
+            self.settings.app_config.candle_validation.max_volume,
+        );
+        self.quarantine_candles(uid, &rejected).await?;
+        // Every backend replaces candles with the same time, so the day is overwritten
+        let inserted = self
+            .candle_repository
+            .insert_candles(valid_candles, uid)
+            .await?;
+
+        // The materialized views counted the re-inserted minutes a second time
+        if let (StorageBackend::Clickhouse, Some(clickhouse_service)) = (
+            self.settings.app_config.storage.backend,
+            &self.clickhouse_service,
+        ) {
+            let group = InstrumentGroup::Only(vec![uid.to_string()]);
+            for resolution in ROLLUP_RESOLUTIONS {
+                clickhouse_service
+                    .repository_rollup
+                    .rebuild(resolution, request.day, request.day + DAY_SECONDS, &group)
+                    .await?;
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// Записывает отклонённые проверкой свечи в tinkoff_candles_quarantine
+    async fn quarantine_candles(
+        &self,
+        instrument_id: &str,
+        rejected: &[(HistoricCandle, RejectReason)],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if rejected.is_empty() {
+            return Ok(());
+        }
+
+        let Some(clickhouse_service) = &self.clickhouse_service else {
+            warn!(
+                "{} candles for {} failed validation and were dropped: ClickHouse with the quarantine is not connected",
+                rejected.len(),
+                instrument_id
+            );
+            return Ok(());
+        };
+        warn!(
+            "{} candles for {} failed validation and were quarantined",
+            rejected.len(),
+            instrument_id
+        );
+
+        let detected_at = chrono::Utc::now().timestamp();
+        let rows: Vec<DbQuarantinedCandle> = rejected
+            .iter()
+            .map(|(candle, reason)| DbQuarantinedCandle {
+                instrument_uid: instrument_id.to_string(),
+                time: candle.time.as_ref().map_or(0, |t| t.seconds),
+                open_units: candle.open.as_ref().map(|q| q.units),
+                open_nano: candle.open.as_ref().map(|q| q.nano),
+                high_units: candle.high.as_ref().map(|q| q.units),
+                high_nano: candle.high.as_ref().map(|q| q.nano),
+                low_units: candle.low.as_ref().map(|q| q.units),
+                low_nano: candle.low.as_ref().map(|q| q.nano),
+                close_units: candle.close.as_ref().map(|q| q.units),
+                close_nano: candle.close.as_ref().map(|q| q.nano),
+                volume: candle.volume,
+                reason: reason.as_str_name().to_string(),
+                detected_at,
+            })
+            .collect();
+
+        clickhouse_service
+            .repository_candle_quarantine
+            .insert_candles(&rows)
+            .await?;
+
+        Ok(())
     }
 
+    /// Загружает свечи по всем активным инструментам и записывает запуск в журнал job_runs
     pub async fn load_and_save_candles(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut run = self.ledger.start(CANDLES_JOB).await;
+        // The error is kept as a string: a boxed error is not Send across the ledger write
+        let result = self
+            .load_all_instruments(&mut run)
+            .await
+            .map_err(|e| e.to_string());
+        if self.shutdown.is_cancelled() {
+            self.ledger.cancel(&mut run).await;
+        } else {
+            self.ledger
+                .finish(&mut run, result.as_ref().err().cloned())
+                .await;
+        }
+        Ok(result?)
+    }
+
+    async fn load_all_instruments(
+        &self,
+        run: &mut DbJobRun,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        // Without a database with the catalog or the watchlist the source catalog stands in
+        let source_shares = if self.clickhouse_service.is_none() || self.postgres_service.is_none()
+        {
+            self.candle_source.get_shares().await?
+        } else {
+            Vec::new()
+        };
+
         // Get list of instruments with their candle info
-        let my_instruments = self
-            .clickhouse_service
-            .repository_my_instrument
-            .get_my_instrument()
-            .await?;
+        let my_instruments = match &self.postgres_service {
+            Some(postgres_service) => postgres_service.repository_watchlist.get_active().await?,
+            None => configured_instruments(
+                &self.settings.app_config.storage.instruments,
+                &source_shares,
+            ),
+        };
+
+        // Exclusions can be keyed by FIGI or ticker, so resolve them via the current catalog
+        if let Err(e) = self.exclusions.reload().await {
+            error!(
+                "Failed to reload instrument exclusions, using cached list: {}",
+                e
+            );
+        }
+        let exclusions = self.exclusions.snapshot().await;
+        let catalog: HashMap<String, (String, String)> = match &self.clickhouse_service {
+            Some(clickhouse_service) => clickhouse_service
+                .repository_share_history
+                .get_current_versions()
+                .await?
+                .into_iter()
+                .map(|v| (v.uid, (v.figi, v.ticker)))
+                .collect(),
+            None => source_shares
+                .into_iter()
+                .map(|share| (share.uid, (share.figi, share.ticker)))
+                .collect(),
+        };
+
+        let my_instruments: Vec<_> = my_instruments
+            .into_iter()
+            .filter(|instrument| {
+                let (figi, ticker) = catalog
+                    .get(&instrument.uid)
+                    .map(|(figi, ticker)| (figi.as_str(), ticker.as_str()))
+                    .unwrap_or_default();
+                match exclusions.find(&instrument.uid, figi, ticker) {
+                    Some(exclusion) => {
+                        info!(
+                            "Skipping excluded instrument {}: {}",
+                            instrument.uid, exclusion.reason
+                        );
+                        false
+                    }
+                    None => true,
+                }
+            })
+            .collect();
 
         if my_instruments.is_empty() {
             warn!("No instruments found to process");
             return Ok(0);
         }
 
+        let uids: Vec<&str> = my_instruments.iter().map(|i| i.uid.as_str()).collect();
+        match self.refetch_queued_days(&uids).await {
+            Ok(0) => {}
+            Ok(count) => info!("Refetched {} queued days", count),
+            Err(e) => error!("Failed to process the refetch queue: {}", e),
+        }
+
         info!("Starting to process {} instruments", my_instruments.len());
 
         let mut processed_count = 0;
 
         // Process each instrument
         for (index, instrument) in my_instruments.iter().enumerate() {
+            if self.shutdown.is_cancelled() {
+                info!(
+                    "Shutdown requested, skipping remaining {} instruments",
+                    my_instruments.len() - index
+                );
+                break;
+            }
+
+            let ticker = catalog
+                .get(&instrument.uid)
+                .map(|(_, ticker)| ticker.as_str())
+                .unwrap_or_default();
+            let mut status = DbLoadStatus::start(&run.run_id, &instrument.uid, ticker);
+
             match self
                 .process_instrument(
                     &instrument.uid,
                     instrument.first_1min_candle_date,
-                    instrument.last_1min_candle_date,
                     index,
                     my_instruments.len(),
+                    &mut status,
                 )
                 .await
             {
                 Ok(_) => {
+                    status.finish(None);
                     processed_count += 1;
                     debug!(
                         "Successfully processed instrument {}/{}: {}",
@@ -248,9 +532,11 @@ impl ClientCandle {
                         instrument.uid,
                         e
                     );
+                    status.finish(Some(e.to_string()));
                     // Continue with the next instrument
                 }
             }
+            self.ledger.record_instrument(run, &status).await;
 
             // Add a small delay between instruments to avoid API throttling
             tokio::time::sleep(std::time::Duration::from_millis(500)).await;
@@ -265,3 +551,209 @@ impl ClientCandle {
         Ok(processed_count)
     }
 }
+
+/// Инструменты из [storage] instruments для загрузки без PostgreSQL
+///
+/// Дата первой свечи берётся из каталога источника; инструменты, которых в
+/// каталоге нет, пропускаются
+fn configured_instruments(uids: &[String], shares: &[Share]) -> Vec<DbWatchlistInstrument> {
+    uids.iter()
+        .filter_map(|uid| {
+            let Some(share) = shares.iter().find(|share| &share.uid == uid) else {
+                warn!(
+                    "Configured instrument {} is not in the source catalog, skipping",
+                    uid
+                );
+                return None;
+            };
+            Some(DbWatchlistInstrument {
+                uid: uid.clone(),
+                first_1min_candle_date: share
+                    .first_1min_candle_date
+                    .as_ref()
+                    .map_or(0, |t| t.seconds),
+                last_1min_candle_date: 0,
+                is_active: true,
+                origin: WatchlistOrigin::Manual.as_str().to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::storage::memory::MemoryCandleRepository;
+    use crate::env_config::models::app_config::{
+        StorageBackend, TinkoffApiConfig, TrafficConfig, TrafficMode,
+    };
+    use crate::env_config::models::app_env::{AppEnv, Env};
+    use crate::services::sources::tinkoff_source::TinkoffCandleSource;
+    use crate::services::tinkoff_client_grpc::TinkoffClient;
+    use crate::services::tinkoff_mock::{spawn_test_mock, test_api};
+
+    #[test]
+    fn configured_instruments_take_first_dates_from_the_catalog() {
+        let shares = vec![Share {
+            uid: "uid-1".to_string(),
+            first_1min_candle_date: Some(prost_types::Timestamp {
+                seconds: 600,
+                nanos: 0,
+            }),
+            ..Default::default()
+        }];
+
+        let instruments =
+            configured_instruments(&["uid-1".to_string(), "unknown".to_string()], &shares);
+
+        assert_eq!(instruments.len(), 1);
+        assert_eq!(instruments[0].uid, "uid-1");
+        assert_eq!(instruments[0].first_1min_candle_date, 600);
+        assert!(instruments[0].is_active);
+    }
+
+    fn settings(base_url: String, mode: TrafficMode, dir: &std::path::Path) -> Arc<AppSettings> {
+        let mut app_config = AppConfig::new(&Env::Local);
+        app_config.tinkoff_api = TinkoffApiConfig {
+            traffic: TrafficConfig {
+                mode,
+                dir: dir.to_string_lossy().to_string(),
+                methods: vec!["GetCandles".to_string(), "Shares".to_string()],
+            },
+            ..test_api(base_url)
+        };
+        app_config.candles_scheduler.request_delay_ms = 0;
+        app_config.storage.backend = StorageBackend::Memory;
+        app_config.storage.instruments = vec!["uid-1".to_string()];
+
+        Arc::new(AppSettings {
+            app_config,
+            app_env: AppEnv {
+                env: Env::Local,
+                clickhouse_url: String::new(),
+                clickhouse_user: String::new(),
+                clickhouse_password: String::new(),
+                clickhouse_database: String::new(),
+                postgres_host: String::new(),
+                postgres_user: String::new(),
+                postgres_password: String::new(),
+                postgres_database: String::new(),
+                tinkoff_token: "token".to_string(),
+                api_admin_key: None,
+                server_port: 0,
+                server_address: String::new(),
+            },
+        })
+    }
+
+    /// Собирает загрузчик без баз данных поверх `repository`
+    async fn loader(
+        settings: Arc<AppSettings>,
+        repository: Arc<MemoryCandleRepository>,
+    ) -> ClientCandle {
+        let client =
+            TinkoffClient::connect(&settings.app_config.tinkoff_api, vec!["token".to_string()])
+                .await
+                .unwrap();
+        let source = Arc::new(TinkoffCandleSource::new(Arc::new(client)));
+        ClientCandle::new(
+            None,
+            None,
+            repository,
+            source,
+            Arc::new(ExclusionList::new(None, &settings)),
+            CancellationToken::new(),
+            settings,
+        )
+    }
+
+    /// Загружает свечи без баз данных и возвращает минуты из памяти и контрольную точку
+    async fn load(settings: Arc<AppSettings>) -> (usize, Vec<i64>, Option<i64>) {
+        let repository = Arc::new(MemoryCandleRepository::new());
+        let loader = loader(settings, repository.clone()).await;
+
+        let processed = loader.load_and_save_candles().await.unwrap();
+        let times = repository
+            .get_candles("uid-1", 0, i64::MAX)
+            .await
+            .unwrap()
+            .iter()
+            .map(|candle| candle.time)
+            .collect();
+        (
+            processed,
+            times,
+            repository.get_checkpoint("uid-1").await.unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn replays_a_recorded_load_into_the_memory_backend() {
+        let dir = std::env::temp_dir().join(format!("t-candles-load-{}", uuid::Uuid::new_v4()));
+        let now = chrono::Utc::now().timestamp();
+        let first_day = now - now.rem_euclid(DAY_SECONDS) - 2 * DAY_SECONDS;
+        let (_, base_url, shutdown) = spawn_test_mock(serde_json::json!({
+            "shares": [{
+                "uid": "uid-1", "figi": "FIGI1", "ticker": "AAA",
+                "first_1min_candle_date": first_day
+            }],
+            "candles": [
+                { "instrument_uid": "uid-1", "time": first_day + 600, "open": 1, "high": 2, "low": 1, "close": 2, "volume": 5 },
+                { "instrument_uid": "uid-1", "time": first_day + DAY_SECONDS + 600, "open": 2, "high": 2, "low": 1, "close": 1, "volume": 7 },
+                // A broken row is dropped: there is no quarantine without ClickHouse
+                { "instrument_uid": "uid-1", "time": first_day + DAY_SECONDS + 660, "open": 1, "high": 0, "low": 1, "close": 1, "volume": 1 }
+            ]
+        }))
+        .await;
+
+        let recorded = load(settings(base_url, TrafficMode::Record, &dir)).await;
+        shutdown.cancel();
+        let minutes = vec![first_day + 600, first_day + DAY_SECONDS + 600];
+        assert_eq!(
+            recorded,
+            (1, minutes.clone(), Some(first_day + DAY_SECONDS + 660))
+        );
+
+        // The replay runs against a closed port and loads the same candles
+        let replayed = load(settings(
+            "http://127.0.0.1:1".to_string(),
+            TrafficMode::Replay,
+            &dir,
+        ))
+        .await;
+        assert_eq!(replayed, recorded);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resumes_after_the_checkpoint_minute() {
+        let now = chrono::Utc::now().timestamp();
+        let first_day = now - now.rem_euclid(DAY_SECONDS) - 2 * DAY_SECONDS;
+        let (_, base_url, shutdown) = spawn_test_mock(serde_json::json!({
+            "candles": [
+                { "instrument_uid": "uid-1", "time": first_day + 600, "open": 1, "high": 2, "low": 1, "close": 2, "volume": 5 },
+                { "instrument_uid": "uid-1", "time": first_day + 660, "open": 2, "high": 2, "low": 1, "close": 1, "volume": 7 }
+            ]
+        }))
+        .await;
+        let settings = settings(base_url, TrafficMode::Off, &std::env::temp_dir());
+
+        let repository = Arc::new(MemoryCandleRepository::new());
+        repository
+            .save_checkpoint("uid-1", first_day + 600)
+            .await
+            .unwrap();
+        let loader = loader(settings, repository).await;
+        let mut status = DbLoadStatus::start("run", "uid-1", "AAA");
+        loader
+            .process_instrument("uid-1", first_day, 0, 1, &mut status)
+            .await
+            .unwrap();
+        shutdown.cancel();
+
+        // The stored checkpoint minute is not fetched and inserted again
+        assert_eq!(status.range_from, first_day + 660);
+        assert_eq!(status.candles_received, 1);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod client_candle;
+pub mod ohlcv_bar;
 pub mod scheduler_candles;
+pub mod validation;
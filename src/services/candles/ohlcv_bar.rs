@@ -0,0 +1,104 @@
+use crate::db::storage::stored_candle::StoredCandle;
+use crate::generate::tinkoff_public_invest_api_contract_v1::{HistoricCandle, Quotation};
+use crate::services::shares::models::quotation::quotation_to_f64;
+
+/// Цены и объём одной свечи, приведённые к числам для сравнения источников
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OhlcvBar {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+}
+
+impl OhlcvBar {
+    pub fn from_historic(candle: &HistoricCandle) -> Self {
+        let price = |q: Option<&Quotation>| q.map_or(0.0, |q| quotation_to_f64(q.units, q.nano));
+        Self {
+            open: price(candle.open.as_ref()),
+            high: price(candle.high.as_ref()),
+            low: price(candle.low.as_ref()),
+            close: price(candle.close.as_ref()),
+            volume: candle.volume,
+        }
+    }
+
+    pub fn from_stored(candle: &StoredCandle) -> Self {
+        Self {
+            open: quotation_to_f64(candle.open_units, candle.open_nano),
+            high: quotation_to_f64(candle.high_units, candle.high_nano),
+            low: quotation_to_f64(candle.low_units, candle.low_nano),
+            close: quotation_to_f64(candle.close_units, candle.close_nano),
+            volume: candle.volume,
+        }
+    }
+
+    /// Поля, по которым свеча отличается от эталонной `reference` больше допуска
+    ///
+    /// Допуски относительные: доля от значения эталона
+    pub fn diverging_fields(
+        &self,
+        reference: &OhlcvBar,
+        price_tolerance: f64,
+        volume_tolerance: f64,
+    ) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        let prices = [
+            ("open", reference.open, self.open),
+            ("high", reference.high, self.high),
+            ("low", reference.low, self.low),
+            ("close", reference.close, self.close),
+        ];
+        for (name, expected, actual) in prices {
+            if differs(expected, actual, price_tolerance) {
+                fields.push(name);
+            }
+        }
+        if differs(
+            reference.volume as f64,
+            self.volume as f64,
+            volume_tolerance,
+        ) {
+            fields.push("volume");
+        }
+        fields
+    }
+}
+
+fn differs(expected: f64, actual: f64, tolerance: f64) -> bool {
+    (expected - actual).abs() > tolerance * expected.abs().max(f64::EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tolerance_is_relative_to_the_reference() {
+        let reference = OhlcvBar {
+            open: 100.0,
+            high: 110.0,
+            low: 90.0,
+            close: 105.0,
+            volume: 10_000,
+        };
+        let bar = OhlcvBar {
+            close: 105.1,
+            volume: 10_100,
+            ..reference
+        };
+
+        assert!(bar.diverging_fields(&reference, 0.001, 0.01).is_empty());
+        assert_eq!(
+            bar.diverging_fields(&reference, 0.0005, 0.005),
+            vec!["close", "volume"]
+        );
+        // A zero reference still flags any difference
+        let zero = OhlcvBar {
+            volume: 0,
+            ..reference
+        };
+        assert_eq!(bar.diverging_fields(&zero, 0.001, 0.01), vec!["volume"]);
+    }
+}
@@ -1,8 +1,12 @@
-use std::{sync::Arc, time::Duration};
-use tracing::{debug, error, info};
+use std::sync::Arc;
+use tracing::{error, info};
 
 use super::client_candle::ClientCandle;
-use crate::{AppState, env_config::models::app_config::OperationWindow};
+use crate::{
+    AppState,
+    env_config::models::app_config::OperationWindow,
+    services::{job_ledger::CANDLES_JOB, scheduling::scheduled_job::ScheduledJob},
+};
 
 pub struct SchedulerCandles {
     app_state: Arc<AppState>,
@@ -21,11 +25,16 @@ impl SchedulerCandles {
             return Ok(0);
         }
 
-        // Используем клиент напрямую из AppState
+        // Используем клиент напрямую из AppState; при выборе лидера запуск
+        // пропускается, если задачу уже выполняет другая реплика
         self.app_state
-            .client_tinkoff_candle
-            .load_and_save_candles()
+            .job_locks
+            .run_exclusive(
+                CANDLES_JOB,
+                self.app_state.client_tinkoff_candle.load_and_save_candles(),
+            )
             .await
+            .unwrap_or(Ok(0))
     }
 
     /// Start the scheduler with proper configuration checks
@@ -41,6 +50,7 @@ impl SchedulerCandles {
         info!("Starting candles scheduler");
 
         // Run initial update if configured
+        // A shutdown signal during this run stops it at the next checkpoint
         if config.initial_run {
             info!("Performing initial historical candle data update");
             match self.trigger_update().await {
@@ -54,8 +64,8 @@ impl SchedulerCandles {
 
         // Log operation window
         info!(
-            "Candle scheduler operation window: {} to {} UTC",
-            config.start_time, config.end_time
+            "Candle scheduler operation window: {}",
+            config.operation_window.describe()
         );
 
         info!(
@@ -63,34 +73,11 @@ impl SchedulerCandles {
             config.request_delay_ms,
         );
 
-        // Create the candle client
-        // let candle_client = ClientTinkoffCandle::new(self.app_state.clone());
-
-        // Clone app_state for the task
-        let app_state = self.app_state.clone();
-
-        tokio::spawn(async move {
-            // Initial delay to allow other initialization to complete
-            tokio::time::sleep(Duration::from_secs(60)).await;
-
-            // Main scheduler loop
-            loop {
-                // Check operation window
-                let config = &app_state.settings.app_config.candles_scheduler;
-                if !config.is_operation_allowed() {
-                    debug!(
-                        "Candle scheduler: skipping update - outside operation window (current time: {})",
-                        chrono::Utc::now().format("%H:%M:%S")
-                    );
-
-                    // Wait before checking again
-                    tokio::time::sleep(Duration::from_secs(300)).await;
-                    continue;
-                }
-
-                info!("Candle scheduler: triggering update");
-
-                // Trigger candle update using client from app_state
+        // The loop runs on the task tracker so shutdown waits for the batch in flight
+        ScheduledJob::new(CANDLES_JOB, &config.schedule).spawn(
+            self.app_state.clone(),
+            |config| config.candles_scheduler.is_operation_allowed(),
+            |app_state| async move {
                 match app_state
                     .client_tinkoff_candle
                     .load_and_save_candles()
@@ -102,10 +89,7 @@ impl SchedulerCandles {
                     ),
                     Err(e) => error!("Candle scheduler: failed to update candle data: {}", e),
                 }
-
-                // Wait before the next full update
-                tokio::time::sleep(Duration::from_secs(12 * 60 * 60)).await;
-            }
-        });
+            },
+        );
     }
 }
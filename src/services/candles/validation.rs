@@ -0,0 +1,165 @@
+use crate::generate::tinkoff_public_invest_api_contract_v1::{HistoricCandle, Quotation};
+
+/// Причина, по которой свеча не прошла проверку и отправлена в карантин
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    MissingTime,
+    MissingPrice,
+    NonPositivePrice,
+    HighBelowBody,
+    LowAboveBody,
+    MisalignedTime,
+    AbsurdVolume,
+}
+
+impl RejectReason {
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::MissingTime => "MISSING_TIME",
+            Self::MissingPrice => "MISSING_PRICE",
+            Self::NonPositivePrice => "NON_POSITIVE_PRICE",
+            Self::HighBelowBody => "HIGH_BELOW_BODY",
+            Self::LowAboveBody => "LOW_ABOVE_BODY",
+            Self::MisalignedTime => "MISALIGNED_TIME",
+            Self::AbsurdVolume => "ABSURD_VOLUME",
+        }
+    }
+}
+
+/// Цена в нано-единицах, чтобы сравнивать Quotation без потери точности
+fn to_nano(q: &Quotation) -> i128 {
+    q.units as i128 * 1_000_000_000 + q.nano as i128
+}
+
+/// Проверяет свечу перед записью
+///
+/// # Arguments
+/// * `candle` - Свеча из ответа API
+/// * `interval_seconds` - Длина интервала; время свечи должно быть кратно ему
+/// * `max_volume` - Верхняя граница объёма в лотах
+pub fn validate(
+    candle: &HistoricCandle,
+    interval_seconds: i64,
+    max_volume: i64,
+) -> Result<(), RejectReason> {
+    let time = candle.time.as_ref().ok_or(RejectReason::MissingTime)?;
+
+    let (Some(open), Some(high), Some(low), Some(close)) =
+        (&candle.open, &candle.high, &candle.low, &candle.close)
+    else {
+        return Err(RejectReason::MissingPrice);
+    };
+    let (open, high, low, close) = (to_nano(open), to_nano(high), to_nano(low), to_nano(close));
+
+    if open <= 0 || high <= 0 || low <= 0 || close <= 0 {
+        return Err(RejectReason::NonPositivePrice);
+    }
+    if high < open.max(close) {
+        return Err(RejectReason::HighBelowBody);
+    }
+    if low > open.min(close) {
+        return Err(RejectReason::LowAboveBody);
+    }
+    if time.nanos != 0 || time.seconds % interval_seconds != 0 {
+        return Err(RejectReason::MisalignedTime);
+    }
+    if candle.volume < 0 || candle.volume > max_volume {
+        return Err(RejectReason::AbsurdVolume);
+    }
+
+    Ok(())
+}
+
+/// Делит пачку свечей на прошедшие проверку и отклонённые с причиной
+pub fn partition(
+    candles: Vec<HistoricCandle>,
+    interval_seconds: i64,
+    max_volume: i64,
+) -> (Vec<HistoricCandle>, Vec<(HistoricCandle, RejectReason)>) {
+    let mut valid = Vec::with_capacity(candles.len());
+    let mut rejected = Vec::new();
+
+    for candle in candles {
+        match validate(&candle, interval_seconds, max_volume) {
+            Ok(()) => valid.push(candle),
+            Err(reason) => rejected.push((candle, reason)),
+        }
+    }
+
+    (valid, rejected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(units: i64) -> Option<Quotation> {
+        Some(Quotation { units, nano: 0 })
+    }
+
+    fn candle(open: i64, high: i64, low: i64, close: i64) -> HistoricCandle {
+        HistoricCandle {
+            open: price(open),
+            high: price(high),
+            low: price(low),
+            close: price(close),
+            volume: 100,
+            time: Some(prost_types::Timestamp {
+                seconds: 1_700_000_040,
+                nanos: 0,
+            }),
+            is_complete: true,
+        }
+    }
+
+    #[test]
+    fn test_validate_reason_codes() {
+        let check = |c: &HistoricCandle| validate(c, 60, 1_000);
+
+        assert_eq!(check(&candle(10, 12, 9, 11)), Ok(()));
+        assert_eq!(check(&candle(10, 10, 10, 10)), Ok(()));
+        assert_eq!(
+            check(&candle(10, 11, 9, 12)),
+            Err(RejectReason::HighBelowBody)
+        );
+        assert_eq!(
+            check(&candle(10, 12, 11, 11)),
+            Err(RejectReason::LowAboveBody)
+        );
+        assert_eq!(
+            check(&candle(10, 12, 0, 11)),
+            Err(RejectReason::NonPositivePrice)
+        );
+
+        let missing = HistoricCandle {
+            close: None,
+            ..candle(10, 12, 9, 11)
+        };
+        assert_eq!(check(&missing), Err(RejectReason::MissingPrice));
+
+        let misaligned = HistoricCandle {
+            time: Some(prost_types::Timestamp {
+                seconds: 1_700_000_041,
+                nanos: 0,
+            }),
+            ..candle(10, 12, 9, 11)
+        };
+        assert_eq!(check(&misaligned), Err(RejectReason::MisalignedTime));
+
+        let huge = HistoricCandle {
+            volume: 1_001,
+            ..candle(10, 12, 9, 11)
+        };
+        assert_eq!(check(&huge), Err(RejectReason::AbsurdVolume));
+    }
+
+    #[test]
+    fn test_partition() {
+        let (valid, rejected) =
+            partition(vec![candle(10, 12, 9, 11), candle(0, 0, 0, 0)], 60, 1_000);
+
+        assert_eq!(valid.len(), 1);
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].1, RejectReason::NonPositivePrice);
+    }
+}
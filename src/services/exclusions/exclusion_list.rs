@@ -0,0 +1,224 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use clickhouse::error::Error as ClickhouseError;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::{
+    db::clickhouse::{
+        models::exclusion::DbExclusion, repository::repository_exclusion::ExclusionRepository,
+    },
+    env_config::models::{app_config::ExclusionKeyType, app_setting::AppSettings},
+};
+
+/// Откуда взялось исключение: из конфигурации или через API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExclusionSource {
+    Config,
+    Api,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exclusion {
+    pub key_type: ExclusionKeyType,
+    pub key_value: String,
+    pub reason: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_deserializing, default = "api_source")]
+    pub source: ExclusionSource,
+}
+
+fn api_source() -> ExclusionSource {
+    ExclusionSource::Api
+}
+
+/// Совпадает ли ключ исключения со значением инструмента: тикеры сравниваются без учёта регистра
+fn key_matches(key_type: ExclusionKeyType, key_value: &str, value: &str) -> bool {
+    match key_type {
+        ExclusionKeyType::Uid | ExclusionKeyType::Figi => key_value == value,
+        ExclusionKeyType::Ticker => key_value.eq_ignore_ascii_case(value),
+    }
+}
+
+/// Ключ в том виде, в каком он хранится в instrument_exclusions
+fn normalize_key(key_type: ExclusionKeyType, key_value: &str) -> String {
+    match key_type {
+        ExclusionKeyType::Uid | ExclusionKeyType::Figi => key_value.to_string(),
+        ExclusionKeyType::Ticker => key_value.to_ascii_uppercase(),
+    }
+}
+
+impl Exclusion {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+
+    fn matches(&self, uid: &str, figi: &str, ticker: &str) -> bool {
+        let value = match self.key_type {
+            ExclusionKeyType::Uid => uid,
+            ExclusionKeyType::Figi => figi,
+            ExclusionKeyType::Ticker => ticker,
+        };
+        key_matches(self.key_type, &self.key_value, value)
+    }
+}
+
+/// Снимок действующих исключений для синхронной проверки в фильтрах
+pub struct ExclusionSet {
+    entries: Vec<Exclusion>,
+}
+
+impl ExclusionSet {
+    /// Возвращает исключение, под которое попадает инструмент
+    pub fn find(&self, uid: &str, figi: &str, ticker: &str) -> Option<&Exclusion> {
+        self.entries.iter().find(|e| e.matches(uid, figi, ticker))
+    }
+}
+
+/// Управляемый список исключённых инструментов
+///
+/// Исключения из конфигурации объединяются с исключениями из таблицы
+/// instrument_exclusions, которые редактируются через API. Список
+/// применяется к обновлению каталога, вселенной и загрузке свечей.
+/// Без ClickHouse действуют только исключения из конфигурации
+pub struct ExclusionList {
+    repository: Option<Arc<ExclusionRepository>>,
+    config_entries: Vec<Exclusion>,
+    entries: RwLock<Vec<Exclusion>>,
+}
+
+impl ExclusionList {
+    pub fn new(repository: Option<Arc<ExclusionRepository>>, settings: &AppSettings) -> Self {
+        let config_entries: Vec<Exclusion> = settings
+            .app_config
+            .exclusions
+            .iter()
+            .map(|e| Exclusion {
+                key_type: e.key_type,
+                key_value: e.key_value.clone(),
+                reason: e.reason.clone(),
+                expires_at: e.expires_at,
+                source: ExclusionSource::Config,
+            })
+            .collect();
+
+        Self {
+            repository,
+            entries: RwLock::new(config_entries.clone()),
+            config_entries,
+        }
+    }
+
+    /// Перечитывает исключения из ClickHouse; вызывается перед каждым запуском задач,
+    /// чтобы изменения через API на любой реплике применялись без перезапуска
+    pub async fn reload(&self) -> Result<usize, ClickhouseError> {
+        let mut entries = self.config_entries.clone();
+
+        let rows = match &self.repository {
+            Some(repository) => repository.get_exclusions().await?,
+            None => Vec::new(),
+        };
+        for row in rows {
+            let Ok(key_type) = row.key_type.parse::<ExclusionKeyType>() else {
+                warn!("Skipping exclusion with unknown key type: {}", row.key_type);
+                continue;
+            };
+            entries.push(Exclusion {
+                key_type,
+                key_value: row.key_value,
+                reason: row.reason,
+                expires_at: row.expires_at.and_then(|t| DateTime::from_timestamp(t, 0)),
+                source: ExclusionSource::Api,
+            });
+        }
+
+        let count = entries.len();
+        *self.entries.write().await = entries;
+        info!("Loaded {} instrument exclusions", count);
+        Ok(count)
+    }
+
+    /// Действующие (не истёкшие) исключения
+    pub async fn snapshot(&self) -> ExclusionSet {
+        let now = Utc::now();
+        let entries = self
+            .entries
+            .read()
+            .await
+            .iter()
+            .filter(|e| e.is_active(now))
+            .cloned()
+            .collect();
+        ExclusionSet { entries }
+    }
+
+    /// Все исключения, включая истёкшие
+    pub async fn list(&self) -> Vec<Exclusion> {
+        self.entries.read().await.clone()
+    }
+
+    /// Можно ли менять список через API: исключения хранятся в ClickHouse
+    pub fn is_editable(&self) -> bool {
+        self.repository.is_some()
+    }
+
+    pub async fn add(&self, exclusion: &Exclusion) -> Result<(), ClickhouseError> {
+        self.editable_repository()?
+            .upsert_exclusion(&DbExclusion {
+                key_type: exclusion.key_type.as_str_name().to_string(),
+                key_value: normalize_key(exclusion.key_type, &exclusion.key_value),
+                reason: exclusion.reason.clone(),
+                expires_at: exclusion.expires_at.map(|t| t.timestamp()),
+            })
+            .await?;
+        self.reload().await?;
+        Ok(())
+    }
+
+    pub async fn remove(
+        &self,
+        key_type: ExclusionKeyType,
+        key_value: &str,
+    ) -> Result<(), ClickhouseError> {
+        self.editable_repository()?
+            .delete_exclusion(key_type.as_str_name(), &normalize_key(key_type, key_value))
+            .await?;
+        self.reload().await?;
+        Ok(())
+    }
+
+    fn editable_repository(&self) -> Result<&ExclusionRepository, ClickhouseError> {
+        self.repository
+            .as_deref()
+            .ok_or_else(|| ClickhouseError::Custom("ClickHouse is not connected".to_string()))
+    }
+
+    /// Является ли исключение частью конфигурации (через API его не удалить)
+    pub fn is_config_entry(&self, key_type: ExclusionKeyType, key_value: &str) -> bool {
+        self.config_entries
+            .iter()
+            .any(|e| e.key_type == key_type && key_matches(key_type, &e.key_value, key_value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_follow_matcher_normalization() {
+        assert!(key_matches(ExclusionKeyType::Ticker, "sber", "SBER"));
+        assert!(!key_matches(
+            ExclusionKeyType::Figi,
+            "bbg004730n88",
+            "BBG004730N88"
+        ));
+        assert!(key_matches(ExclusionKeyType::Uid, "uid-1", "uid-1"));
+
+        assert_eq!(normalize_key(ExclusionKeyType::Ticker, "sber"), "SBER");
+        assert_eq!(normalize_key(ExclusionKeyType::Uid, "Uid-1"), "Uid-1");
+    }
+}
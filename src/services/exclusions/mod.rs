@@ -0,0 +1 @@
+pub mod exclusion_list;
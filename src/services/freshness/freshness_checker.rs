@@ -0,0 +1,281 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use super::freshness_sla::{AlertPlan, NO_CANDLES, plan_alerts, trading_days_lag};
+use crate::app_state::models::AppState;
+use crate::db::clickhouse::clickhouse_service::ClickhouseService;
+use crate::db::postgres::models::freshness_alert::DbFreshnessAlert;
+use crate::db::postgres::postgres_service::PostgresService;
+use crate::env_config::models::freshness::AlertFormat;
+use crate::services::job_ledger::FRESHNESS_JOB;
+use crate::services::scheduling::scheduled_job::ScheduledJob;
+use crate::services::webhook_notifier::WebhookNotifier;
+
+pub type FreshnessError = Box<dyn Error + Send + Sync>;
+
+/// Инструмент в уведомлении
+#[derive(Debug, Serialize)]
+pub struct FreshnessAlertItem {
+    pub instrument_uid: String,
+    pub ticker: String,
+    pub sla: String,
+    pub last_candle_time: Option<i64>, // None — свечей нет
+    pub lag_trading_days: Option<i64>,
+    pub lag_hours: Option<i64>,
+}
+
+/// Проверка свежести свечей по SLA из секции [freshness]
+pub struct FreshnessChecker {
+    app_state: Arc<AppState>,
+    clickhouse_service: Arc<ClickhouseService>,
+    postgres_service: Arc<PostgresService>,
+    notifier: WebhookNotifier,
+}
+
+impl FreshnessChecker {
+    /// None, если ClickHouse или PostgreSQL не подключены
+    pub fn new(app_state: Arc<AppState>) -> Option<Self> {
+        let config = &app_state.settings.app_config.freshness;
+        let notifier =
+            WebhookNotifier::new(config.webhook_url.clone(), config.webhook_timeout_seconds);
+        Some(Self {
+            clickhouse_service: app_state.clickhouse_service.clone()?,
+            postgres_service: app_state.postgres_service.clone()?,
+            app_state,
+            notifier,
+        })
+    }
+
+    /// Проверяет инструменты, отправляет уведомления и сохраняет состояние алертов
+    ///
+    /// Если уведомление не доставлено, состояние не меняется и следующая проверка
+    /// отправит его снова
+    pub async fn check(&self, now: DateTime<Utc>) -> Result<AlertPlan, FreshnessError> {
+        let config = &self.app_state.settings.app_config.freshness;
+        let instruments = self
+            .postgres_service
+            .repository_watchlist
+            .get_active()
+            .await?;
+        let tickers: HashMap<String, (String, String)> = self
+            .clickhouse_service
+            .repository_share_history
+            .get_current_versions()
+            .await?
+            .into_iter()
+            .map(|v| (v.uid, (v.figi, v.ticker)))
+            .collect();
+        let exclusions = self.app_state.exclusions.snapshot().await;
+
+        let mut monitored = HashSet::new();
+        let mut breaches = Vec::new();
+        for instrument in &instruments {
+            let (figi, ticker) = tickers
+                .get(&instrument.uid)
+                .map(|(figi, ticker)| (figi.as_str(), ticker.as_str()))
+                .unwrap_or_default();
+            if exclusions.find(&instrument.uid, figi, ticker).is_some() {
+                continue;
+            }
+            let Some(sla) = config.sla_for(&instrument.uid) else {
+                continue;
+            };
+            monitored.insert(instrument.uid.clone());
+
+            let last_candle_time = self
+                .app_state
+                .candle_repository
+                .get_checkpoint(&instrument.uid)
+                .await?
+                .unwrap_or(0);
+            let lag = if last_candle_time > 0 {
+                trading_days_lag(last_candle_time, now, config)
+            } else {
+                NO_CANDLES
+            };
+            if lag == NO_CANDLES || lag > i64::from(sla.max_lag_trading_days) {
+                breaches.push(DbFreshnessAlert {
+                    instrument_uid: instrument.uid.clone(),
+                    sla: sla.name.clone(),
+                    opened_at: now.timestamp(),
+                    last_notified_at: now.timestamp(),
+                    last_candle_time,
+                    lag_trading_days: lag,
+                });
+            }
+        }
+
+        let repository = &self.postgres_service.repository_freshness_alert;
+        let open = repository.get_open().await?;
+        let plan = plan_alerts(
+            breaches,
+            open,
+            &monitored,
+            now.timestamp(),
+            config.repeat_after_minutes * 60,
+        );
+
+        if plan.has_notifications() {
+            let item = |alert: &DbFreshnessAlert| FreshnessAlertItem {
+                instrument_uid: alert.instrument_uid.clone(),
+                ticker: tickers
+                    .get(&alert.instrument_uid)
+                    .map(|(_, ticker)| ticker.clone())
+                    .unwrap_or_default(),
+                sla: alert.sla.clone(),
+                last_candle_time: (alert.last_candle_time > 0).then_some(alert.last_candle_time),
+                lag_trading_days: (alert.lag_trading_days != NO_CANDLES)
+                    .then_some(alert.lag_trading_days),
+                lag_hours: (alert.last_candle_time > 0)
+                    .then(|| (now.timestamp() - alert.last_candle_time) / 3600),
+            };
+            let opened: Vec<_> = plan.opened.iter().map(item).collect();
+            let repeated: Vec<_> = plan.repeated.iter().map(item).collect();
+            let recovered: Vec<_> = plan.recovered.iter().map(item).collect();
+            for alert in opened.iter().chain(&repeated) {
+                warn!(
+                    "Freshness SLA {} breached by {} ({}): {} trading days behind",
+                    alert.sla,
+                    alert.ticker,
+                    alert.instrument_uid,
+                    alert
+                        .lag_trading_days
+                        .map_or("no candles".to_string(), |d| d.to_string())
+                );
+            }
+
+            if !self.notifier.is_enabled() {
+                warn!("freshness.webhook_url is not set, alerts are only logged");
+            }
+            self.notifier
+                .send(&payload(
+                    config.format,
+                    config.telegram_chat_id.as_deref(),
+                    &opened,
+                    &repeated,
+                    &recovered,
+                ))
+                .await?;
+        }
+
+        repository.save(&plan.to_save).await?;
+        repository.close(&plan.to_close).await?;
+        Ok(plan)
+    }
+}
+
+fn describe(item: &FreshnessAlertItem) -> String {
+    let name = if item.ticker.is_empty() {
+        item.instrument_uid.clone()
+    } else {
+        format!("{} ({})", item.ticker, item.instrument_uid)
+    };
+    match (item.last_candle_time, item.lag_trading_days, item.lag_hours) {
+        (Some(time), Some(days), Some(hours)) => format!(
+            "[{}] {}: last candle {}, {} trading days / {} h behind",
+            item.sla,
+            name,
+            DateTime::from_timestamp(time, 0).map_or(time.to_string(), |t| t
+                .format("%Y-%m-%d %H:%M UTC")
+                .to_string()),
+            days,
+            hours
+        ),
+        _ => format!("[{}] {}: no candles", item.sla, name),
+    }
+}
+
+/// Тело запроса к webhook в выбранном формате
+fn payload(
+    format: AlertFormat,
+    telegram_chat_id: Option<&str>,
+    opened: &[FreshnessAlertItem],
+    repeated: &[FreshnessAlertItem],
+    recovered: &[FreshnessAlertItem],
+) -> serde_json::Value {
+    if format == AlertFormat::Json {
+        return json!({
+            "event": "candle_freshness",
+            "breached": opened,
+            "still_breached": repeated,
+            "recovered": recovered,
+        });
+    }
+
+    let mut sections = Vec::new();
+    let sections_spec = [
+        ("Candle freshness SLA breached", opened),
+        ("Candle freshness SLA still breached", repeated),
+        ("Candle freshness recovered", recovered),
+    ];
+    for (title, items) in sections_spec {
+        if items.is_empty() {
+            continue;
+        }
+        let lines: Vec<String> = items.iter().map(describe).collect();
+        sections.push(format!(
+            "{} ({}):\n{}",
+            title,
+            items.len(),
+            lines.join("\n")
+        ));
+    }
+    let text = sections.join("\n\n");
+
+    match format {
+        AlertFormat::Telegram => json!({ "chat_id": telegram_chat_id, "text": text }),
+        _ => json!({ "text": text }),
+    }
+}
+
+/// Периодический запуск проверки свежести
+///
+/// Проверка идёт каждые несколько минут, поэтому в журнал job_runs она не пишется
+pub struct FreshnessScheduler {
+    app_state: Arc<AppState>,
+}
+
+impl FreshnessScheduler {
+    pub fn new(app_state: Arc<AppState>) -> Self {
+        Self { app_state }
+    }
+
+    pub async fn start(&self) {
+        let config = &self.app_state.settings.app_config.freshness;
+        if !config.enabled {
+            info!("Candle freshness monitoring is disabled in configuration");
+            return;
+        }
+        if self.app_state.clickhouse_service.is_none() || self.app_state.postgres_service.is_none()
+        {
+            warn!("Candle freshness monitoring needs ClickHouse and PostgreSQL; not starting");
+            return;
+        }
+
+        info!("Starting freshness checker with {} SLAs", config.slas.len());
+
+        ScheduledJob::new(FRESHNESS_JOB, &config.schedule).spawn(
+            self.app_state.clone(),
+            |_| true,
+            |app_state| async move {
+                let Some(checker) = FreshnessChecker::new(app_state) else {
+                    return;
+                };
+                match checker.check(Utc::now()).await {
+                    Ok(plan) => info!(
+                        "Freshness check: {} breached, {} new, {} recovered",
+                        plan.to_save.len(),
+                        plan.opened.len(),
+                        plan.recovered.len()
+                    ),
+                    Err(e) => error!("Freshness check failed: {}", e),
+                }
+            },
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc, Weekday};
+use std::collections::{HashMap, HashSet};
+
+use crate::db::postgres::models::freshness_alert::DbFreshnessAlert;
+use crate::env_config::models::freshness::FreshnessConfig;
+
+/// Lag of an instrument that has no candles at all
+pub const NO_CANDLES: i64 = -1;
+
+fn is_trading_day(date: NaiveDate, config: &FreshnessConfig) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !config.holidays.contains(&date)
+}
+
+/// Завершённые торговые дни после даты последней свечи, не считая сегодняшнего
+///
+/// Даты берутся в часовом поясе `config.timezone`: свеча пятницы в понедельник даёт 0,
+/// во вторник — 1 (понедельник прошёл без свечей)
+pub fn trading_days_lag(
+    last_candle_time: i64,
+    now: DateTime<Utc>,
+    config: &FreshnessConfig,
+) -> i64 {
+    let Some(last) = DateTime::from_timestamp(last_candle_time, 0) else {
+        return NO_CANDLES;
+    };
+    let last_date = last.with_timezone(&config.timezone).date_naive();
+    let today = now.with_timezone(&config.timezone).date_naive();
+
+    let mut lag = 0;
+    let mut date = last_date;
+    while let Some(next) = date.checked_add_days(Days::new(1)) {
+        if next >= today {
+            break;
+        }
+        if is_trading_day(next, config) {
+            lag += 1;
+        }
+        date = next;
+    }
+    lag
+}
+
+/// Какие алерты отправить и какое состояние сохранить после проверки
+#[derive(Debug, Default, PartialEq)]
+pub struct AlertPlan {
+    pub opened: Vec<DbFreshnessAlert>,    // Новые нарушения
+    pub repeated: Vec<DbFreshnessAlert>,  // Нарушения, о которых пора напомнить
+    pub recovered: Vec<DbFreshnessAlert>, // Снова свежие инструменты
+    pub to_save: Vec<DbFreshnessAlert>,   // Все открытые алерты после проверки
+    pub to_close: Vec<String>,            // Восстановившиеся и больше не проверяемые инструменты
+}
+
+impl AlertPlan {
+    pub fn has_notifications(&self) -> bool {
+        !self.opened.is_empty() || !self.repeated.is_empty() || !self.recovered.is_empty()
+    }
+}
+
+/// Сопоставляет текущие нарушения с открытыми алертами
+///
+/// `breaches` — нарушения этой проверки с opened_at и last_notified_at = `now`;
+/// `monitored` — инструменты, которые проверялись. Алерты инструментов, выпавших
+/// из проверки (выключены или исключены), закрываются без уведомления
+pub fn plan_alerts(
+    breaches: Vec<DbFreshnessAlert>,
+    open: Vec<DbFreshnessAlert>,
+    monitored: &HashSet<String>,
+    now: i64,
+    repeat_after_seconds: i64,
+) -> AlertPlan {
+    let mut open: HashMap<String, DbFreshnessAlert> = open
+        .into_iter()
+        .map(|alert| (alert.instrument_uid.clone(), alert))
+        .collect();
+
+    let mut plan = AlertPlan::default();
+    for mut breach in breaches {
+        match open.remove(&breach.instrument_uid) {
+            None => plan.opened.push(breach.clone()),
+            Some(previous) => {
+                breach.opened_at = previous.opened_at;
+                if now - previous.last_notified_at >= repeat_after_seconds {
+                    plan.repeated.push(breach.clone());
+                } else {
+                    breach.last_notified_at = previous.last_notified_at;
+                }
+            }
+        }
+        plan.to_save.push(breach);
+    }
+
+    for (uid, alert) in open {
+        if monitored.contains(&uid) {
+            plan.recovered.push(alert);
+        }
+        plan.to_close.push(uid);
+    }
+    plan.recovered
+        .sort_by(|a, b| a.instrument_uid.cmp(&b.instrument_uid));
+    plan.to_close.sort();
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env_config::models::freshness::AlertFormat;
+    use crate::env_config::models::schedule::{CatchUpPolicy, ScheduleConfig};
+    use chrono::TimeZone;
+
+    fn config() -> FreshnessConfig {
+        FreshnessConfig {
+            enabled: true,
+            schedule: ScheduleConfig {
+                cron: Some("0 */15 * * * *".to_string()),
+                run_times: Vec::new(),
+                catch_up: CatchUpPolicy::Skip,
+            },
+            timezone: chrono_tz::Europe::Moscow,
+            holidays: vec![NaiveDate::from_ymd_opt(2025, 6, 12).unwrap()],
+            webhook_url: None,
+            webhook_timeout_seconds: 10,
+            format: AlertFormat::Json,
+            telegram_chat_id: None,
+            repeat_after_minutes: 60,
+            slas: Vec::new(),
+        }
+    }
+
+    fn alert(uid: &str, at: i64) -> DbFreshnessAlert {
+        DbFreshnessAlert {
+            instrument_uid: uid.to_string(),
+            sla: "default".to_string(),
+            opened_at: at,
+            last_notified_at: at,
+            last_candle_time: 0,
+            lag_trading_days: 2,
+        }
+    }
+
+    #[test]
+    fn counts_finished_trading_days() {
+        let config = config();
+        // Friday 2025-06-06 20:49 MSK
+        let friday = Utc
+            .with_ymd_and_hms(2025, 6, 6, 17, 49, 0)
+            .unwrap()
+            .timestamp();
+        let monday = Utc.with_ymd_and_hms(2025, 6, 9, 5, 0, 0).unwrap();
+        let tuesday = Utc.with_ymd_and_hms(2025, 6, 10, 5, 0, 0).unwrap();
+        let friday_after_holiday = Utc.with_ymd_and_hms(2025, 6, 13, 5, 0, 0).unwrap();
+
+        assert_eq!(trading_days_lag(friday, monday, &config), 0);
+        assert_eq!(trading_days_lag(friday, tuesday, &config), 1);
+        // Mon, Tue, Wed; Thursday 12 June is a holiday
+        assert_eq!(trading_days_lag(friday, friday_after_holiday, &config), 3);
+    }
+
+    #[test]
+    fn deduplicates_and_recovers() {
+        let monitored: HashSet<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        let open = vec![
+            alert("a", 0),
+            alert("b", 3000),
+            alert("c", 0),
+            alert("gone", 0),
+        ];
+        let breaches = vec![alert("a", 3600), alert("b", 3600), alert("new", 3600)];
+
+        let plan = plan_alerts(breaches, open, &monitored, 3600, 3600);
+        assert_eq!(plan.opened, vec![alert("new", 3600)]);
+        assert_eq!(plan.repeated.len(), 1);
+        assert_eq!(plan.repeated[0].opened_at, 0);
+        assert_eq!(plan.recovered, vec![alert("c", 0)]);
+        assert_eq!(plan.to_close, vec!["c", "gone"]);
+
+        // b was notified 10 minutes ago: saved without a new notification
+        let b = plan
+            .to_save
+            .iter()
+            .find(|a| a.instrument_uid == "b")
+            .unwrap();
+        assert_eq!((b.opened_at, b.last_notified_at), (3000, 3000));
+    }
+}
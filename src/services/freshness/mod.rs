@@ -0,0 +1,2 @@
+pub mod freshness_checker;
+pub mod freshness_sla;
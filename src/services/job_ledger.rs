@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use crate::db::postgres::{
+    models::job_run::{DbJobRun, DbLoadStatus},
+    repository::repository_job_run::JobRunRepository,
+};
+
+/// Имена задач в журнале и в реестре расписаний
+pub const CANDLES_JOB: &str = "candles";
+pub const SHARES_JOB: &str = "shares";
+pub const ARCHIVE_JOB: &str = "archive_compaction";
+pub const RETENTION_JOB: &str = "retention";
+pub const RECONCILIATION_JOB: &str = "reconciliation";
+pub const FRESHNESS_JOB: &str = "freshness";
+pub const SOURCE_COMPARISON_JOB: &str = "source_comparison";
+
+/// Журнал запусков задач планировщиков
+///
+/// Ошибки записи в журнал логируются репозиторием и не прерывают саму задачу.
+/// Без PostgreSQL журнал не ведётся, а запуски считаются только в памяти
+pub struct JobLedger {
+    repository: Option<Arc<JobRunRepository>>,
+}
+
+impl JobLedger {
+    pub fn new(repository: Option<Arc<JobRunRepository>>) -> Self {
+        Self { repository }
+    }
+
+    pub async fn start(&self, job: &str) -> DbJobRun {
+        let run = DbJobRun::start(job);
+        self.save_run(&run).await;
+        run
+    }
+
+    pub async fn record_instrument(&self, run: &mut DbJobRun, status: &DbLoadStatus) {
+        run.add(status);
+        if let Some(repository) = &self.repository {
+            let _ = repository.insert_load_status(status).await;
+        }
+    }
+
+    pub async fn cancel(&self, run: &mut DbJobRun) {
+        run.cancel();
+        self.save_run(run).await;
+    }
+
+    pub async fn finish(&self, run: &mut DbJobRun, error: Option<String>) {
+        run.finish(error);
+        self.save_run(run).await;
+    }
+
+    async fn save_run(&self, run: &DbJobRun) {
+        if let Some(repository) = &self.repository {
+            let _ = repository.save_run(run).await;
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::{future::Future, sync::Arc};
+
+use sqlx::{Postgres, pool::PoolConnection};
+use tracing::{debug, error, info};
+
+use crate::db::postgres::connection::PostgresConnection;
+
+/// Ключ advisory lock вычисляется в PostgreSQL из имени задачи
+const LOCK_PREFIX: &str = "t-candles:";
+
+/// Блокировки задач между репликами на advisory lock PostgreSQL
+///
+/// Блокировка сессионная: она держится на отдельном соединении из пула и
+/// снимается при освобождении или автоматически, если реплика упала и
+/// соединение закрылось, — тогда задачу подхватывает следующая реплика.
+/// Без PostgreSQL выбор лидера выключен и задачи выполняются без блокировок
+pub struct JobLocks {
+    postgres: Option<Arc<PostgresConnection>>,
+    enabled: bool,
+}
+
+/// Взятая блокировка; соединение нужно держать до конца задачи
+pub struct JobLockGuard {
+    connection: PoolConnection<Postgres>,
+    job: String,
+}
+
+impl JobLocks {
+    pub fn new(postgres: Option<Arc<PostgresConnection>>, enabled: bool) -> Self {
+        Self {
+            enabled: enabled && postgres.is_some(),
+            postgres,
+        }
+    }
+
+    /// Пытается взять блокировку без ожидания; None — её держит другая реплика
+    pub async fn try_acquire(&self, job: &str) -> Result<Option<JobLockGuard>, sqlx::Error> {
+        let Some(postgres) = &self.postgres else {
+            return Err(sqlx::Error::PoolClosed);
+        };
+        let mut connection = postgres.get_pool().acquire().await?;
+        let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock(hashtext($1))")
+            .bind(format!("{}{}", LOCK_PREFIX, job))
+            .fetch_one(&mut *connection)
+            .await?;
+
+        if !acquired {
+            return Ok(None);
+        }
+
+        // If the guard is dropped without release (e.g. the task panicked), closing the
+        // session drops the lock instead of leaking it into a pooled connection
+        connection.close_on_drop();
+        debug!("Acquired job lock for {}", job);
+        Ok(Some(JobLockGuard {
+            connection,
+            job: job.to_string(),
+        }))
+    }
+
+    /// Выполняет `task`, только если эта реплика взяла блокировку задачи
+    ///
+    /// Возвращает None, если задачу выполняет другая реплика или блокировку
+    /// не удалось проверить (в этом случае безопаснее пропустить запуск)
+    pub async fn run_exclusive<F: Future>(&self, job: &str, task: F) -> Option<F::Output> {
+        if !self.enabled {
+            return Some(task.await);
+        }
+
+        let guard = match self.try_acquire(job).await {
+            Ok(Some(guard)) => guard,
+            Ok(None) => {
+                info!("{} is running on another replica, skipping", job);
+                return None;
+            }
+            Err(e) => {
+                error!(
+                    "Failed to acquire job lock for {}, skipping run: {}",
+                    job, e
+                );
+                return None;
+            }
+        };
+
+        let output = task.await;
+        guard.release().await;
+        Some(output)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl JobLockGuard {
+    pub async fn release(mut self) {
+        let result = sqlx::query("SELECT pg_advisory_unlock(hashtext($1))")
+            .bind(format!("{}{}", LOCK_PREFIX, self.job))
+            .execute(&mut *self.connection)
+            .await;
+
+        // The session is closed on drop anyway, so a failed unlock only delays the release
+        match result {
+            Ok(_) => debug!("Released job lock for {}", self.job),
+            Err(e) => error!("Failed to release job lock for {}: {}", self.job, e),
+        }
+    }
+}
@@ -1,4 +1,22 @@
+pub mod api_keys;
+pub mod archive;
 pub mod candles;
+pub mod exclusions;
+pub mod freshness;
+pub mod job_ledger;
+pub mod job_lock;
+pub mod orderbook;
+pub mod reconciliation;
+pub mod retention;
+pub mod rollup;
+pub mod scheduling;
 pub mod shares;
+pub mod source_comparison;
+pub mod sources;
+pub mod tinkoff_channel;
+pub mod tinkoff_mock;
+pub mod tinkoff_tokens;
+pub mod tinkoff_traffic;
 
 pub mod tinkoff_client_grpc;
+pub mod webhook_notifier;
@@ -0,0 +1,368 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use tokio::sync::{Mutex, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    AppState,
+    db::clickhouse::models::orderbook::DbOrderBookSnapshot,
+    generate::tinkoff_public_invest_api_contract_v1::{
+        MarketDataRequest, Order, OrderBook, OrderBookInstrument, SubscribeOrderBookRequest,
+        SubscriptionAction, SubscriptionStatus, market_data_request, market_data_response,
+    },
+    services::shares::models::quotation::extract_quotation,
+};
+
+/// Задержка перед повторным подключением к стриму после ошибки
+const RECONNECT_DELAY_SECONDS: u64 = 5;
+
+/// Имя блокировки записи стаканов между репликами
+const ORDERBOOK_JOB: &str = "orderbook_recorder";
+
+/// Последний полученный стакан по каждому инструменту
+type LatestBooks = Arc<Mutex<HashMap<String, OrderBook>>>;
+
+/// Добавляет срез в буфер; если буфер полон, отбрасывает самые старые срезы
+///
+/// Возвращает число отброшенных срезов
+fn push_capped(
+    buffer: &mut VecDeque<DbOrderBookSnapshot>,
+    snapshot: DbOrderBookSnapshot,
+    limit: usize,
+) -> usize {
+    let mut dropped = 0;
+    while buffer.len() >= limit.max(1) {
+        buffer.pop_front();
+        dropped += 1;
+    }
+    buffer.push_back(snapshot);
+    dropped
+}
+
+/// Записывает срезы стаканов из `market_data_stream` в ClickHouse
+///
+/// Стрим держит в памяти последний стакан по каждому инструменту,
+/// раз в `sample_interval_ms` снимается срез, раз в `flush_interval_seconds`
+/// накопленные срезы записываются в таблицу tinkoff_orderbooks
+pub struct RecorderOrderBook {
+    app_state: Arc<AppState>,
+}
+
+impl RecorderOrderBook {
+    pub fn new(app_state: Arc<AppState>) -> Self {
+        RecorderOrderBook { app_state }
+    }
+
+    /// Start the recorder with proper configuration checks
+    pub async fn start(&self) {
+        let config = &self.app_state.settings.app_config.orderbook_recorder;
+
+        if !config.enabled {
+            info!("Order book recorder is disabled in configuration");
+            return;
+        }
+
+        if config.instruments.is_empty() {
+            warn!("Order book recorder is enabled but no instruments are configured");
+            return;
+        }
+
+        if self.app_state.clickhouse_service.is_none() {
+            warn!("Order book recorder needs ClickHouse, which is not connected; not starting");
+            return;
+        }
+
+        info!(
+            "Starting order book recorder: {} instruments, depth {}, sampling every {} ms",
+            config.instruments.len(),
+            config.depth,
+            config.sample_interval_ms
+        );
+
+        let latest_books: LatestBooks = Arc::new(Mutex::new(HashMap::new()));
+
+        // Stream task: keeps the subscription alive and reconnects on errors
+        let app_state = self.app_state.clone();
+        let books = latest_books.clone();
+        let shutdown = self.app_state.shutdown.clone();
+        self.app_state.tasks.spawn(async move {
+            loop {
+                // With leader election only the replica holding the lock subscribes,
+                // the others stay on standby and retry after the reconnect delay
+                let mut lock = None;
+                if app_state.job_locks.is_enabled() {
+                    match app_state.job_locks.try_acquire(ORDERBOOK_JOB).await {
+                        Ok(Some(acquired)) => lock = Some(acquired),
+                        Ok(None) => debug!("Order book recorder is active on another replica"),
+                        Err(e) => error!("Order book recorder: failed to acquire job lock: {}", e),
+                    }
+                }
+
+                if !app_state.job_locks.is_enabled() || lock.is_some() {
+                    tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        result = Self::run_stream(&app_state, &books) => match result {
+                            Ok(()) => warn!("Order book stream closed by server, reconnecting"),
+                            Err(e) => error!("Order book stream failed: {}", e),
+                        },
+                    }
+                }
+                if let Some(lock) = lock {
+                    lock.release().await;
+                }
+
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = tokio::time::sleep(Duration::from_secs(RECONNECT_DELAY_SECONDS)) => {}
+                }
+            }
+            info!("Order book stream closed");
+        });
+
+        // Sampling task: snapshots the latest books and flushes them to ClickHouse
+        let app_state = self.app_state.clone();
+        let shutdown = self.app_state.shutdown.clone();
+        self.app_state.tasks.spawn(async move {
+            let config = &app_state.settings.app_config.orderbook_recorder;
+            let mut sample_interval =
+                tokio::time::interval(Duration::from_millis(config.sample_interval_ms));
+            let mut flush_interval =
+                tokio::time::interval(Duration::from_secs(config.flush_interval_seconds));
+
+            let mut buffer: VecDeque<DbOrderBookSnapshot> = VecDeque::new();
+            let mut dropped = 0;
+            // Время последнего записанного стакана, чтобы не писать один и тот же стакан дважды
+            let mut last_sampled: HashMap<String, i64> = HashMap::new();
+
+            loop {
+                tokio::select! {
+                    _ = sample_interval.tick() => {
+                        let books = latest_books.lock().await;
+                        for (uid, book) in books.iter() {
+                            let snapshot = Self::to_snapshot(book);
+                            if last_sampled.get(uid) == Some(&snapshot.time_ms) {
+                                continue;
+                            }
+                            last_sampled.insert(uid.clone(), snapshot.time_ms);
+                            dropped +=
+                                push_capped(&mut buffer, snapshot, config.max_buffered_snapshots);
+                        }
+                    }
+                    _ = flush_interval.tick() => {
+                        if dropped > 0 {
+                            warn!(
+                                "Order book recorder: buffer is full, dropped {} oldest snapshots",
+                                dropped
+                            );
+                            dropped = 0;
+                        }
+                        Self::flush(&app_state, &mut buffer).await;
+                    }
+                    _ = shutdown.cancelled() => {
+                        Self::flush(&app_state, &mut buffer).await;
+                        info!("Order book recorder stopped");
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Записывает накопленные срезы; при ошибке буфер сохраняется до следующей попытки
+    async fn flush(app_state: &Arc<AppState>, buffer: &mut VecDeque<DbOrderBookSnapshot>) {
+        if buffer.is_empty() {
+            return;
+        }
+        let Some(clickhouse_service) = &app_state.clickhouse_service else {
+            return;
+        };
+        match clickhouse_service
+            .repository_orderbook
+            .insert_snapshots(buffer.make_contiguous())
+            .await
+        {
+            Ok(count) => {
+                debug!("Order book recorder: flushed {} snapshots", count);
+                buffer.clear();
+            }
+            Err(e) => {
+                // Keep the buffer and retry on the next flush
+                error!("Order book recorder: failed to flush snapshots: {}", e);
+            }
+        }
+    }
+
+    /// Подписывается на стаканы и обновляет `latest_books`, пока стрим открыт
+    async fn run_stream(
+        app_state: &Arc<AppState>,
+        latest_books: &LatestBooks,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let config = &app_state.settings.app_config.orderbook_recorder;
+
+        let instruments = config
+            .instruments
+            .iter()
+            .map(|uid| OrderBookInstrument {
+                depth: config.depth,
+                instrument_id: uid.clone(),
+                ..Default::default()
+            })
+            .collect();
+
+        let subscribe = MarketDataRequest {
+            payload: Some(market_data_request::Payload::SubscribeOrderBookRequest(
+                SubscribeOrderBookRequest {
+                    subscription_action: SubscriptionAction::Subscribe as i32,
+                    instruments,
+                },
+            )),
+        };
+
+        // The sender must stay alive for as long as we read the stream,
+        // otherwise the server treats the subscription as finished
+        let (sender, receiver) = mpsc::channel::<MarketDataRequest>(1);
+        sender.send(subscribe).await?;
+
+        let request = app_state
+            .grpc_tinkoff
+            .create_request(ReceiverStream::new(receiver))?;
+        let mut stream_client = app_state.grpc_tinkoff.market_data_stream.clone();
+        let mut stream = stream_client
+            .market_data_stream(request)
+            .await?
+            .into_inner();
+
+        info!("Order book stream connected");
+
+        while let Some(response) = stream.message().await? {
+            match response.payload {
+                Some(market_data_response::Payload::Orderbook(book)) => {
+                    latest_books
+                        .lock()
+                        .await
+                        .insert(book.instrument_uid.clone(), book);
+                }
+                Some(market_data_response::Payload::SubscribeOrderBookResponse(result)) => {
+                    for subscription in result.order_book_subscriptions {
+                        if subscription.subscription_status == SubscriptionStatus::Success as i32 {
+                            info!(
+                                "Subscribed to order book {} (depth {})",
+                                subscription.instrument_uid, subscription.depth
+                            );
+                        } else {
+                            error!(
+                                "Order book subscription failed for {}: status {}",
+                                subscription.instrument_uid, subscription.subscription_status
+                            );
+                        }
+                    }
+                }
+                Some(market_data_response::Payload::Ping(_)) => {
+                    debug!("Order book stream: ping");
+                }
+                _ => {}
+            }
+        }
+
+        drop(sender);
+        Ok(())
+    }
+
+    fn to_snapshot(book: &OrderBook) -> DbOrderBookSnapshot {
+        let time_ms = match &book.time {
+            Some(ts) => ts.seconds * 1000 + i64::from(ts.nanos) / 1_000_000,
+            None => chrono::Utc::now().timestamp_millis(),
+        };
+
+        fn split(orders: &[Order]) -> (Vec<i64>, Vec<i32>, Vec<i64>) {
+            let mut units = Vec::with_capacity(orders.len());
+            let mut nanos = Vec::with_capacity(orders.len());
+            let mut quantities = Vec::with_capacity(orders.len());
+            for order in orders {
+                let (u, n) = extract_quotation(&order.price);
+                units.push(u);
+                nanos.push(n);
+                quantities.push(order.quantity);
+            }
+            (units, nanos, quantities)
+        }
+
+        let (bids_price_units, bids_price_nano, bids_quantity) = split(&book.bids);
+        let (asks_price_units, asks_price_nano, asks_quantity) = split(&book.asks);
+
+        DbOrderBookSnapshot {
+            instrument_uid: book.instrument_uid.clone(),
+            time_ms,
+            depth: book.depth,
+            is_consistent: book.is_consistent,
+            bids_price_units,
+            bids_price_nano,
+            bids_quantity,
+            asks_price_units,
+            asks_price_nano,
+            asks_quantity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::tinkoff_public_invest_api_contract_v1::Quotation;
+
+    fn order(units: i64, nano: i32, quantity: i64) -> Order {
+        Order {
+            price: Some(Quotation { units, nano }),
+            quantity,
+        }
+    }
+
+    fn book(uid: &str, seconds: i64) -> OrderBook {
+        OrderBook {
+            instrument_uid: uid.to_string(),
+            depth: 2,
+            is_consistent: true,
+            bids: vec![order(100, 500_000_000, 3), order(100, 0, 7)],
+            asks: vec![order(101, 250_000_000, 2)],
+            time: Some(prost_types::Timestamp {
+                seconds,
+                nanos: 123_000_000,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn builds_snapshot_from_book() {
+        let snapshot = RecorderOrderBook::to_snapshot(&book("uid-1", 1_700_000_000));
+
+        assert_eq!(snapshot.instrument_uid, "uid-1");
+        assert_eq!(snapshot.time_ms, 1_700_000_000_123);
+        assert_eq!((snapshot.depth, snapshot.is_consistent), (2, true));
+        assert_eq!(snapshot.bids_price_units, vec![100, 100]);
+        assert_eq!(snapshot.bids_price_nano, vec![500_000_000, 0]);
+        assert_eq!(snapshot.bids_quantity, vec![3, 7]);
+        assert_eq!(snapshot.asks_price_units, vec![101]);
+        assert_eq!(snapshot.asks_price_nano, vec![250_000_000]);
+        assert_eq!(snapshot.asks_quantity, vec![2]);
+    }
+
+    #[test]
+    fn drops_oldest_snapshots_when_buffer_is_full() {
+        let mut buffer = VecDeque::new();
+        let mut dropped = 0;
+        for seconds in 0..5 {
+            let snapshot = RecorderOrderBook::to_snapshot(&book("uid-1", seconds));
+            dropped += push_capped(&mut buffer, snapshot, 3);
+        }
+
+        assert_eq!(dropped, 2);
+        let times: Vec<i64> = buffer.iter().map(|s| s.time_ms / 1000).collect();
+        assert_eq!(times, vec![2, 3, 4]);
+    }
+}
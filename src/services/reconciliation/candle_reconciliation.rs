@@ -0,0 +1,292 @@
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+use crate::app_state::models::AppState;
+use crate::db::clickhouse::clickhouse_service::ClickhouseService;
+use crate::db::clickhouse::models::reconciliation_mismatch::DbReconciliationMismatch;
+use crate::db::postgres::models::refetch::DbRefetchRequest;
+use crate::db::postgres::postgres_service::PostgresService;
+use crate::env_config::models::reconciliation::ReconciliationConfig;
+use crate::env_config::models::retention::CandleResolution;
+use crate::generate::tinkoff_public_invest_api_contract_v1::HistoricCandle;
+use crate::services::candles::ohlcv_bar::OhlcvBar;
+
+pub type ReconciliationError = Box<dyn Error + Send + Sync>;
+
+const DAY_SECONDS: i64 = 86_400;
+
+/// Итог сверки всех инструментов
+#[derive(Debug, Default)]
+pub struct ReconciliationReport {
+    pub instruments_checked: usize,
+    pub instruments_failed: usize,
+    pub days_checked: usize,
+    pub mismatches: usize,
+    pub days_queued: usize,
+}
+
+/// Поля, по которым сутки расходятся; пусто — сутки сходятся
+///
+/// missing — дневная свеча есть, минут нет; unexpected — минуты есть, дневной свечи нет
+fn compare_day(
+    official: Option<OhlcvBar>,
+    stored: Option<OhlcvBar>,
+    config: &ReconciliationConfig,
+) -> Vec<&'static str> {
+    match (official, stored) {
+        (None, None) => Vec::new(),
+        // A day without trades has a zero-volume candle and no minutes
+        (Some(official), None) if official.volume == 0 => Vec::new(),
+        (Some(_), None) => vec!["missing"],
+        (None, Some(_)) => vec!["unexpected"],
+        (Some(official), Some(stored)) => {
+            stored.diverging_fields(&official, config.price_tolerance, config.volume_tolerance)
+        }
+    }
+}
+
+/// Сверка сохранённых минутных свечей с официальными дневными свечами источника
+pub struct CandleReconciliation {
+    app_state: Arc<AppState>,
+    clickhouse_service: Arc<ClickhouseService>,
+    postgres_service: Arc<PostgresService>,
+}
+
+impl CandleReconciliation {
+    /// None, если ClickHouse или PostgreSQL не подключены
+    pub fn new(app_state: Arc<AppState>) -> Option<Self> {
+        Some(Self {
+            clickhouse_service: app_state.clickhouse_service.clone()?,
+            postgres_service: app_state.postgres_service.clone()?,
+            app_state,
+        })
+    }
+
+    /// Проверяет `lookback_days` завершённых суток UTC у каждого активного инструмента
+    pub async fn run(&self, run_id: &str, now: DateTime<Utc>) -> ReconciliationReport {
+        let mut report = ReconciliationReport::default();
+        let instruments = match self
+            .postgres_service
+            .repository_watchlist
+            .get_active()
+            .await
+        {
+            Ok(instruments) => instruments,
+            Err(e) => {
+                warn!("Reconciliation: failed to read the watchlist: {}", e);
+                report.instruments_failed = 1;
+                return report;
+            }
+        };
+
+        let config = &self.app_state.settings.app_config.reconciliation;
+        let today = now.timestamp() - now.timestamp().rem_euclid(DAY_SECONDS);
+        for instrument in &instruments {
+            if self.app_state.shutdown.is_cancelled() {
+                info!("Shutdown requested, stopping reconciliation");
+                break;
+            }
+
+            // Only days the loader has already reached can be compared
+            let day = |time: i64| CandleResolution::Day.bucket_start(time);
+            let from = (today - config.lookback_days * DAY_SECONDS)
+                .max(day(instrument.first_1min_candle_date));
+            let loaded_until = day(instrument.last_1min_candle_date) + DAY_SECONDS;
+            let to = today.min(loaded_until);
+            if instrument.last_1min_candle_date == 0 || from >= to {
+                debug!("Nothing to reconcile for {}", instrument.uid);
+                continue;
+            }
+
+            match self
+                .reconcile_instrument(run_id, &instrument.uid, from, to, now)
+                .await
+            {
+                Ok((days, mismatches, queued)) => {
+                    report.instruments_checked += 1;
+                    report.days_checked += days;
+                    report.mismatches += mismatches;
+                    report.days_queued += queued;
+                }
+                Err(e) => {
+                    warn!("Failed to reconcile {}: {}", instrument.uid, e);
+                    report.instruments_failed += 1;
+                }
+            }
+
+            let delay_ms = self
+                .app_state
+                .settings
+                .app_config
+                .candles_scheduler
+                .request_delay_ms;
+            if delay_ms > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+            }
+        }
+
+        info!(
+            "Reconciliation finished: {} instruments, {} days, {} mismatches, {} days queued",
+            report.instruments_checked, report.days_checked, report.mismatches, report.days_queued
+        );
+        report
+    }
+
+    /// Возвращает число проверенных суток, расхождений и суток, поставленных в очередь
+    async fn reconcile_instrument(
+        &self,
+        run_id: &str,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+        now: DateTime<Utc>,
+    ) -> Result<(usize, usize, usize), ReconciliationError> {
+        let config = &self.app_state.settings.app_config.reconciliation;
+
+        let official: HashMap<i64, OhlcvBar> = self
+            .fetch_daily_candles(instrument_uid, from, to)
+            .await?
+            .iter()
+            .filter(|candle| candle.is_complete)
+            .filter_map(|candle| {
+                let time = candle.time.as_ref()?.seconds;
+                Some((
+                    CandleResolution::Day.bucket_start(time),
+                    OhlcvBar::from_historic(candle),
+                ))
+            })
+            .collect();
+        let stored: HashMap<i64, OhlcvBar> = self
+            .app_state
+            .candle_repository
+            .get_candles_at(instrument_uid, from, to - 1, CandleResolution::Day)
+            .await?
+            .iter()
+            .map(|candle| (candle.time, OhlcvBar::from_stored(candle)))
+            .collect();
+
+        let days: BTreeSet<i64> = official.keys().chain(stored.keys()).copied().collect();
+        let mut mismatches = Vec::new();
+        let mut refetch = Vec::new();
+        for &day in &days {
+            let fields = compare_day(
+                official.get(&day).copied(),
+                stored.get(&day).copied(),
+                config,
+            );
+            if fields.is_empty() {
+                continue;
+            }
+
+            // Refetching minutes cannot remove candles the exchange never had
+            let queue = config.refetch && fields != ["unexpected"];
+            let fields = fields.join(",");
+            if queue {
+                refetch.push(DbRefetchRequest {
+                    instrument_uid: instrument_uid.to_string(),
+                    day,
+                    reason: format!("reconciliation: {}", fields),
+                });
+            }
+
+            let official = official.get(&day);
+            let stored = stored.get(&day);
+            mismatches.push(DbReconciliationMismatch {
+                run_id: run_id.to_string(),
+                instrument_uid: instrument_uid.to_string(),
+                day,
+                fields,
+                official_open: official.map(|bar| bar.open),
+                official_high: official.map(|bar| bar.high),
+                official_low: official.map(|bar| bar.low),
+                official_close: official.map(|bar| bar.close),
+                official_volume: official.map(|bar| bar.volume),
+                stored_open: stored.map(|bar| bar.open),
+                stored_high: stored.map(|bar| bar.high),
+                stored_low: stored.map(|bar| bar.low),
+                stored_close: stored.map(|bar| bar.close),
+                stored_volume: stored.map(|bar| bar.volume),
+                refetch_queued: queue,
+                detected_at: now.timestamp(),
+            });
+        }
+
+        if !mismatches.is_empty() {
+            warn!(
+                "{} of {} days of {} differ from official daily candles",
+                mismatches.len(),
+                days.len(),
+                instrument_uid
+            );
+        }
+        self.clickhouse_service
+            .repository_reconciliation
+            .insert_mismatches(&mismatches)
+            .await?;
+        self.postgres_service
+            .repository_refetch
+            .enqueue(&refetch)
+            .await?;
+
+        Ok((days.len(), mismatches.len(), refetch.len()))
+    }
+
+    /// Официальные дневные свечи за период [from, to)
+    async fn fetch_daily_candles(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<HistoricCandle>, ReconciliationError> {
+        Ok(self
+            .app_state
+            .candle_source
+            .get_candles(instrument_uid, from, to, CandleResolution::Day)
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env_config::models::schedule::{CatchUpPolicy, ScheduleConfig};
+
+    fn bar(close: f64, volume: i64) -> OhlcvBar {
+        OhlcvBar {
+            open: 100.0,
+            high: 110.0,
+            low: 90.0,
+            close,
+            volume,
+        }
+    }
+
+    #[test]
+    fn compares_days_within_tolerance() {
+        let config = ReconciliationConfig {
+            enabled: true,
+            schedule: ScheduleConfig {
+                cron: None,
+                run_times: vec!["05:00:00".to_string()],
+                catch_up: CatchUpPolicy::Skip,
+            },
+            lookback_days: 7,
+            price_tolerance: 0.001,
+            volume_tolerance: 0.01,
+            refetch: true,
+        };
+
+        let official = Some(bar(105.0, 10_000));
+        assert!(compare_day(official, Some(bar(105.05, 9_950)), &config).is_empty());
+        assert_eq!(
+            compare_day(official, Some(bar(106.0, 9_000)), &config),
+            vec!["close", "volume"]
+        );
+        assert_eq!(compare_day(official, None, &config), vec!["missing"]);
+        assert!(compare_day(Some(bar(105.0, 0)), None, &config).is_empty());
+        assert_eq!(compare_day(None, official, &config), vec!["unexpected"]);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod candle_reconciliation;
+pub mod reconciliation_scheduler;
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::candle_reconciliation::CandleReconciliation;
+use crate::{
+    AppState,
+    services::{job_ledger::RECONCILIATION_JOB, scheduling::scheduled_job::ScheduledJob},
+};
+
+/// Ежедневная сверка минутной истории с дневными свечами Tinkoff
+pub struct ReconciliationScheduler {
+    app_state: Arc<AppState>,
+}
+
+impl ReconciliationScheduler {
+    pub fn new(app_state: Arc<AppState>) -> Self {
+        Self { app_state }
+    }
+
+    pub async fn start(&self) {
+        let config = &self.app_state.settings.app_config.reconciliation;
+        if !config.enabled {
+            info!("Candle reconciliation is disabled in configuration");
+            return;
+        }
+        if self.app_state.clickhouse_service.is_none() || self.app_state.postgres_service.is_none()
+        {
+            warn!("Candle reconciliation needs ClickHouse and PostgreSQL; not starting");
+            return;
+        }
+
+        info!(
+            "Starting reconciliation scheduler: {} days back, refetch {}",
+            config.lookback_days, config.refetch
+        );
+
+        ScheduledJob::new(RECONCILIATION_JOB, &config.schedule).spawn(
+            self.app_state.clone(),
+            |_| true,
+            |app_state| async move {
+                let Some(reconciliation) = CandleReconciliation::new(app_state.clone()) else {
+                    return;
+                };
+                let ledger = app_state.job_ledger();
+
+                let mut run = ledger.start(RECONCILIATION_JOB).await;
+                let report = reconciliation.run(&run.run_id, chrono::Utc::now()).await;
+
+                // The ledger counts checked days as received candles and mismatches as inserted rows
+                run.instruments_total =
+                    (report.instruments_checked + report.instruments_failed) as i64;
+                run.instruments_failed = report.instruments_failed as i64;
+                run.candles_received = report.days_checked as i64;
+                run.candles_inserted = report.mismatches as i64;
+                let error = (report.instruments_failed > 0).then(|| {
+                    format!(
+                        "{} instruments could not be reconciled",
+                        report.instruments_failed
+                    )
+                });
+                if app_state.shutdown.is_cancelled() {
+                    ledger.cancel(&mut run).await;
+                } else {
+                    ledger.finish(&mut run, error).await;
+                }
+            },
+        );
+    }
+}
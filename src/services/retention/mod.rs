@@ -0,0 +1,2 @@
+pub mod retention_maintenance;
+pub mod retention_scheduler;
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::db::clickhouse::repository::repository_retention::{
+    DbRetentionPreview, InstrumentGroup, RetentionRepository,
+};
+use crate::env_config::models::retention::{CandleResolution, RetentionConfig, RetentionPolicy};
+
+/// Что сделает политика хранения при запуске в момент `now`
+#[derive(Debug, Serialize)]
+pub struct RetentionPlanItem {
+    pub resolution: CandleResolution,
+    pub instruments: String,
+    pub keep_days: i64,
+    pub cutoff: i64, // Удаляются строки раньше этого момента, секунды
+    pub downsample_to: Option<CandleResolution>,
+    pub rollups_to_create: u64, // Свечи downsample_to, которые появятся перед удалением
+    pub to_drop: DbRetentionPreview,
+}
+
+/// Итог применения политик
+#[derive(Debug, Default)]
+pub struct RetentionReport {
+    pub policies_applied: usize,
+    pub policies_failed: usize,
+    pub rows_dropped: u64,
+    pub rollups_created: u64,
+}
+
+/// Применяет политики хранения из секции [retention] к таблицам свечей ClickHouse
+pub struct RetentionMaintenance {
+    repository: Arc<RetentionRepository>,
+}
+
+/// Граница хранения: начало суток UTC, чтобы свёртка не резала часовые и дневные свечи
+pub fn cutoff(now: DateTime<Utc>, keep_days: i64) -> i64 {
+    const DAY_SECONDS: i64 = 86_400;
+    let edge = (now - Duration::days(keep_days)).timestamp();
+    edge - edge.rem_euclid(DAY_SECONDS)
+}
+
+fn instrument_group(config: &RetentionConfig, policy: &RetentionPolicy) -> InstrumentGroup {
+    if policy.instruments.is_empty() {
+        InstrumentGroup::AllExcept(config.explicit_instruments(policy.resolution))
+    } else {
+        InstrumentGroup::Only(policy.instruments.clone())
+    }
+}
+
+impl RetentionMaintenance {
+    pub fn new(repository: Arc<RetentionRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// Отчёт о том, что будет свёрнуто и удалено, без изменений в данных
+    pub async fn plan(
+        &self,
+        config: &RetentionConfig,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<RetentionPlanItem>, clickhouse::error::Error> {
+        let mut items = Vec::new();
+        // Policies without keep_days keep their candles forever
+        for policy in &config.policies {
+            let Some(keep_days) = policy.keep_days else {
+                continue;
+            };
+            let cutoff = cutoff(now, keep_days);
+            let group = instrument_group(config, policy);
+
+            let rollups_to_create = match policy.downsample_to {
+                Some(target) => {
+                    self.repository
+                        .count_missing_rollups(target, cutoff, &group)
+                        .await?
+                }
+                None => 0,
+            };
+            let to_drop = self
+                .repository
+                .preview(policy.resolution, cutoff, &group)
+                .await?;
+
+            items.push(RetentionPlanItem {
+                resolution: policy.resolution,
+                instruments: policy.describe_instruments(),
+                keep_days,
+                cutoff,
+                downsample_to: policy.downsample_to,
+                rollups_to_create,
+                to_drop,
+            });
+        }
+        Ok(items)
+    }
+
+    /// Сворачивает и удаляет старые свечи; ошибка одной политики не останавливает остальные
+    pub async fn apply(&self, config: &RetentionConfig, now: DateTime<Utc>) -> RetentionReport {
+        let mut report = RetentionReport::default();
+        for policy in &config.policies {
+            let Some(keep_days) = policy.keep_days else {
+                continue;
+            };
+            let cutoff = cutoff(now, keep_days);
+            let group = instrument_group(config, policy);
+
+            match self.apply_policy(policy, cutoff, &group).await {
+                Ok((rows_dropped, rollups_created)) => {
+                    info!(
+                        "Retention {} ({}): dropped {} rows before {}, created {} rollups",
+                        policy.resolution.as_str(),
+                        policy.describe_instruments(),
+                        rows_dropped,
+                        cutoff,
+                        rollups_created
+                    );
+                    report.policies_applied += 1;
+                    report.rows_dropped += rows_dropped;
+                    report.rollups_created += rollups_created;
+                }
+                Err(e) => {
+                    error!(
+                        "Retention {} ({}) failed: {}",
+                        policy.resolution.as_str(),
+                        policy.describe_instruments(),
+                        e
+                    );
+                    report.policies_failed += 1;
+                }
+            }
+        }
+        report
+    }
+
+    async fn apply_policy(
+        &self,
+        policy: &RetentionPolicy,
+        cutoff: i64,
+        group: &InstrumentGroup,
+    ) -> Result<(u64, u64), clickhouse::error::Error> {
+        // Minutes are dropped only after their rollup is written
+        let mut rollups_created = 0;
+        if let Some(target) = policy.downsample_to {
+            rollups_created = self
+                .repository
+                .count_missing_rollups(target, cutoff, group)
+                .await?;
+            self.repository.downsample(target, cutoff, group).await?;
+        }
+
+        let preview = self
+            .repository
+            .preview(policy.resolution, cutoff, group)
+            .await?;
+        if preview.rows > 0 {
+            self.repository
+                .delete_before(policy.resolution, cutoff, group)
+                .await?;
+        }
+        Ok((preview.rows, rollups_created))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cutoff_is_aligned_to_day() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 10, 15, 42, 7).unwrap();
+        let expected = Utc.with_ymd_and_hms(2025, 3, 8, 0, 0, 0).unwrap();
+        assert_eq!(cutoff(now, 2), expected.timestamp());
+    }
+}
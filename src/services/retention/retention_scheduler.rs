@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::retention_maintenance::RetentionMaintenance;
+use crate::{
+    AppState,
+    services::{job_ledger::RETENTION_JOB, scheduling::scheduled_job::ScheduledJob},
+};
+
+/// Ежедневное применение политик хранения свечей
+pub struct RetentionScheduler {
+    app_state: Arc<AppState>,
+}
+
+impl RetentionScheduler {
+    pub fn new(app_state: Arc<AppState>) -> Self {
+        Self { app_state }
+    }
+
+    pub async fn start(&self) {
+        let config = &self.app_state.settings.app_config.retention;
+        if !config.enabled {
+            info!("Candle retention is disabled in configuration");
+            return;
+        }
+        if self.app_state.clickhouse_service.is_none() {
+            warn!("Candle retention needs ClickHouse, which is not connected; not starting");
+            return;
+        }
+
+        info!(
+            "Starting retention scheduler with {} policies",
+            config.policies.len()
+        );
+
+        ScheduledJob::new(RETENTION_JOB, &config.schedule).spawn(
+            self.app_state.clone(),
+            |_| true,
+            |app_state| async move {
+                let Some(clickhouse_service) = &app_state.clickhouse_service else {
+                    return;
+                };
+                let maintenance =
+                    RetentionMaintenance::new(clickhouse_service.repository_retention.clone());
+                let ledger = app_state.job_ledger();
+
+                let mut run = ledger.start(RETENTION_JOB).await;
+                let report = maintenance
+                    .apply(&app_state.settings.app_config.retention, chrono::Utc::now())
+                    .await;
+
+                // The ledger counts policies as "instruments" and dropped rows as received candles
+                run.instruments_total = (report.policies_applied + report.policies_failed) as i64;
+                run.instruments_failed = report.policies_failed as i64;
+                run.candles_received = report.rows_dropped as i64;
+                run.candles_inserted = report.rollups_created as i64;
+                let error = (report.policies_failed > 0)
+                    .then(|| format!("{} retention policies failed", report.policies_failed));
+                ledger.finish(&mut run, error).await;
+            },
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::db::clickhouse::repository::repository_retention::InstrumentGroup;
+use crate::db::clickhouse::repository::repository_rollup::{DbRollupMismatch, RollupRepository};
+use crate::env_config::models::retention::CandleResolution;
+
+/// Свёртки, которые ведут материализованные представления
+pub const ROLLUP_RESOLUTIONS: [CandleResolution; 3] = [
+    CandleResolution::FiveMinutes,
+    CandleResolution::Hour,
+    CandleResolution::Day,
+];
+
+const DAY_SECONDS: i64 = 86_400;
+// Пересчёт идёт кусками, чтобы INSERT ... SELECT не читал всю историю разом
+const BACKFILL_CHUNK_DAYS: i64 = 7;
+const MISMATCH_SAMPLE: u64 = 100;
+
+fn day_start(time: i64) -> i64 {
+    time - time.rem_euclid(DAY_SECONDS)
+}
+
+/// Итог заполнения свёрток
+#[derive(Debug, Default)]
+pub struct BackfillReport {
+    pub from: i64,
+    pub to: i64,
+    pub chunks: usize,
+}
+
+/// Сверка одной свёртки с минутными свечами
+#[derive(Debug, Serialize)]
+pub struct RollupCheck {
+    pub resolution: CandleResolution,
+    pub from: i64,
+    pub to: i64,
+    pub buckets: u64,
+    pub mismatches: u64,
+    pub sample: Vec<DbRollupMismatch>, // Первые расхождения; исправляются командой rollup-backfill
+}
+
+/// Заполнение и сверка свёрток 5min, 1hour и 1day
+pub struct RollupMaintenance {
+    repository: Arc<RollupRepository>,
+}
+
+impl RollupMaintenance {
+    pub fn new(repository: Arc<RollupRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// Пересчитывает свёртки за сутки [from, to) из минутных свечей
+    ///
+    /// Без границ берётся вся история до начала текущих суток: сегодняшние минуты
+    /// ещё загружаются, их ведут материализованные представления
+    pub async fn backfill(
+        &self,
+        from: Option<i64>,
+        to: Option<i64>,
+        instrument_uid: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<BackfillReport, clickhouse::error::Error> {
+        let group = match instrument_uid {
+            Some(uid) => InstrumentGroup::Only(vec![uid.to_string()]),
+            None => InstrumentGroup::AllExcept(Vec::new()),
+        };
+
+        let Some((first, _)) = self.repository.raw_range(&group).await? else {
+            info!("No 1min candles to roll up");
+            return Ok(BackfillReport::default());
+        };
+        let from = day_start(from.unwrap_or(first));
+        let to = day_start(to.unwrap_or_else(|| now.timestamp()));
+
+        let mut report = BackfillReport {
+            from,
+            to,
+            chunks: 0,
+        };
+        let mut chunk_start = from;
+        while chunk_start < to {
+            let chunk_end = (chunk_start + BACKFILL_CHUNK_DAYS * DAY_SECONDS).min(to);
+            for resolution in ROLLUP_RESOLUTIONS {
+                self.repository
+                    .rebuild(resolution, chunk_start, chunk_end, &group)
+                    .await?;
+            }
+            report.chunks += 1;
+            chunk_start = chunk_end;
+        }
+
+        info!(
+            "Rollups rebuilt for [{}, {}) in {} chunks",
+            report.from, report.to, report.chunks
+        );
+        Ok(report)
+    }
+
+    /// Сверяет свёртки с минутными свечами за период [from, to) по числу минут и объёму
+    pub async fn check(
+        &self,
+        resolutions: &[CandleResolution],
+        from: i64,
+        to: i64,
+        instrument_uid: Option<&str>,
+    ) -> Result<Vec<RollupCheck>, clickhouse::error::Error> {
+        let group = match instrument_uid {
+            Some(uid) => InstrumentGroup::Only(vec![uid.to_string()]),
+            None => InstrumentGroup::AllExcept(Vec::new()),
+        };
+
+        let mut checks = Vec::new();
+        for &resolution in resolutions {
+            // Partial buckets at the edges would always look inconsistent
+            let from = resolution.bucket_start(from);
+            let to = resolution.bucket_start(to);
+
+            let summary = self
+                .repository
+                .check_summary(resolution, from, to, &group)
+                .await?;
+            let sample = if summary.mismatches > 0 {
+                warn!(
+                    "{} of {} {} candles differ from 1min candles in [{}, {})",
+                    summary.mismatches,
+                    summary.buckets,
+                    resolution.as_str(),
+                    from,
+                    to
+                );
+                self.repository
+                    .find_mismatches(resolution, from, to, &group, MISMATCH_SAMPLE)
+                    .await?
+            } else {
+                Vec::new()
+            };
+
+            checks.push(RollupCheck {
+                resolution,
+                from,
+                to,
+                buckets: summary.buckets,
+                mismatches: summary.mismatches,
+                sample,
+            });
+        }
+        Ok(checks)
+    }
+}
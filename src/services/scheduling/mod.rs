@@ -0,0 +1,2 @@
+pub mod schedule_registry;
+pub mod scheduled_job;
@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+/// Состояние расписания одной задачи
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleStatus {
+    pub job: String,
+    pub schedule: String,
+    pub catch_up: String,
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_started: Option<DateTime<Utc>>,
+    pub last_finished: Option<DateTime<Utc>>,
+    pub running: bool,
+}
+
+/// Общий реестр расписаний: задачи обновляют его, API отдаёт снимок
+#[derive(Default)]
+pub struct ScheduleRegistry {
+    jobs: RwLock<BTreeMap<String, ScheduleStatus>>,
+}
+
+impl ScheduleRegistry {
+    pub async fn register(&self, job: &str, schedule: String, catch_up: String) {
+        self.jobs.write().await.insert(
+            job.to_string(),
+            ScheduleStatus {
+                job: job.to_string(),
+                schedule,
+                catch_up,
+                next_run: None,
+                last_started: None,
+                last_finished: None,
+                running: false,
+            },
+        );
+    }
+
+    async fn update(&self, job: &str, f: impl FnOnce(&mut ScheduleStatus)) {
+        if let Some(status) = self.jobs.write().await.get_mut(job) {
+            f(status);
+        }
+    }
+
+    pub async fn set_next_run(&self, job: &str, next_run: Option<DateTime<Utc>>) {
+        self.update(job, |s| s.next_run = next_run).await;
+    }
+
+    pub async fn mark_started(&self, job: &str, at: DateTime<Utc>) {
+        self.update(job, |s| {
+            s.running = true;
+            s.last_started = Some(at);
+            s.next_run = None;
+        })
+        .await;
+    }
+
+    pub async fn mark_finished(&self, job: &str, at: DateTime<Utc>) {
+        self.update(job, |s| {
+            s.running = false;
+            s.last_finished = Some(at);
+        })
+        .await;
+    }
+
+    pub async fn snapshot(&self) -> Vec<ScheduleStatus> {
+        self.jobs.read().await.values().cloned().collect()
+    }
+}
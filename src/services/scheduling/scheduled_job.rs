@@ -0,0 +1,190 @@
+use std::{future::Future, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    AppState,
+    env_config::models::{
+        app_config::AppConfig,
+        schedule::{CatchUpPolicy, JobSchedule, ScheduleConfig},
+    },
+};
+
+/// Задача, запускаемая по расписанию из конфигурации
+///
+/// Время следующего запуска публикуется в `AppState::schedules`. Пропущенные
+/// запуски (приложение было остановлено или предыдущий запуск затянулся)
+/// обрабатываются по `catch_up`: `skip` ждёт следующего времени по расписанию,
+/// `run_once` сразу выполняет один запуск вместо всех пропущенных
+pub struct ScheduledJob {
+    name: &'static str,
+    schedule: JobSchedule,
+    catch_up: CatchUpPolicy,
+}
+
+impl ScheduledJob {
+    pub fn new(name: &'static str, config: &ScheduleConfig) -> Self {
+        // The schedule has already been validated when the configuration was loaded
+        let schedule = JobSchedule::from_config(config)
+            .unwrap_or_else(|e| panic!("Invalid schedule for {}: {}", name, e));
+
+        Self {
+            name,
+            schedule,
+            catch_up: config.catch_up,
+        }
+    }
+
+    /// Время следующего запуска
+    ///
+    /// # Arguments
+    /// * `last_run` - Начало последнего запуска или пропущенного по окну времени слота
+    /// * `now` - Текущее время
+    pub fn plan_next(
+        &self,
+        last_run: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let missed = match (last_run, self.catch_up) {
+            (Some(last_run), CatchUpPolicy::RunOnce) => self
+                .schedule
+                .next_after(last_run)
+                .is_some_and(|due| due <= now),
+            _ => false,
+        };
+        if missed {
+            return Some(now);
+        }
+        self.schedule.next_after(now)
+    }
+
+    /// Запускает цикл задачи в трекере задач приложения
+    ///
+    /// # Arguments
+    /// * `is_allowed` - Проверка окна работы; слоты вне окна пропускаются
+    /// * `job` - Сам запуск; результат логируется внутри
+    pub fn spawn<F, Fut>(self, app_state: Arc<AppState>, is_allowed: fn(&AppConfig) -> bool, job: F)
+    where
+        F: Fn(Arc<AppState>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let tasks = app_state.tasks.clone();
+        tasks.spawn(async move {
+            let registry = &app_state.schedules;
+            let shutdown = app_state.shutdown.clone();
+            let catch_up = format!("{:?}", self.catch_up).to_lowercase();
+            registry
+                .register(self.name, self.schedule.describe(), catch_up)
+                .await;
+
+            // The ledger remembers the last run across restarts, which drives catch-up
+            // Without PostgreSQL there is no ledger, so the first slot is planned from now
+            let last_start = match &app_state.postgres_service {
+                Some(postgres) => {
+                    postgres
+                        .repository_job_run
+                        .get_last_run_start(self.name)
+                        .await
+                }
+                None => Ok(None),
+            };
+            let mut last_run = match last_start {
+                Ok(started_at) => started_at.and_then(|t| DateTime::from_timestamp(t, 0)),
+                Err(e) => {
+                    error!("{} scheduler: failed to read last run: {}", self.name, e);
+                    None
+                }
+            };
+
+            loop {
+                let now = Utc::now();
+                let Some(next) = self.plan_next(last_run, now) else {
+                    warn!("{} scheduler: schedule has no further runs", self.name);
+                    break;
+                };
+                if next <= now {
+                    info!("{} scheduler: catching up a missed run", self.name);
+                }
+                registry.set_next_run(self.name, Some(next)).await;
+                info!("{} scheduler: next run at {}", self.name, next);
+
+                let wait = (next - now).to_std().unwrap_or_default();
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = tokio::time::sleep(wait) => {}
+                }
+
+                if !is_allowed(&app_state.settings.app_config) {
+                    debug!(
+                        "{} scheduler: skipping run at {} - outside operation window",
+                        self.name, next
+                    );
+                    last_run = Some(next);
+                    continue;
+                }
+
+                let started = Utc::now();
+                registry.mark_started(self.name, started).await;
+                info!("{} scheduler: triggering update", self.name);
+
+                // With leader election the run is skipped if another replica holds the job lock
+                app_state
+                    .job_locks
+                    .run_exclusive(self.name, job(app_state.clone()))
+                    .await;
+
+                // The wall clock may lag the timer slightly; never count the slot twice
+                last_run = Some(started.max(next));
+                registry.mark_finished(self.name, Utc::now()).await;
+            }
+
+            registry.set_next_run(self.name, None).await;
+            info!("{} scheduler stopped", self.name);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn job(catch_up: CatchUpPolicy) -> ScheduledJob {
+        ScheduledJob::new(
+            "test",
+            &ScheduleConfig {
+                cron: None,
+                run_times: vec!["04:00:00".to_string()],
+                catch_up,
+            },
+        )
+    }
+
+    #[test]
+    fn test_catch_up_policy() {
+        let at = |d, h| Utc.with_ymd_and_hms(2025, 3, d, h, 0, 0).unwrap();
+        // The run on the 3rd at 04:00 was missed
+        let last_run = Some(at(2, 4));
+        let now = at(3, 10);
+
+        assert_eq!(
+            job(CatchUpPolicy::RunOnce).plan_next(last_run, now),
+            Some(now)
+        );
+        assert_eq!(
+            job(CatchUpPolicy::Skip).plan_next(last_run, now),
+            Some(at(4, 4))
+        );
+
+        // Nothing missed, nothing to catch up
+        assert_eq!(
+            job(CatchUpPolicy::RunOnce).plan_next(Some(at(3, 4)), now),
+            Some(at(4, 4))
+        );
+        assert_eq!(
+            job(CatchUpPolicy::RunOnce).plan_next(None, now),
+            Some(at(4, 4))
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::db::clickhouse::models::share_version::DbShareVersion;
+use crate::generate::tinkoff_public_invest_api_contract_v1::Share;
+
+/// Разница между сохранённым каталогом и свежим ответом `shares`
+#[derive(Debug, Default)]
+pub struct CatalogDiff {
+    /// Инструменты, которых не было в каталоге
+    pub listed: Vec<DbShareVersion>,
+    /// Инструменты с изменёнными атрибутами: (прежняя версия, новая версия)
+    pub changed: Vec<(DbShareVersion, DbShareVersion)>,
+    /// Инструменты, пропавшие из ответа API
+    pub removed: Vec<DbShareVersion>,
+}
+
+impl CatalogDiff {
+    /// Сравнивает актуальные версии каталога с ответом API
+    ///
+    /// # Arguments
+    /// * `current` - Открытые версии из tinkoff_shares_history
+    /// * `shares` - Инструменты из ответа API (уже без исключённых)
+    /// * `now` - Момент обновления в секундах, становится valid_from/valid_to
+    pub fn compute(current: &[DbShareVersion], shares: &[&Share], now: i64) -> Self {
+        let mut previous: HashMap<&str, &DbShareVersion> =
+            current.iter().map(|v| (v.uid.as_str(), v)).collect();
+        let mut diff = CatalogDiff::default();
+
+        for share in shares {
+            let fresh = DbShareVersion::from_share(share, now);
+            match previous.remove(share.uid.as_str()) {
+                None => diff.listed.push(fresh),
+                Some(old) if !old.same_attributes(&fresh) => {
+                    diff.changed.push((old.clone(), fresh))
+                }
+                Some(_) => {}
+            }
+        }
+
+        // Всё, что осталось в previous, отсутствует в ответе API
+        diff.removed = previous.into_values().cloned().collect();
+        diff.removed.sort_by(|a, b| a.uid.cmp(&b.uid));
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.listed.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    /// Строки для вставки в историю: закрытые прежние версии и новые открытые
+    pub fn history_rows(&self, now: i64) -> Vec<DbShareVersion> {
+        let close = |v: &DbShareVersion| DbShareVersion {
+            valid_to: Some(now),
+            ..v.clone()
+        };
+
+        let mut rows =
+            Vec::with_capacity(self.listed.len() + self.changed.len() * 2 + self.removed.len());
+        rows.extend(self.listed.iter().cloned());
+        for (old, new) in &self.changed {
+            rows.push(close(old));
+            rows.push(new.clone());
+        }
+        rows.extend(self.removed.iter().map(close));
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::tinkoff_public_invest_api_contract_v1::SecurityTradingStatus;
+
+    fn share(uid: &str, lot: i32) -> Share {
+        Share {
+            uid: uid.to_string(),
+            lot,
+            trading_status: SecurityTradingStatus::NormalTrading as i32,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_detects_listed_changed_and_removed() {
+        let stored = vec![
+            DbShareVersion::from_share(&share("a", 1), 100),
+            DbShareVersion::from_share(&share("b", 10), 100),
+            DbShareVersion::from_share(&share("c", 1), 100),
+        ];
+        let a = share("a", 1);
+        let b = share("b", 100);
+        let d = share("d", 1);
+
+        let diff = CatalogDiff::compute(&stored, &[&a, &b, &d], 200);
+
+        assert_eq!(diff.listed.len(), 1);
+        assert_eq!(diff.listed[0].uid, "d");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].0.lot, 10);
+        assert_eq!(diff.changed[0].1.lot, 100);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].uid, "c");
+    }
+
+    #[test]
+    fn test_history_rows_close_previous_versions() {
+        let stored = vec![DbShareVersion::from_share(&share("b", 10), 100)];
+        let b = share("b", 100);
+
+        let rows = CatalogDiff::compute(&stored, &[&b], 200).history_rows(200);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].valid_from, rows[0].valid_to), (100, Some(200)));
+        assert_eq!((rows[1].valid_from, rows[1].valid_to), (200, None));
+    }
+
+    #[test]
+    fn test_unchanged_catalog_produces_no_rows() {
+        let stored = vec![DbShareVersion::from_share(&share("a", 1), 100)];
+        let a = share("a", 1);
+
+        let diff = CatalogDiff::compute(&stored, &[&a], 200);
+
+        assert!(diff.is_empty());
+        assert!(diff.history_rows(200).is_empty());
+    }
+}
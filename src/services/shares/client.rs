@@ -1,66 +1,296 @@
 use std::{sync::Arc, time::Duration};
 use tokio::time;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
+use super::{
+    catalog_diff::CatalogDiff,
+    instrument_events::events_from_diff,
+    universe::{self, UniverseSyncPlan},
+};
 use crate::{
-    app_state::models::AppState, db::clickhouse::clickhouse_service::ClickhouseService, generate::tinkoff_public_invest_api_contract_v1::{InstrumentStatus, InstrumentsRequest}, services::tinkoff_client_grpc::TinkoffClient
+    app_state::models::AppState,
+    db::{
+        clickhouse::{
+            clickhouse_service::ClickhouseService, models::universe_change::DbUniverseChange,
+        },
+        postgres::{models::watchlist::WatchlistOrigin, postgres_service::PostgresService},
+    },
+    env_config::models::app_setting::AppSettings,
+    generate::tinkoff_public_invest_api_contract_v1::Share,
+    services::{
+        exclusions::exclusion_list::{ExclusionList, ExclusionSet},
+        job_ledger::{JobLedger, SHARES_JOB},
+        sources::candle_source::CandleSource,
+        webhook_notifier::WebhookNotifier,
+    },
 };
 
 // Mark the struct as pub to make it visible only within the parent module
 pub struct ClientShares {
     clickhouse_service: Arc<ClickhouseService>,
-    grpc_tinkoff: Arc<TinkoffClient>,
+    postgres_service: Option<Arc<PostgresService>>, // Список загрузки; без него вселенная не синхронизируется
+    candle_source: Arc<dyn CandleSource + Send + Sync>,
+    events_webhook: WebhookNotifier,
+    exclusions: Arc<ExclusionList>,
+    ledger: JobLedger,
+    settings: Arc<AppSettings>,
 }
 
 impl ClientShares {
     pub async fn new(
         clickhouse_service: Arc<ClickhouseService>,
-        grpc_tinkoff: Arc<TinkoffClient>
+        postgres_service: Option<Arc<PostgresService>>,
+        candle_source: Arc<dyn CandleSource + Send + Sync>,
+        exclusions: Arc<ExclusionList>,
+        settings: Arc<AppSettings>,
     ) -> Self {
-        Self { 
-            clickhouse_service, 
-            grpc_tinkoff 
+        let events_config = &settings.app_config.instrument_events;
+        let events_webhook = WebhookNotifier::new(
+            events_config.webhook_url.clone(),
+            events_config.webhook_timeout_seconds,
+        );
+
+        let ledger = JobLedger::new(
+            postgres_service
+                .as_ref()
+                .map(|postgres| postgres.repository_job_run.clone()),
+        );
+
+        Self {
+            clickhouse_service,
+            postgres_service,
+            candle_source,
+            events_webhook,
+            exclusions,
+            ledger,
+            settings,
         }
     }
 
-    // This method is now pub(super) and can be called only by code in the parent module
+    /// Обновляет каталог акций и записывает запуск в журнал job_runs
     pub async fn update_shares(&self) -> Result<u64, Box<dyn std::error::Error>> {
-        info!("Fetching updated instruments data");
-
-        // Fetch shares from Tinkoff API
-        let request = self
-            .grpc_tinkoff
-            .create_request(InstrumentsRequest {
-                instrument_status: InstrumentStatus::All as i32,
-            })
-            .expect("Failed to create request");
+        let mut run = self.ledger.start(SHARES_JOB).await;
+        // The error is kept as a string: a boxed error is not Send across the ledger write
+        let result = self.refresh_catalog().await.map_err(|e| e.to_string());
+        if let Ok(count) = &result {
+            run.instruments_total = *count as i64;
+        }
+        self.ledger
+            .finish(&mut run, result.as_ref().err().cloned())
+            .await;
+        Ok(result?)
+    }
 
-        let mut instruments_client = self.grpc_tinkoff.instruments.clone();
+    async fn refresh_catalog(&self) -> Result<u64, Box<dyn std::error::Error>> {
+        info!("Fetching updated instruments data");
 
-        let shares_response = instruments_client
-            .shares(request)
-            .await
-            .map(|response| response.into_inner())
-            .expect("Failed to get shares");
+        let instruments = self.candle_source.get_shares().await?;
+        info!(
+            "Shares: total {} records fetched from {}",
+            instruments.len(),
+            self.candle_source.source_name()
+        );
 
-        let total_shares = shares_response.instruments.len();
-        info!("Shares: total {} records fetched", total_shares);
+        // Drop excluded instruments before anything is stored
+        if let Err(e) = self.exclusions.reload().await {
+            error!(
+                "Failed to reload instrument exclusions, using cached list: {}",
+                e
+            );
+        }
+        let exclusions = self.exclusions.snapshot().await;
+        let shares: Vec<&Share> = instruments
+            .iter()
+            .filter(
+                |share| match exclusions.find(&share.uid, &share.figi, &share.ticker) {
+                    Some(exclusion) => {
+                        info!(
+                            "Skipping excluded share: FIGI={}, Name='{}', Ticker='{}', reason: {}",
+                            share.figi, share.name, share.ticker, exclusion.reason
+                        );
+                        false
+                    }
+                    None => true,
+                },
+            )
+            .collect();
 
         // Insert directly from proto models
-        match self
+        let count = match self
             .clickhouse_service
             .repository_share
-            .insert_shares(&shares_response.instruments, true) // Set clean_first to true
+            .insert_shares(&shares)
             .await
         {
             Ok(count) => {
                 info!("Successfully inserted {} shares into the database", count);
-                Ok(count)
+                count
             }
             Err(e) => {
                 error!("Failed to insert shares into database: {}", e);
-                Err(Box::new(e))
+                return Err(Box::new(e));
             }
+        };
+
+        // Shares missing from the catalog stay only in the history
+        self.clickhouse_service
+            .repository_share
+            .remove_delisted(&shares)
+            .await?;
+
+        // Record attribute changes in the catalog history and emit lifecycle events
+        if let Err(e) = self.apply_catalog_changes(&shares, &exclusions).await {
+            error!("Failed to update share catalog history: {}", e);
+            return Err(e);
+        }
+
+        // Select the instrument universe and propagate it to liquid_shares and the backfill list
+        if let Err(e) = self.sync_universe(&shares).await {
+            error!("Failed to sync instrument universe: {}", e);
+            return Err(e);
         }
+
+        Ok(count)
+    }
+
+    /// Сравнивает ответ API с актуальными версиями каталога,
+    /// записывает новые версии и события жизненного цикла инструментов
+    async fn apply_catalog_changes(
+        &self,
+        shares: &[&Share],
+        exclusions: &ExclusionSet,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let repository = &self.clickhouse_service.repository_share_history;
+        let now = chrono::Utc::now().timestamp();
+
+        // Excluded instruments are frozen in the history rather than reported as delisted
+        let current: Vec<_> = repository
+            .get_current_versions()
+            .await?
+            .into_iter()
+            .filter(|v| exclusions.find(&v.uid, &v.figi, &v.ticker).is_none())
+            .collect();
+        let diff = CatalogDiff::compute(&current, shares, now);
+
+        if diff.is_empty() {
+            debug!("Share catalog history: no changes");
+            return Ok(());
+        }
+
+        info!(
+            "Share catalog history: {} listed, {} changed, {} removed",
+            diff.listed.len(),
+            diff.changed.len(),
+            diff.removed.len()
+        );
+
+        // При первом заполнении истории весь каталог выглядел бы как новые листинги
+        let events = if current.is_empty() {
+            info!("Share catalog history initialised, skipping lifecycle events");
+            Vec::new()
+        } else {
+            events_from_diff(&diff, now)
+        };
+
+        // Events go first: once the history is updated the next diff no longer sees
+        // these changes, so a failed events insert must leave the history untouched
+        if !events.is_empty() {
+            self.clickhouse_service
+                .repository_instrument_event
+                .insert_events(&events)
+                .await?;
+        }
+        repository.insert_versions(&diff.history_rows(now)).await?;
+
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        if self.events_webhook.is_enabled() {
+            let payload = serde_json::json!({ "events": events });
+            match self.events_webhook.send(&payload).await {
+                Ok(()) => info!("Sent {} instrument events to webhook", events.len()),
+                // The events are already stored, a failed webhook must not fail the refresh
+                Err(e) => error!("Failed to send instrument events to webhook: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Применяет правила вселенной к каталогу: обновляет liquid_shares и,
+    /// если включено, список загрузки свечей instrument_candle_info
+    async fn sync_universe(&self, shares: &[&Share]) -> Result<(), Box<dyn std::error::Error>> {
+        let rules = &self.settings.app_config.universe;
+        let now = chrono::Utc::now().timestamp();
+
+        let universe: Vec<&Share> = shares
+            .iter()
+            .copied()
+            .filter(|share| universe::matches(rules, share, now))
+            .collect();
+        info!(
+            "Instrument universe: {} of {} shares match the configured rules",
+            universe.len(),
+            shares.len()
+        );
+
+        let universe_uids: Vec<String> = universe.iter().map(|s| s.uid.clone()).collect();
+        self.clickhouse_service
+            .repository_share
+            .update_liquid_shares(&universe_uids)
+            .await?;
+
+        if !rules.sync_backfill {
+            debug!("Backfill list sync is disabled in configuration");
+            return Ok(());
+        }
+
+        let Some(postgres_service) = &self.postgres_service else {
+            warn!("Backfill list sync needs PostgreSQL, which is not connected");
+            return Ok(());
+        };
+        let watchlist = &postgres_service.repository_watchlist;
+        let plan = UniverseSyncPlan::build(&universe, &watchlist.get_backfill_state().await?);
+        if plan.is_empty() {
+            debug!("Backfill list already matches the instrument universe");
+            return Ok(());
+        }
+
+        watchlist
+            .add_instruments(&plan.add, WatchlistOrigin::Universe)
+            .await?;
+        watchlist.set_active(&plan.reactivate, true).await?;
+        watchlist.set_active(&plan.deactivate, false).await?;
+
+        // Log every change, both to the application log and to universe_changes
+        let tickers: std::collections::HashMap<&str, &str> = shares
+            .iter()
+            .map(|s| (s.uid.as_str(), s.ticker.as_str()))
+            .collect();
+        let change = |uid: &str, action: &str| {
+            let ticker = tickers.get(uid).copied().unwrap_or_default();
+            info!("Instrument universe: {} {} ({})", action, uid, ticker);
+            DbUniverseChange {
+                change_time: now,
+                uid: uid.to_string(),
+                ticker: ticker.to_string(),
+                action: action.to_string(),
+            }
+        };
+
+        let changes: Vec<DbUniverseChange> = plan
+            .add
+            .iter()
+            .map(|(uid, _)| change(uid, "ADDED"))
+            .chain(plan.reactivate.iter().map(|uid| change(uid, "ADDED")))
+            .chain(plan.deactivate.iter().map(|uid| change(uid, "REMOVED")))
+            .collect();
+        self.clickhouse_service
+            .repository_my_instrument
+            .log_universe_changes(&changes)
+            .await?;
+
+        Ok(())
     }
 }
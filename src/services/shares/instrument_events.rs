@@ -0,0 +1,216 @@
+use crate::db::clickhouse::models::{
+    instrument_event::DbInstrumentEvent, share_version::DbShareVersion,
+};
+
+use super::catalog_diff::CatalogDiff;
+
+/// Типы событий жизненного цикла инструмента
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstrumentEventType {
+    /// Инструмент впервые появился в каталоге
+    Listed,
+    /// Инструмент пропал из ответа API
+    Delisted,
+    TradingStatusChanged,
+    BuyAvailableChanged,
+    SellAvailableChanged,
+}
+
+impl InstrumentEventType {
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Listed => "LISTED",
+            Self::Delisted => "DELISTED",
+            Self::TradingStatusChanged => "TRADING_STATUS_CHANGED",
+            Self::BuyAvailableChanged => "BUY_AVAILABLE_CHANGED",
+            Self::SellAvailableChanged => "SELL_AVAILABLE_CHANGED",
+        }
+    }
+}
+
+fn event(
+    event_type: InstrumentEventType,
+    version: &DbShareVersion,
+    old_value: String,
+    new_value: String,
+    now: i64,
+) -> DbInstrumentEvent {
+    DbInstrumentEvent {
+        event_time: now,
+        event_type: event_type.as_str_name().to_string(),
+        uid: version.uid.clone(),
+        figi: version.figi.clone(),
+        ticker: version.ticker.clone(),
+        old_value,
+        new_value,
+    }
+}
+
+/// Строит типизированные события из разницы каталога
+///
+/// Изменения атрибутов, не имеющих своего типа события (лотность, шаг цены и
+/// прочие флаги), попадают только в историю каталога
+pub fn events_from_diff(diff: &CatalogDiff, now: i64) -> Vec<DbInstrumentEvent> {
+    use InstrumentEventType::*;
+
+    let mut events = Vec::new();
+
+    for listed in &diff.listed {
+        events.push(event(
+            Listed,
+            listed,
+            String::new(),
+            listed.trading_status.clone(),
+            now,
+        ));
+    }
+
+    for (old, new) in &diff.changed {
+        if old.trading_status != new.trading_status {
+            events.push(event(
+                TradingStatusChanged,
+                new,
+                old.trading_status.clone(),
+                new.trading_status.clone(),
+                now,
+            ));
+        }
+        if old.buy_available_flag != new.buy_available_flag {
+            events.push(event(
+                BuyAvailableChanged,
+                new,
+                old.buy_available_flag.to_string(),
+                new.buy_available_flag.to_string(),
+                now,
+            ));
+        }
+        if old.sell_available_flag != new.sell_available_flag {
+            events.push(event(
+                SellAvailableChanged,
+                new,
+                old.sell_available_flag.to_string(),
+                new.sell_available_flag.to_string(),
+                now,
+            ));
+        }
+    }
+
+    for removed in &diff.removed {
+        events.push(event(
+            Delisted,
+            removed,
+            removed.trading_status.clone(),
+            String::new(),
+            now,
+        ));
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::tinkoff_public_invest_api_contract_v1::{SecurityTradingStatus, Share};
+
+    fn share(uid: &str, status: SecurityTradingStatus, buy: bool, sell: bool) -> Share {
+        Share {
+            uid: uid.to_string(),
+            figi: format!("FIGI-{}", uid),
+            ticker: uid.to_uppercase(),
+            lot: 1,
+            trading_status: status as i32,
+            buy_available_flag: buy,
+            sell_available_flag: sell,
+            ..Default::default()
+        }
+    }
+
+    fn types(events: &[DbInstrumentEvent]) -> Vec<(&str, &str)> {
+        events
+            .iter()
+            .map(|e| (e.uid.as_str(), e.event_type.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_listed_and_delisted_events() {
+        let stored = vec![DbShareVersion::from_share(
+            &share("a", SecurityTradingStatus::NormalTrading, true, true),
+            100,
+        )];
+        let b = share(
+            "b",
+            SecurityTradingStatus::NotAvailableForTrading,
+            true,
+            true,
+        );
+
+        let events = events_from_diff(&CatalogDiff::compute(&stored, &[&b], 200), 200);
+
+        assert_eq!(types(&events), vec![("b", "LISTED"), ("a", "DELISTED")]);
+        let (listed, delisted) = (&events[0], &events[1]);
+        assert_eq!(
+            (listed.figi.as_str(), listed.ticker.as_str()),
+            ("FIGI-b", "B")
+        );
+        assert_eq!(listed.old_value, "");
+        assert_eq!(
+            listed.new_value,
+            SecurityTradingStatus::NotAvailableForTrading.as_str_name()
+        );
+        assert_eq!(
+            delisted.old_value,
+            SecurityTradingStatus::NormalTrading.as_str_name()
+        );
+        assert_eq!(delisted.new_value, "");
+        assert!(events.iter().all(|e| e.event_time == 200));
+    }
+
+    #[test]
+    fn test_changed_events() {
+        let stored = vec![DbShareVersion::from_share(
+            &share("a", SecurityTradingStatus::NormalTrading, true, true),
+            100,
+        )];
+        let a = share("a", SecurityTradingStatus::BreakInTrading, false, true);
+
+        let events = events_from_diff(&CatalogDiff::compute(&stored, &[&a], 200), 200);
+
+        assert_eq!(
+            types(&events),
+            vec![
+                ("a", "TRADING_STATUS_CHANGED"),
+                ("a", "BUY_AVAILABLE_CHANGED")
+            ]
+        );
+        assert_eq!(
+            (events[0].old_value.as_str(), events[0].new_value.as_str()),
+            (
+                SecurityTradingStatus::NormalTrading.as_str_name(),
+                SecurityTradingStatus::BreakInTrading.as_str_name()
+            )
+        );
+        assert_eq!(
+            (events[1].old_value.as_str(), events[1].new_value.as_str()),
+            ("true", "false")
+        );
+    }
+
+    #[test]
+    fn test_attribute_changes_without_event_type() {
+        let stored = vec![DbShareVersion::from_share(
+            &share("a", SecurityTradingStatus::NormalTrading, true, true),
+            100,
+        )];
+        let a = Share {
+            lot: 10,
+            ..share("a", SecurityTradingStatus::NormalTrading, true, true)
+        };
+
+        let diff = CatalogDiff::compute(&stored, &[&a], 200);
+
+        assert_eq!(diff.changed.len(), 1);
+        assert!(events_from_diff(&diff, 200).is_empty());
+    }
+}
@@ -1,3 +1,6 @@
+pub mod catalog_diff;
 pub mod client;
+pub mod instrument_events;
 pub mod models;
 pub mod shares_scheduler;
+pub mod universe;
@@ -41,3 +41,12 @@ pub fn extract_quotation(quotation: &Option<Quotation>) -> (i64, i32) {
 pub fn quotation_to_f64(units: i64, nano: i32) -> f64 {
     units as f64 + (nano as f64 / 1_000_000_000.0)
 }
+
+// Helper function to convert float to quotation
+pub fn f64_to_quotation(value: f64) -> Quotation {
+    let units = value.trunc();
+    Quotation {
+        units: units as i64,
+        nano: ((value - units) * 1_000_000_000.0).round() as i32,
+    }
+}
@@ -1,9 +1,12 @@
-use std::{sync::Arc, time::Duration};
-use tokio::time;
-use tracing::{debug, error, info};
+use std::sync::Arc;
+use tracing::{error, info, warn};
 
 use super::client::ClientShares;
-use crate::{AppState, env_config::models::app_config::OperationWindow};
+use crate::{
+    AppState,
+    env_config::models::app_config::OperationWindow,
+    services::{job_ledger::SHARES_JOB, scheduling::scheduled_job::ScheduledJob},
+};
 
 /// Scheduler for periodic tasks related to Tinkoff instruments
 pub struct InstrumentsScheduler {
@@ -22,9 +25,16 @@ impl InstrumentsScheduler {
             info!("Instruments updates are disabled in configuration");
             return Ok(0);
         }
-
-        //
-        self.app_state.client_shares.update_shares().await
+        let Some(client_shares) = &self.app_state.client_shares else {
+            return Ok(0);
+        };
+
+        // При выборе лидера запуск пропускается, если задачу уже выполняет другая реплика
+        self.app_state
+            .job_locks
+            .run_exclusive(SHARES_JOB, client_shares.update_shares())
+            .await
+            .unwrap_or(Ok(0))
     }
 
     /// Start the scheduler with proper configuration checks
@@ -36,6 +46,10 @@ impl InstrumentsScheduler {
             info!("Instruments scheduler is disabled in configuration");
             return;
         }
+        if self.app_state.clickhouse_service.is_none() {
+            warn!("Instruments scheduler needs ClickHouse, which is not connected; not starting");
+            return;
+        }
 
         info!("Starting instruments scheduler");
 
@@ -50,43 +64,26 @@ impl InstrumentsScheduler {
 
         // Log operation window
         info!(
-            "Instruments scheduler operation window: {} to {} UTC",
-            config.start_time, config.end_time
+            "Instruments scheduler operation window: {}",
+            config.operation_window.describe()
         );
 
-
-        // Clone app_state for the task
-        let app_state = self.app_state.clone();
-
-        // Start interval-based loop
-        let interval_seconds = config.interval_seconds;
-        let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds));
-
-        tokio::spawn(async move {
-            loop {
-                interval.tick().await;
-
-                // Check operation window
-                let config = &app_state.settings.app_config.shares_scheduler;
-                if !config.is_operation_allowed() {
-                    debug!(
-                        "Instruments scheduler: skipping update - outside operation window (current time: {})",
-                        chrono::Utc::now().format("%H:%M:%S")
-                    );
-                    continue;
-                }
-
-                info!("Instruments scheduler: triggering update");
-
-                // Trigger update using client from app_state
-                match app_state.client_shares.update_shares().await {
+        // The loop runs on the task tracker so shutdown waits for the refresh in flight
+        ScheduledJob::new(SHARES_JOB, &config.schedule).spawn(
+            self.app_state.clone(),
+            |config| config.shares_scheduler.is_operation_allowed(),
+            |app_state| async move {
+                let Some(client_shares) = &app_state.client_shares else {
+                    return;
+                };
+                match client_shares.update_shares().await {
                     Ok(count) => info!(
                         "Instruments scheduler: successfully updated {} shares",
                         count
                     ),
                     Err(e) => error!("Instruments scheduler: failed to update shares: {}", e),
                 }
-            }
-        });
+            },
+        );
     }
 }
@@ -0,0 +1,196 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::db::postgres::models::watchlist::WatchlistOrigin;
+use crate::env_config::models::app_config::UniverseConfig;
+use crate::generate::tinkoff_public_invest_api_contract_v1::{RealExchange, Share, ShareType};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Сравнение без учёта регистра; пустой список фильтра пропускает любое значение
+fn allowed(filter: &[String], values: &[&str]) -> bool {
+    filter.is_empty()
+        || filter
+            .iter()
+            .any(|f| values.iter().any(|v| f.eq_ignore_ascii_case(v)))
+}
+
+/// Проверяет, входит ли акция во вселенную по правилам из конфигурации
+///
+/// # Arguments
+/// * `rules` - Правила вселенной
+/// * `share` - Инструмент из ответа API
+/// * `now` - Текущее время в секундах, от него считается длина истории
+pub fn matches(rules: &UniverseConfig, share: &Share, now: i64) -> bool {
+    let real_exchange = RealExchange::try_from(share.real_exchange)
+        .map(|e| e.as_str_name())
+        .unwrap_or_default();
+    let share_type = ShareType::try_from(share.share_type)
+        .map(|t| t.as_str_name())
+        .unwrap_or_default();
+    let first_candle = share.first_1min_candle_date.as_ref().map(|ts| ts.seconds);
+
+    if !allowed(&rules.exchanges, &[&share.exchange, real_exchange])
+        || !allowed(&rules.currencies, &[&share.currency])
+        || !allowed(&rules.sectors, &[&share.sector])
+        || !allowed(&rules.share_types, &[share_type])
+    {
+        return false;
+    }
+
+    if !rules.include_qualified && share.for_qual_investor_flag {
+        return false;
+    }
+
+    if rules.require_liquidity
+        && !(share.buy_available_flag && share.sell_available_flag && first_candle.is_some())
+    {
+        return false;
+    }
+
+    if rules.min_history_days > 0 {
+        match first_candle {
+            Some(first) if now - first >= rules.min_history_days * SECONDS_PER_DAY => {}
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+/// Изменения списка загрузки свечей, необходимые для совпадения со вселенной
+#[derive(Debug, Default, PartialEq)]
+pub struct UniverseSyncPlan {
+    /// Новые инструменты: (uid, first_1min_candle_date)
+    pub add: Vec<(String, i64)>,
+    /// Инструменты, снова попавшие во вселенную; выключенные вручную не включаются
+    pub reactivate: Vec<String>,
+    /// Инструменты, выпавшие из вселенной; только добавленные синхронизацией
+    pub deactivate: Vec<String>,
+}
+
+impl UniverseSyncPlan {
+    /// # Arguments
+    /// * `universe` - Акции, подходящие под правила
+    /// * `backfill` - Текущее состояние списка загрузки: (uid, is_active, origin)
+    pub fn build(universe: &[&Share], backfill: &[(String, bool, WatchlistOrigin)]) -> Self {
+        let state: HashMap<&str, (bool, WatchlistOrigin)> = backfill
+            .iter()
+            .map(|(uid, active, origin)| (uid.as_str(), (*active, *origin)))
+            .collect();
+        let universe_uids: HashSet<&str> = universe.iter().map(|s| s.uid.as_str()).collect();
+
+        let mut plan = UniverseSyncPlan::default();
+
+        for share in universe {
+            match state.get(share.uid.as_str()) {
+                None => plan.add.push((
+                    share.uid.clone(),
+                    share
+                        .first_1min_candle_date
+                        .as_ref()
+                        .map_or(0, |ts| ts.seconds),
+                )),
+                Some((false, WatchlistOrigin::Universe)) => plan.reactivate.push(share.uid.clone()),
+                // Active, or switched off by hand
+                Some(_) => {}
+            }
+        }
+
+        // Manually added instruments stay until they are removed through the API
+        for (uid, active, origin) in backfill {
+            if *active
+                && *origin == WatchlistOrigin::Universe
+                && !universe_uids.contains(uid.as_str())
+            {
+                plan.deactivate.push(uid.clone());
+            }
+        }
+
+        plan
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.reactivate.is_empty() && self.deactivate.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> UniverseConfig {
+        UniverseConfig {
+            sync_backfill: true,
+            exchanges: vec![],
+            currencies: vec!["rub".to_string()],
+            sectors: vec![],
+            share_types: vec![],
+            include_qualified: false,
+            min_history_days: 30,
+            require_liquidity: true,
+        }
+    }
+
+    fn liquid_share(uid: &str, first_candle: i64) -> Share {
+        Share {
+            uid: uid.to_string(),
+            currency: "RUB".to_string(),
+            buy_available_flag: true,
+            sell_available_flag: true,
+            first_1min_candle_date: Some(prost_types::Timestamp {
+                seconds: first_candle,
+                nanos: 0,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_matches_applies_every_rule() {
+        let now = 100 * SECONDS_PER_DAY;
+        let rules = rules();
+
+        assert!(matches(&rules, &liquid_share("a", 0), now));
+
+        let short_history = liquid_share("b", 90 * SECONDS_PER_DAY);
+        assert!(!matches(&rules, &short_history, now));
+
+        let usd = Share {
+            currency: "usd".to_string(),
+            ..liquid_share("c", 0)
+        };
+        assert!(!matches(&rules, &usd, now));
+
+        let qualified = Share {
+            for_qual_investor_flag: true,
+            ..liquid_share("d", 0)
+        };
+        assert!(!matches(&rules, &qualified, now));
+
+        let not_tradable = Share {
+            sell_available_flag: false,
+            ..liquid_share("e", 0)
+        };
+        assert!(!matches(&rules, &not_tradable, now));
+    }
+
+    #[test]
+    fn test_sync_plan() {
+        let a = liquid_share("a", 10);
+        let b = liquid_share("b", 20);
+        let backfill = vec![
+            ("b".to_string(), false, WatchlistOrigin::Universe),
+            ("c".to_string(), true, WatchlistOrigin::Universe),
+            ("d".to_string(), true, WatchlistOrigin::Manual),
+            ("e".to_string(), false, WatchlistOrigin::Manual),
+        ];
+        let e = liquid_share("e", 30);
+
+        let plan = UniverseSyncPlan::build(&[&a, &b, &e], &backfill);
+
+        assert_eq!(plan.add, vec![("a".to_string(), 10)]);
+        assert_eq!(plan.reactivate, vec!["b".to_string()]);
+        // "d" and "e" were added or removed through the API, the sync leaves them alone
+        assert_eq!(plan.deactivate, vec!["c".to_string()]);
+    }
+}
@@ -0,0 +1,283 @@
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+use crate::app_state::models::AppState;
+use crate::db::clickhouse::clickhouse_service::ClickhouseService;
+use crate::db::clickhouse::models::source_divergence::DbSourceDivergence;
+use crate::db::postgres::postgres_service::PostgresService;
+use crate::db::storage::stored_candle::StoredCandle;
+use crate::env_config::models::source_comparison::SourceComparisonConfig;
+use crate::services::candles::ohlcv_bar::OhlcvBar;
+use crate::services::sources::candle_source::CandleSource;
+use crate::services::sources::moex_iss_source::MoexIssSource;
+
+pub type ComparisonError = Box<dyn Error + Send + Sync>;
+
+const DAY_SECONDS: i64 = 86_400;
+
+/// Итог сравнения всех инструментов
+#[derive(Debug, Default)]
+pub struct ComparisonReport {
+    pub instruments_checked: usize,
+    pub instruments_skipped: usize, // Не торгуются на Московской бирже
+    pub instruments_failed: usize,
+    pub candles_compared: usize,
+    pub divergences: usize,
+}
+
+/// Поля, по которым свеча расходится; пусто — источники согласны
+///
+/// only_tinkoff и only_iss — свеча есть только в одном источнике
+fn compare_bars(
+    tinkoff: Option<OhlcvBar>,
+    iss: Option<OhlcvBar>,
+    config: &SourceComparisonConfig,
+) -> Vec<&'static str> {
+    match (tinkoff, iss) {
+        (None, None) => Vec::new(),
+        (Some(_), None) => vec!["only_tinkoff"],
+        (None, Some(_)) => vec!["only_iss"],
+        (Some(tinkoff), Some(iss)) => {
+            tinkoff.diverging_fields(&iss, config.price_tolerance, config.volume_tolerance)
+        }
+    }
+}
+
+/// Сравнение сохранённых свечей Tinkoff со свечами MOEX ISS
+pub struct CandleComparison {
+    app_state: Arc<AppState>,
+    clickhouse_service: Arc<ClickhouseService>,
+    postgres_service: Arc<PostgresService>,
+}
+
+impl CandleComparison {
+    /// None, если ClickHouse или PostgreSQL не подключены
+    pub fn new(app_state: Arc<AppState>) -> Option<Self> {
+        Some(Self {
+            clickhouse_service: app_state.clickhouse_service.clone()?,
+            postgres_service: app_state.postgres_service.clone()?,
+            app_state,
+        })
+    }
+
+    /// Сравнивает `lookback_days` завершённых суток UTC у каждого активного инструмента MOEX
+    pub async fn run(&self, run_id: &str, now: DateTime<Utc>) -> ComparisonReport {
+        let mut report = ComparisonReport::default();
+        let config = &self.app_state.settings.app_config.source_comparison;
+
+        let source = match self.open_source().await {
+            Ok(source) => source,
+            Err(e) => {
+                warn!("Source comparison: failed to prepare MOEX ISS: {}", e);
+                report.instruments_failed = 1;
+                return report;
+            }
+        };
+        let instruments = match self
+            .postgres_service
+            .repository_watchlist
+            .get_active()
+            .await
+        {
+            Ok(instruments) => instruments,
+            Err(e) => {
+                warn!("Source comparison: failed to read the watchlist: {}", e);
+                report.instruments_failed = 1;
+                return report;
+            }
+        };
+
+        let today = now.timestamp() - now.timestamp().rem_euclid(DAY_SECONDS);
+        for instrument in &instruments {
+            if self.app_state.shutdown.is_cancelled() {
+                info!("Shutdown requested, stopping source comparison");
+                break;
+            }
+            if source.listing(&instrument.uid).is_none() {
+                debug!("{} is not listed on MOEX, not compared", instrument.uid);
+                report.instruments_skipped += 1;
+                continue;
+            }
+
+            // Only candles the loader has fully stored can be compared
+            let bucket = |time: i64| config.resolution.bucket_start(time);
+            let from = (today - config.lookback_days * DAY_SECONDS)
+                .max(bucket(instrument.first_1min_candle_date));
+            let to = today.min(bucket(instrument.last_1min_candle_date));
+            if instrument.last_1min_candle_date == 0 || from >= to {
+                debug!("Nothing to compare for {}", instrument.uid);
+                continue;
+            }
+
+            match self
+                .compare_instrument(&source, run_id, &instrument.uid, from, to, now)
+                .await
+            {
+                Ok((candles, divergences)) => {
+                    report.instruments_checked += 1;
+                    report.candles_compared += candles;
+                    report.divergences += divergences;
+                }
+                Err(e) => {
+                    warn!("Failed to compare {} with MOEX ISS: {}", instrument.uid, e);
+                    report.instruments_failed += 1;
+                }
+            }
+        }
+
+        info!(
+            "Source comparison finished: {} instruments, {} skipped, {} candles, {} divergences",
+            report.instruments_checked,
+            report.instruments_skipped,
+            report.candles_compared,
+            report.divergences
+        );
+        report
+    }
+
+    /// ISS-источник с биржевыми реквизитами акций из tinkoff_shares
+    async fn open_source(&self) -> Result<MoexIssSource, ComparisonError> {
+        let listings = self
+            .clickhouse_service
+            .repository_share
+            .get_moex_listings()
+            .await?;
+        let config = &self.app_state.settings.app_config.source_comparison;
+        Ok(MoexIssSource::new(&config.moex_iss, listings)?)
+    }
+
+    /// Возвращает число сравнённых свечей и расхождений
+    async fn compare_instrument(
+        &self,
+        source: &MoexIssSource,
+        run_id: &str,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+        now: DateTime<Utc>,
+    ) -> Result<(usize, usize), ComparisonError> {
+        let config = &self.app_state.settings.app_config.source_comparison;
+        let resolution = config.resolution;
+
+        let iss: HashMap<i64, OhlcvBar> = source
+            .get_candles(instrument_uid, from, to, resolution)
+            .await?
+            .iter()
+            .filter(|candle| candle.is_complete)
+            .filter_map(|candle| StoredCandle::from_historic(instrument_uid, candle))
+            .map(|candle| (candle.time, OhlcvBar::from_stored(&candle)))
+            .collect();
+        let tinkoff: HashMap<i64, OhlcvBar> = self
+            .app_state
+            .candle_repository
+            .get_candles_at(instrument_uid, from, to - 1, resolution)
+            .await?
+            .iter()
+            .map(|candle| (candle.time, OhlcvBar::from_stored(candle)))
+            .collect();
+
+        let ticker = source
+            .listing(instrument_uid)
+            .map(|listing| listing.ticker.clone())
+            .unwrap_or_default();
+        let times: BTreeSet<i64> = tinkoff.keys().chain(iss.keys()).copied().collect();
+        let mut divergences = Vec::new();
+        for &time in &times {
+            let tinkoff = tinkoff.get(&time);
+            let iss = iss.get(&time);
+            let fields = compare_bars(tinkoff.copied(), iss.copied(), config);
+            if fields.is_empty() {
+                continue;
+            }
+
+            divergences.push(DbSourceDivergence {
+                run_id: run_id.to_string(),
+                instrument_uid: instrument_uid.to_string(),
+                ticker: ticker.clone(),
+                resolution: resolution.as_str().to_string(),
+                time,
+                fields: fields.join(","),
+                tinkoff_open: tinkoff.map(|bar| bar.open),
+                tinkoff_high: tinkoff.map(|bar| bar.high),
+                tinkoff_low: tinkoff.map(|bar| bar.low),
+                tinkoff_close: tinkoff.map(|bar| bar.close),
+                tinkoff_volume: tinkoff.map(|bar| bar.volume),
+                iss_open: iss.map(|bar| bar.open),
+                iss_high: iss.map(|bar| bar.high),
+                iss_low: iss.map(|bar| bar.low),
+                iss_close: iss.map(|bar| bar.close),
+                iss_volume: iss.map(|bar| bar.volume),
+                detected_at: now.timestamp(),
+            });
+        }
+
+        if !divergences.is_empty() {
+            warn!(
+                "{} of {} {} candles of {} differ from MOEX ISS",
+                divergences.len(),
+                times.len(),
+                resolution.as_str(),
+                ticker
+            );
+        }
+        self.clickhouse_service
+            .repository_source_divergence
+            .insert_divergences(&divergences)
+            .await?;
+
+        Ok((times.len(), divergences.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env_config::models::retention::CandleResolution;
+    use crate::env_config::models::schedule::{CatchUpPolicy, ScheduleConfig};
+    use crate::env_config::models::source_comparison::MoexIssConfig;
+
+    fn bar(close: f64, volume: i64) -> OhlcvBar {
+        OhlcvBar {
+            open: 100.0,
+            high: 110.0,
+            low: 90.0,
+            close,
+            volume,
+        }
+    }
+
+    #[test]
+    fn flags_divergent_candles() {
+        let config = SourceComparisonConfig {
+            enabled: true,
+            schedule: ScheduleConfig {
+                cron: None,
+                run_times: vec!["05:30:00".to_string()],
+                catch_up: CatchUpPolicy::Skip,
+            },
+            resolution: CandleResolution::Hour,
+            lookback_days: 3,
+            price_tolerance: 0.0001,
+            volume_tolerance: 0.01,
+            moex_iss: MoexIssConfig {
+                base_url: "https://iss.moex.com".to_string(),
+                timeout: 30,
+            },
+        };
+
+        let iss = Some(bar(105.0, 10_000));
+        assert!(compare_bars(Some(bar(105.005, 9_950)), iss, &config).is_empty());
+        assert_eq!(
+            compare_bars(Some(bar(105.2, 8_000)), iss, &config),
+            vec!["close", "volume"]
+        );
+        assert_eq!(
+            compare_bars(Some(bar(105.0, 1)), None, &config),
+            vec!["only_tinkoff"]
+        );
+        assert_eq!(compare_bars(None, iss, &config), vec!["only_iss"]);
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::candle_comparison::CandleComparison;
+use crate::{
+    AppState,
+    services::{job_ledger::SOURCE_COMPARISON_JOB, scheduling::scheduled_job::ScheduledJob},
+};
+
+/// Ежедневное сравнение сохранённых свечей со свечами MOEX ISS
+pub struct SourceComparisonScheduler {
+    app_state: Arc<AppState>,
+}
+
+impl SourceComparisonScheduler {
+    pub fn new(app_state: Arc<AppState>) -> Self {
+        Self { app_state }
+    }
+
+    pub async fn start(&self) {
+        let config = &self.app_state.settings.app_config.source_comparison;
+        if !config.enabled {
+            info!("Source comparison is disabled in configuration");
+            return;
+        }
+        if self.app_state.clickhouse_service.is_none() || self.app_state.postgres_service.is_none()
+        {
+            warn!("Source comparison needs ClickHouse and PostgreSQL; not starting");
+            return;
+        }
+
+        info!(
+            "Starting source comparison scheduler: {} candles, {} days back",
+            config.resolution.as_str(),
+            config.lookback_days
+        );
+
+        ScheduledJob::new(SOURCE_COMPARISON_JOB, &config.schedule).spawn(
+            self.app_state.clone(),
+            |_| true,
+            |app_state| async move {
+                let Some(comparison) = CandleComparison::new(app_state.clone()) else {
+                    return;
+                };
+                let ledger = app_state.job_ledger();
+
+                let mut run = ledger.start(SOURCE_COMPARISON_JOB).await;
+                let report = comparison.run(&run.run_id, chrono::Utc::now()).await;
+
+                // The ledger counts compared candles as received and divergences as inserted rows;
+                // instruments outside MOEX are not counted
+                run.instruments_total =
+                    (report.instruments_checked + report.instruments_failed) as i64;
+                run.instruments_failed = report.instruments_failed as i64;
+                run.candles_received = report.candles_compared as i64;
+                run.candles_inserted = report.divergences as i64;
+                let error = (report.instruments_failed > 0).then(|| {
+                    format!(
+                        "{} instruments could not be compared with MOEX ISS",
+                        report.instruments_failed
+                    )
+                });
+                if app_state.shutdown.is_cancelled() {
+                    ledger.cancel(&mut run).await;
+                } else {
+                    ledger.finish(&mut run, error).await;
+                }
+            },
+        );
+    }
+}
@@ -0,0 +1,2 @@
+pub mod candle_comparison;
+pub mod comparison_scheduler;
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use std::fmt;
+
+use crate::env_config::models::retention::CandleResolution;
+use crate::generate::tinkoff_public_invest_api_contract_v1::{HistoricCandle, Share};
+
+/// Источник свечей и каталога инструментов для загрузчика
+///
+/// Свечи возвращаются в формате Tinkoff, поэтому проверка, карантин, хранилище
+/// и контрольные точки одинаковы для всех источников
+#[async_trait]
+pub trait CandleSource {
+    /// Свечи `resolution` инструмента за период [from, to) (секунды) по возрастанию времени
+    async fn get_candles(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+        resolution: CandleResolution,
+    ) -> Result<Vec<HistoricCandle>, SourceError>;
+
+    /// Каталог акций источника
+    async fn get_shares(&self) -> Result<Vec<Share>, SourceError>;
+
+    /// Текущее время источника в секундах; записи трафика подменяют его временем записи
+    fn now(&self) -> i64 {
+        Utc::now().timestamp()
+    }
+
+    /// Имя источника для логов
+    fn source_name(&self) -> &'static str;
+}
+
+/// Ошибка источника свечей независимо от протокола
+#[derive(Debug)]
+pub enum SourceError {
+    Grpc(Box<tonic::Status>),
+    Http(reqwest::Error),
+    InvalidResponse(String), // Ответ не удалось разобрать
+    Unsupported(String),     // Источник не умеет выполнить запрос
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceError::Grpc(e) => write!(f, "gRPC error: {}", e),
+            SourceError::Http(e) => write!(f, "HTTP error: {}", e),
+            SourceError::InvalidResponse(e) => write!(f, "Invalid response: {}", e),
+            SourceError::Unsupported(e) => write!(f, "Unsupported request: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SourceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SourceError::Grpc(e) => Some(e.as_ref()),
+            SourceError::Http(e) => Some(e),
+            SourceError::InvalidResponse(_) | SourceError::Unsupported(_) => None,
+        }
+    }
+}
+
+impl From<tonic::Status> for SourceError {
+    fn from(e: tonic::Status) -> Self {
+        SourceError::Grpc(Box::new(e))
+    }
+}
+
+impl From<reqwest::Error> for SourceError {
+    fn from(e: reqwest::Error) -> Self {
+        SourceError::Http(e)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod candle_source;
+pub mod moex_iss_source;
+pub mod tinkoff_source;
@@ -0,0 +1,327 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Europe::Moscow;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::debug;
+
+use super::candle_source::{CandleSource, SourceError};
+use crate::db::clickhouse::models::share_listing::DbShareListing;
+use crate::env_config::models::retention::CandleResolution;
+use crate::env_config::models::source_comparison::MoexIssConfig;
+use crate::generate::tinkoff_public_invest_api_contract_v1::{HistoricCandle, Share};
+use crate::services::shares::models::quotation::f64_to_quotation;
+
+const ISS_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Таблица ответа ISS: имена колонок и строки значений в том же порядке
+#[derive(Debug, Deserialize)]
+struct IssTable {
+    columns: Vec<String>,
+    data: Vec<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssCandlesResponse {
+    candles: IssTable,
+}
+
+/// Время ISS (Москва) в секундах UTC
+fn parse_iss_time(value: &str) -> Result<DateTime<Utc>, SourceError> {
+    let naive = NaiveDateTime::parse_from_str(value, ISS_TIME_FORMAT)
+        .map_err(|e| SourceError::InvalidResponse(format!("bad time {}: {}", value, e)))?;
+    Moscow
+        .from_local_datetime(&naive)
+        .single()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| SourceError::InvalidResponse(format!("ambiguous time {}", value)))
+}
+
+fn format_iss_time(seconds: i64) -> String {
+    DateTime::from_timestamp(seconds, 0)
+        .unwrap_or_default()
+        .with_timezone(&Moscow)
+        .format(ISS_TIME_FORMAT)
+        .to_string()
+}
+
+/// Свечи страницы ISS в формате Tinkoff
+///
+/// Объём ISS считается в бумагах и переводится в лоты; дневная свеча получает
+/// начало суток UTC той же даты, как в таблицах свёрток
+fn parse_candles(
+    table: &IssTable,
+    resolution: CandleResolution,
+    lot: i32,
+    now: i64,
+) -> Result<Vec<HistoricCandle>, SourceError> {
+    let column = |name: &str| {
+        table
+            .columns
+            .iter()
+            .position(|c| c == name)
+            .ok_or_else(|| SourceError::InvalidResponse(format!("no {} column", name)))
+    };
+    let (open, close, high, low) = (
+        column("open")?,
+        column("close")?,
+        column("high")?,
+        column("low")?,
+    );
+    let (volume, begin, end) = (column("volume")?, column("begin")?, column("end")?);
+
+    let lot = f64::from(lot.max(1));
+    table
+        .data
+        .iter()
+        .map(|row| {
+            let number = |index: usize| {
+                row.get(index)
+                    .and_then(serde_json::Value::as_f64)
+                    .ok_or_else(|| SourceError::InvalidResponse(format!("bad row {:?}", row)))
+            };
+            let text = |index: usize| {
+                row.get(index)
+                    .and_then(serde_json::Value::as_str)
+                    .ok_or_else(|| SourceError::InvalidResponse(format!("bad row {:?}", row)))
+            };
+
+            let begin = parse_iss_time(text(begin)?)?;
+            let time = if resolution == CandleResolution::Day {
+                begin
+                    .with_timezone(&Moscow)
+                    .date_naive()
+                    .and_time(Default::default())
+                    .and_utc()
+                    .timestamp()
+            } else {
+                begin.timestamp()
+            };
+            Ok(HistoricCandle {
+                open: Some(f64_to_quotation(number(open)?)),
+                high: Some(f64_to_quotation(number(high)?)),
+                low: Some(f64_to_quotation(number(low)?)),
+                close: Some(f64_to_quotation(number(close)?)),
+                volume: (number(volume)? / lot).round() as i64,
+                time: Some(prost_types::Timestamp {
+                    seconds: time,
+                    nanos: 0,
+                }),
+                is_complete: parse_iss_time(text(end)?)?.timestamp() < now,
+            })
+        })
+        .collect()
+}
+
+/// Свечи MOEX ISS для акций Московской биржи
+///
+/// Бумага ищется по ticker и class_code (режиму торгов) из tinkoff_shares,
+/// поэтому свечи запрашиваются по instrument_uid Tinkoff, как у основного источника
+pub struct MoexIssSource {
+    client: reqwest::Client,
+    base_url: String,
+    listings: HashMap<String, DbShareListing>, // По uid
+}
+
+impl MoexIssSource {
+    pub fn new(config: &MoexIssConfig, listings: Vec<DbShareListing>) -> Result<Self, SourceError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout))
+            .build()?;
+        Ok(Self {
+            client,
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            listings: listings
+                .into_iter()
+                .map(|listing| (listing.uid.clone(), listing))
+                .collect(),
+        })
+    }
+
+    pub fn listing(&self, instrument_uid: &str) -> Option<&DbShareListing> {
+        self.listings.get(instrument_uid)
+    }
+
+    async fn get_page(
+        &self,
+        listing: &DbShareListing,
+        query: &[(&str, String)],
+        start: usize,
+    ) -> Result<IssTable, SourceError> {
+        let url = format!(
+            "{}/iss/engines/stock/markets/shares/boards/{}/securities/{}/candles.json",
+            self.base_url, listing.class_code, listing.ticker
+        );
+        let response = self
+            .client
+            .get(&url)
+            .query(query)
+            .query(&[
+                ("start", start.to_string()),
+                ("iss.meta", "off".to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?;
+        let body: IssCandlesResponse = response
+            .json()
+            .await
+            .map_err(|e| SourceError::InvalidResponse(e.to_string()))?;
+        Ok(body.candles)
+    }
+}
+
+#[async_trait]
+impl CandleSource for MoexIssSource {
+    async fn get_candles(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+        resolution: CandleResolution,
+    ) -> Result<Vec<HistoricCandle>, SourceError> {
+        let interval = match resolution {
+            CandleResolution::Minute => "1",
+            CandleResolution::Hour => "60",
+            CandleResolution::Day => "24",
+            CandleResolution::FiveMinutes => {
+                return Err(SourceError::Unsupported(
+                    "MOEX ISS has no 5min candles".to_string(),
+                ));
+            }
+        };
+        let Some(listing) = self.listing(instrument_uid) else {
+            return Err(SourceError::Unsupported(format!(
+                "{} is not listed on MOEX",
+                instrument_uid
+            )));
+        };
+
+        // till в ISS включает границу
+        let query = [
+            ("from", format_iss_time(from)),
+            ("till", format_iss_time(to - 1)),
+            ("interval", interval.to_string()),
+        ];
+        let now = self.now();
+        let mut candles = Vec::new();
+        loop {
+            let page = self.get_page(listing, &query, candles.len()).await?;
+            if page.data.is_empty() {
+                break;
+            }
+            candles.extend(parse_candles(&page, resolution, listing.lot, now)?);
+        }
+        debug!(
+            "MOEX ISS returned {} {} candles of {}",
+            candles.len(),
+            resolution.as_str(),
+            listing.ticker
+        );
+
+        let first = resolution.bucket_start(from);
+        candles.retain(|candle| {
+            candle
+                .time
+                .as_ref()
+                .is_some_and(|time| time.seconds >= first && time.seconds < to)
+        });
+        Ok(candles)
+    }
+
+    async fn get_shares(&self) -> Result<Vec<Share>, SourceError> {
+        Err(SourceError::Unsupported(
+            "MOEX ISS does not provide the Tinkoff share catalog".to_string(),
+        ))
+    }
+
+    fn source_name(&self) -> &'static str {
+        "moex_iss"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        extract::{Path, Query},
+        routing::get,
+    };
+    use chrono::TimeZone;
+
+    /// Локальная замена ISS: отдаёт записанные страницы fixtures/moex_iss/<board>_<ticker>_<interval>_<start>.json
+    async fn serve_recorded(
+        Path((board, ticker)): Path<(String, String)>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> String {
+        let file = format!(
+            "fixtures/moex_iss/{}_{}_{}_{}.json",
+            board,
+            ticker,
+            query["interval"],
+            query.get("start").map_or("0", String::as_str)
+        );
+        std::fs::read_to_string(file).unwrap_or_else(|_| {
+            r#"{"candles": {"columns": ["open", "close", "high", "low", "value", "volume", "begin", "end"], "data": []}}"#
+                .to_string()
+        })
+    }
+
+    async fn spawn_stand_in() -> String {
+        let router = Router::new().route(
+            "/iss/engines/stock/markets/shares/boards/{board}/securities/{ticker}/candles.json",
+            get(serve_recorded),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        format!("http://{}/", address)
+    }
+
+    #[tokio::test]
+    async fn reads_recorded_iss_pages() {
+        let config = MoexIssConfig {
+            base_url: spawn_stand_in().await,
+            timeout: 5,
+        };
+        let listing = DbShareListing {
+            uid: "uid-sber".to_string(),
+            ticker: "SBER".to_string(),
+            class_code: "TQBR".to_string(),
+            lot: 10,
+        };
+        let source = MoexIssSource::new(&config, vec![listing]).unwrap();
+
+        // 2025-06-02 07:00..12:00 MSK
+        let from = Utc
+            .with_ymd_and_hms(2025, 6, 2, 4, 0, 0)
+            .unwrap()
+            .timestamp();
+        let to = Utc
+            .with_ymd_and_hms(2025, 6, 2, 9, 0, 0)
+            .unwrap()
+            .timestamp();
+        let candles = source
+            .get_candles("uid-sber", from, to, CandleResolution::Hour)
+            .await
+            .unwrap();
+
+        // Both recorded pages, read until ISS returns an empty one
+        let times: Vec<i64> = candles.iter().map(|c| c.time.unwrap().seconds).collect();
+        assert_eq!(times, (0..5).map(|h| from + h * 3600).collect::<Vec<_>>());
+        let first = &candles[0];
+        assert_eq!(first.open.unwrap(), f64_to_quotation(310.0));
+        assert_eq!(first.close.unwrap(), f64_to_quotation(311.47));
+        assert_eq!(first.volume, 12_345);
+        assert!(first.is_complete);
+
+        assert!(matches!(
+            source
+                .get_candles("uid-other", from, to, CandleResolution::Hour)
+                .await,
+            Err(SourceError::Unsupported(_))
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tonic::Status;
+
+use super::candle_source::{CandleSource, SourceError};
+use crate::env_config::models::retention::CandleResolution;
+use crate::generate::tinkoff_public_invest_api_contract_v1::{
+    CandleInterval, GetCandlesRequest, HistoricCandle, InstrumentStatus, InstrumentsRequest, Share,
+};
+use crate::services::tinkoff_client_grpc::TinkoffClient;
+
+/// Свечи и каталог из Tinkoff Invest API
+pub struct TinkoffCandleSource {
+    client: Arc<TinkoffClient>,
+}
+
+impl TinkoffCandleSource {
+    pub fn new(client: Arc<TinkoffClient>) -> Self {
+        Self { client }
+    }
+
+    fn request<T>(&self, message: T) -> Result<tonic::Request<T>, SourceError> {
+        self.client
+            .create_request(message)
+            .map_err(|e| Status::internal(e.to_string()).into())
+    }
+}
+
+#[async_trait]
+impl CandleSource for TinkoffCandleSource {
+    async fn get_candles(
+        &self,
+        instrument_uid: &str,
+        from: i64,
+        to: i64,
+        resolution: CandleResolution,
+    ) -> Result<Vec<HistoricCandle>, SourceError> {
+        let interval = match resolution {
+            CandleResolution::Minute => CandleInterval::CandleInterval1Min,
+            CandleResolution::FiveMinutes => CandleInterval::CandleInterval5Min,
+            CandleResolution::Hour => CandleInterval::Hour,
+            CandleResolution::Day => CandleInterval::Day,
+        };
+        let request = GetCandlesRequest {
+            from: Some(prost_types::Timestamp {
+                seconds: from,
+                nanos: 0,
+            }),
+            to: Some(prost_types::Timestamp {
+                seconds: to,
+                nanos: 0,
+            }),
+            instrument_id: instrument_uid.to_string(),
+            interval: interval as i32,
+            ..Default::default()
+        };
+
+        let mut market_data_client = self.client.market_data.clone();
+        let response = market_data_client
+            .get_candles(self.request(request)?)
+            .await?;
+        Ok(response.into_inner().candles)
+    }
+
+    async fn get_shares(&self) -> Result<Vec<Share>, SourceError> {
+        let request = InstrumentsRequest {
+            instrument_status: InstrumentStatus::All as i32,
+        };
+
+        let mut instruments_client = self.client.instruments.clone();
+        let response = instruments_client.shares(self.request(request)?).await?;
+        Ok(response.into_inner().instruments)
+    }
+
+    fn now(&self) -> i64 {
+        self.client.now()
+    }
+
+    fn source_name(&self) -> &'static str {
+        "tinkoff"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::tinkoff_mock::{spawn_test_mock, test_api};
+
+    #[tokio::test]
+    async fn reads_candles_and_shares_through_the_trait() {
+        let (_, base_url, shutdown) = spawn_test_mock(serde_json::json!({
+            "shares": [{ "uid": "uid-1", "figi": "FIGI1", "ticker": "AAA" }],
+            "candles": [
+                { "instrument_uid": "uid-1", "time": 60, "open": 1, "high": 1, "low": 1, "close": 1, "volume": 5 },
+                { "instrument_uid": "uid-1", "interval": "1day", "time": 0, "open": 1, "high": 1, "low": 1, "close": 1, "volume": 5 }
+            ]
+        }))
+        .await;
+        let client = Arc::new(
+            TinkoffClient::connect(&test_api(base_url), vec!["token".to_string()])
+                .await
+                .unwrap(),
+        );
+        let source: Arc<dyn CandleSource + Send + Sync> =
+            Arc::new(TinkoffCandleSource::new(client));
+
+        let minutes = source
+            .get_candles("uid-1", 0, 3600, CandleResolution::Minute)
+            .await
+            .unwrap();
+        let days = source
+            .get_candles("uid-1", 0, 86_400, CandleResolution::Day)
+            .await
+            .unwrap();
+        assert_eq!((minutes.len(), days.len()), (1, 1));
+        assert_eq!(days[0].time.unwrap().seconds, 0);
+        assert_eq!(source.get_shares().await.unwrap()[0].ticker, "AAA");
+
+        let error = source
+            .get_candles("uid-1", 0, 3 * 86_400, CandleResolution::Minute)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, SourceError::Grpc(status) if status.code() == tonic::Code::InvalidArgument)
+        );
+        shutdown.cancel();
+    }
+}
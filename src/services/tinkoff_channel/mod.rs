@@ -0,0 +1 @@
+pub mod resilient_channel;
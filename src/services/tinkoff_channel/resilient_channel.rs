@@ -0,0 +1,443 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tonic::body::BoxBody;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint};
+use tower_service::Service;
+use tracing::{info, warn};
+
+use crate::env_config::models::app_config::TinkoffApiConfig;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type ResponseFuture =
+    Pin<Box<dyn Future<Output = Result<http::Response<BoxBody>, BoxError>> + Send>>;
+
+/// Ошибка вместе с причинами: у ошибок транспорта tonic подробности только в source()
+fn error_chain(error: &(dyn std::error::Error + 'static)) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message = format!("{}: {}", message, cause);
+        source = cause.source();
+    }
+    message
+}
+
+/// Отказ подключения к адресу, а не ответа на отдельный запрос
+///
+/// Только такие ошибки переключают адрес: истёкший таймаут запроса говорит
+/// о медленном ответе, а не о недоступном адресе
+fn is_connection_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(cause) = source {
+        if cause.is::<tonic::TimeoutExpired>() {
+            return false;
+        }
+        if cause.is::<tonic::ConnectError>() || cause.is::<std::io::Error>() {
+            return true;
+        }
+        source = cause.source();
+    }
+    false
+}
+
+/// Состояние адреса Tinkoff API для /ready
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStatus {
+    pub url: String,
+    pub active: bool,
+    pub healthy: Option<bool>, // None — адрес ещё не проверялся и не использовался
+    pub last_error: Option<String>,
+    pub checked_at: Option<i64>, // Секунды UTC
+}
+
+struct EndpointState {
+    url: String,
+    endpoint: Endpoint,
+    healthy: Option<bool>,
+    last_error: Option<String>,
+    checked_at: Option<i64>,
+}
+
+impl EndpointState {
+    fn record(&mut self, result: Result<(), String>) {
+        self.healthy = Some(result.is_ok());
+        self.checked_at = Some(Utc::now().timestamp());
+        if let Err(e) = result {
+            self.last_error = Some(e);
+        }
+    }
+}
+
+/// Доступность адресов и адрес, через который идут запросы
+///
+/// Ошибка транспорта на активном адресе переключает запросы на следующий адрес;
+/// фоновая проверка возвращает их на первый доступный адрес в порядке конфигурации
+pub struct ChannelHealth {
+    active: AtomicUsize,
+    endpoints: Mutex<Vec<EndpointState>>,
+}
+
+impl ChannelHealth {
+    fn active(&self) -> usize {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// Учитывает результат запроса или проверки адреса `index`
+    fn report(&self, index: usize, result: Result<(), String>) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let failed = result.is_err();
+        endpoints[index].record(result);
+
+        if failed && index == self.active() && endpoints.len() > 1 {
+            // Next URL that has not failed yet; all of them failed — simply the next one
+            let count = endpoints.len();
+            let next = (1..count)
+                .map(|offset| (index + offset) % count)
+                .find(|&i| endpoints[i].healthy != Some(false))
+                .unwrap_or((index + 1) % count);
+            if self
+                .active
+                .compare_exchange(index, next, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                warn!(
+                    "Tinkoff API {} is unreachable ({}), switching to {}",
+                    endpoints[index].url,
+                    endpoints[index].last_error.as_deref().unwrap_or_default(),
+                    endpoints[next].url
+                );
+            }
+        }
+    }
+
+    /// Проверяет все адреса новым подключением и выбирает первый доступный
+    async fn probe(&self) {
+        let endpoints: Vec<Endpoint> = self
+            .endpoints
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|state| state.endpoint.clone())
+            .collect();
+        for (index, endpoint) in endpoints.into_iter().enumerate() {
+            let result = endpoint
+                .connect()
+                .await
+                .map(|_| ())
+                .map_err(|e| error_chain(&e));
+            self.endpoints.lock().unwrap()[index].record(result);
+        }
+
+        let endpoints = self.endpoints.lock().unwrap();
+        let active = self.active();
+        let Some(preferred) = endpoints.iter().position(|s| s.healthy == Some(true)) else {
+            return;
+        };
+        if preferred != active
+            && self
+                .active
+                .compare_exchange(active, preferred, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        {
+            info!(
+                "Tinkoff API requests switched from {} to {}",
+                endpoints[active].url, endpoints[preferred].url
+            );
+        }
+    }
+
+    /// Запросы идут через адрес, который не отказал при последней проверке
+    pub fn is_ready(&self) -> bool {
+        self.endpoints.lock().unwrap()[self.active()].healthy != Some(false)
+    }
+
+    pub fn statuses(&self) -> Vec<EndpointStatus> {
+        let active = self.active();
+        self.endpoints
+            .lock()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .map(|(index, state)| EndpointStatus {
+                url: state.url.clone(),
+                active: index == active,
+                healthy: state.healthy,
+                last_error: state.last_error.clone(),
+                checked_at: state.checked_at,
+            })
+            .collect()
+    }
+}
+
+/// Канал к Tinkoff API с ленивым подключением и переключением между адресами
+///
+/// Подключение устанавливается при первом запросе и восстанавливается каналом tonic
+/// после обрыва, поэтому недоступный API не мешает запуску
+#[derive(Clone)]
+pub struct ResilientChannel {
+    channels: Vec<Channel>, // По адресам: base_url, затем fallback_urls
+    health: Arc<ChannelHealth>,
+    // Канал, который опрашивается poll_ready; call уходит в него же, даже если
+    // активный адрес успел смениться, иначе резерв в его буфере не освободится
+    ready: Option<usize>,
+}
+
+impl ResilientChannel {
+    pub fn connect(api: &TinkoffApiConfig) -> Result<Self, String> {
+        let ca = match &api.ca_cert {
+            Some(path) => {
+                Some(Certificate::from_pem(std::fs::read(path).map_err(|e| {
+                    format!("Failed to read CA certificate {}: {}", path, e)
+                })?))
+            }
+            None => None,
+        };
+
+        let mut endpoints = Vec::new();
+        for (index, url) in std::iter::once(&api.base_url)
+            .chain(&api.fallback_urls)
+            .enumerate()
+        {
+            // The configured domain belongs to base_url; fallbacks use their own host
+            let domain = (index == 0).then_some(api.domain.as_str());
+            endpoints.push(EndpointState {
+                url: url.clone(),
+                endpoint: endpoint(url, domain, ca.clone(), api)?,
+                healthy: None,
+                last_error: None,
+                checked_at: None,
+            });
+        }
+
+        let channels = endpoints
+            .iter()
+            .map(|state| state.endpoint.connect_lazy())
+            .collect();
+        let health = Arc::new(ChannelHealth {
+            active: AtomicUsize::new(0),
+            endpoints: Mutex::new(endpoints),
+        });
+        if api.health_check_seconds > 0 {
+            spawn_health_checks(
+                Arc::downgrade(&health),
+                Duration::from_secs(api.health_check_seconds),
+            );
+        }
+
+        Ok(Self {
+            channels,
+            health,
+            ready: None,
+        })
+    }
+
+    pub fn health(&self) -> Arc<ChannelHealth> {
+        self.health.clone()
+    }
+}
+
+fn endpoint(
+    url: &str,
+    domain: Option<&str>,
+    ca: Option<Certificate>,
+    api: &TinkoffApiConfig,
+) -> Result<Endpoint, String> {
+    let mut endpoint = Channel::from_shared(url.to_string())
+        .map_err(|e| format!("Invalid Tinkoff API URL {}: {}", url, e))?;
+
+    if !url.starts_with("http://") {
+        let domain = domain
+            .or_else(|| endpoint.uri().host())
+            .unwrap_or_default()
+            .to_string();
+        let tls = ClientTlsConfig::new().domain_name(domain);
+        let tls = match ca {
+            Some(ca) => tls.ca_certificate(ca),
+            None => tls.with_enabled_roots(),
+        };
+        endpoint = endpoint
+            .tls_config(tls)
+            .map_err(|e| format!("TLS configuration of {} failed: {}", url, e))?;
+    }
+
+    Ok(endpoint
+        .tcp_keepalive(Some(Duration::from_secs(api.keepalive)))
+        .connect_timeout(Duration::from_secs(api.timeout))
+        .timeout(Duration::from_secs(api.timeout)))
+}
+
+/// Периодическая проверка адресов; завершается вместе с каналом
+fn spawn_health_checks(health: Weak<ChannelHealth>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let Some(health) = health.upgrade() else {
+                break;
+            };
+            health.probe().await;
+        }
+    });
+}
+
+impl Service<http::Request<BoxBody>> for ResilientChannel {
+    type Response = http::Response<BoxBody>;
+    type Error = BoxError;
+    type Future = ResponseFuture;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let index = *self.ready.get_or_insert_with(|| self.health.active());
+        self.channels[index].poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
+        let index = self
+            .ready
+            .take()
+            .expect("poll_ready must be called before call");
+        let future = self.channels[index].call(request);
+        let health = self.health.clone();
+        Box::pin(async move {
+            match future.await {
+                Ok(response) => {
+                    health.report(index, Ok(()));
+                    Ok(response)
+                }
+                Err(e) => {
+                    if is_connection_error(&e) {
+                        health.report(index, Err(error_chain(&e)));
+                    }
+                    Err(e.into())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env_config::models::app_config::TinkoffApiConfig;
+    use crate::generate::tinkoff_public_invest_api_contract_v1::InstrumentsRequest;
+    use crate::services::tinkoff_client_grpc::TinkoffClient;
+    use crate::services::tinkoff_mock::{
+        fixtures::MockFixtures, mock_server::MockTinkoff, test_api,
+    };
+    use std::net::SocketAddr;
+    use tokio_util::sync::CancellationToken;
+
+    async fn spawn_mock(address: SocketAddr, shutdown: &CancellationToken) -> SocketAddr {
+        let fixtures: MockFixtures = serde_json::from_str(
+            r#"{ "shares": [{ "uid": "uid-1", "figi": "FIGI1", "ticker": "AAA" }] }"#,
+        )
+        .unwrap();
+        MockTinkoff::new(fixtures)
+            .spawn(address, shutdown.clone())
+            .await
+            .unwrap()
+    }
+
+    fn api(primary: SocketAddr, fallback: SocketAddr) -> TinkoffApiConfig {
+        TinkoffApiConfig {
+            fallback_urls: vec![format!("http://{}", fallback)],
+            ..test_api(format!("http://{}", primary))
+        }
+    }
+
+    #[tokio::test]
+    async fn fails_over_and_returns_to_the_primary() {
+        // Nothing listens on the primary address yet
+        let primary = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let shutdown = CancellationToken::new();
+        let fallback = spawn_mock("127.0.0.1:0".parse().unwrap(), &shutdown).await;
+
+        let api = api(primary, fallback);
+        // The unreachable API does not prevent the client from being created
+        let client = TinkoffClient::connect(&api, vec!["token".to_string()])
+            .await
+            .unwrap();
+        let health = client.health.clone().unwrap();
+        let shares = |client: TinkoffClient| async move {
+            let request = client
+                .create_request(InstrumentsRequest::default())
+                .unwrap();
+            client.instruments.clone().shares(request).await
+        };
+
+        let error = shares(client.clone()).await.unwrap_err();
+        assert_eq!(error.code(), tonic::Code::Unavailable);
+        assert!(shares(client.clone()).await.is_ok());
+        let statuses = health.statuses();
+        assert_eq!(statuses[0].healthy, Some(false));
+        assert!(statuses[1].active && statuses[1].healthy == Some(true));
+        assert!(health.is_ready());
+
+        spawn_mock(primary, &shutdown).await;
+        health.probe().await;
+        assert!(health.statuses()[0].active);
+        assert!(shares(client.clone()).await.is_ok());
+        shutdown.cancel();
+    }
+
+    #[tokio::test]
+    async fn keeps_the_endpoint_when_a_request_times_out() {
+        // The primary accepts connections but never answers
+        let silent = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let primary = silent.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((socket, _)) = silent.accept().await {
+                sockets.push(socket);
+            }
+        });
+        let shutdown = CancellationToken::new();
+        let fallback = spawn_mock("127.0.0.1:0".parse().unwrap(), &shutdown).await;
+
+        let mut api = api(primary, fallback);
+        api.timeout = 1;
+        let client = TinkoffClient::connect(&api, vec!["token".to_string()])
+            .await
+            .unwrap();
+        let request = client
+            .create_request(InstrumentsRequest::default())
+            .unwrap();
+        assert!(client.instruments.clone().shares(request).await.is_err());
+
+        let statuses = client.health.unwrap().statuses();
+        assert!(statuses[0].active);
+        assert_ne!(statuses[0].healthy, Some(false));
+        shutdown.cancel();
+    }
+
+    #[tokio::test]
+    async fn calls_the_channel_that_was_polled() {
+        let unreachable = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut channel = ResilientChannel::connect(&api(unreachable, unreachable)).unwrap();
+
+        std::future::poll_fn(|cx| channel.poll_ready(cx))
+            .await
+            .unwrap();
+        // A failover after poll_ready does not move the reserved request
+        channel.health.report(0, Err("refused".to_string()));
+        assert_eq!(channel.health.active(), 1);
+        std::future::poll_fn(|cx| channel.poll_ready(cx))
+            .await
+            .unwrap();
+        assert_eq!(channel.ready, Some(0));
+
+        drop(channel.call(http::Request::new(tonic::body::empty_body())));
+        assert_eq!(channel.ready, None);
+    }
+}
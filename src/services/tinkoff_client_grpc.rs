@@ -1,80 +1,117 @@
+use crate::env_config::models::app_config::{TinkoffApiConfig, TrafficMode};
 use crate::env_config::models::app_setting::AppSettings;
 use crate::generate::tinkoff_public_invest_api_contract_v1::market_data_stream_service_client::MarketDataStreamServiceClient;
 use crate::generate::tinkoff_public_invest_api_contract_v1::{
     instruments_service_client::InstrumentsServiceClient,
     market_data_service_client::MarketDataServiceClient,
-    operations_service_client::OperationsServiceClient, users_service_client::UsersServiceClient,
 };
+use crate::services::tinkoff_channel::resilient_channel::{ChannelHealth, ResilientChannel};
+use crate::services::tinkoff_tokens::{quota_channel::QuotaChannel, token_pool::TokenPool};
+use crate::services::tinkoff_traffic::{
+    traffic_channel::TrafficChannel, traffic_store::TrafficStore,
+};
+use chrono::Utc;
 use rustls::crypto::aws_lc_rs;
+use tracing::info;
 
-use std::io::Result;
-use std::{sync::Arc, time::Duration};
-use tonic::{
-    Request,
-    metadata::MetadataValue,
-    transport::{Channel, ClientTlsConfig},
-};
+use std::io::{Error, ErrorKind, Result};
+use std::sync::Arc;
+use tonic::{Request, metadata::MetadataValue};
 
 #[derive(Clone)]
 pub struct TinkoffClient {
-    pub instruments: InstrumentsServiceClient<Channel>,
-    pub market_data: MarketDataServiceClient<Channel>,
-    pub market_data_stream: MarketDataStreamServiceClient<Channel>,
-    pub operations: OperationsServiceClient<Channel>,
-    pub users: UsersServiceClient<Channel>,
-    pub token: String,
+    pub instruments: InstrumentsServiceClient<QuotaChannel>,
+    pub market_data: MarketDataServiceClient<QuotaChannel>,
+    pub market_data_stream: MarketDataStreamServiceClient<QuotaChannel>,
+    pub tokens: Arc<TokenPool>,
+    pub health: Option<Arc<ChannelHealth>>, // None при воспроизведении: сеть не используется
+    replay_clock: Option<i64>,              // Время записи, если ответы воспроизводятся
 }
 
 impl TinkoffClient {
     /// Создает новый экземпляр клиента с заданными настройками
     pub async fn new(settings: Arc<AppSettings>) -> Result<Self> {
-        // Инициализация криптографического провайдера
-        let provider = aws_lc_rs::default_provider();
-        provider.install_default().unwrap();
-
-        // Настройка TLS
-        let tls_config = ClientTlsConfig::new()
-            .domain_name(&settings.app_config.tinkoff_api.domain)
-            .with_enabled_roots();
-
-        // Создание канала с настроенной конфигурацией
-        let channel = Channel::from_shared(
-            settings
-                .app_config
-                .tinkoff_api
-                .base_url
-                .clone()
-                .into_bytes(),
+        Self::connect(
+            &settings.app_config.tinkoff_api,
+            settings.app_env.tinkoff_tokens(),
         )
-        .expect("Invalid URI format")
-        .tls_config(tls_config)
-        .expect("TLS configuration failed")
-        .tcp_keepalive(Some(Duration::from_secs(
-            settings.app_config.tinkoff_api.keepalive,
-        )))
-        .timeout(Duration::from_secs(settings.app_config.tinkoff_api.timeout))
-        .connect()
         .await
-        .expect("Failed to connect to gRPC server");
+    }
+
+    /// Создаёт клиента `api.base_url` с запасными адресами `api.fallback_urls`
+    ///
+    /// Соединение устанавливается при первом запросе, поэтому недоступный API
+    /// не мешает запуску. Адрес со схемой http:// (локальный mock-tinkoff) подключается без TLS.
+    /// В режиме replay сеть не используется: ответы берутся из `api.traffic.dir`.
+    /// Запросы распределяются между `tokens` по `api.token_selection`
+    pub async fn connect(api: &TinkoffApiConfig, tokens: Vec<String>) -> Result<Self> {
+        let tokens = Arc::new(TokenPool::new(tokens, api.token_selection));
+        if tokens.usage().is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "no Tinkoff tokens"));
+        }
+        let store = Arc::new(TrafficStore::new(&api.traffic));
+        let (channel, health, replay_clock) = match api.traffic.mode {
+            TrafficMode::Off => {
+                let network = Self::open_channel(api)?;
+                let health = network.health();
+                (TrafficChannel::network(network), Some(health), None)
+            }
+            TrafficMode::Record => {
+                store.start_session()?;
+                info!("Recording Tinkoff API traffic to {}", api.traffic.dir);
+                let network = Self::open_channel(api)?;
+                let health = network.health();
+                (TrafficChannel::record(network, store), Some(health), None)
+            }
+            TrafficMode::Replay => {
+                info!("Replaying Tinkoff API traffic from {}", api.traffic.dir);
+                let clock = store.recorded_at();
+                (TrafficChannel::replay(store), None, clock)
+            }
+        };
+        let channel = QuotaChannel::new(channel, tokens.clone());
 
         Ok(Self {
             instruments: InstrumentsServiceClient::new(channel.clone()),
             market_data: MarketDataServiceClient::new(channel.clone()),
-            market_data_stream: MarketDataStreamServiceClient::new(channel.clone()),
-            operations: OperationsServiceClient::new(channel.clone()),
-            users: UsersServiceClient::new(channel.clone()),
-            token: settings.app_env.tinkoff_token.clone(),
+            market_data_stream: MarketDataStreamServiceClient::new(channel),
+            tokens,
+            health,
+            replay_clock,
         })
     }
 
-    /// Создает новый gRPC запрос с добавлением токена авторизации
+    /// Канал к `base_url` и `fallback_urls`; подключение откладывается до первого запроса
+    fn open_channel(api: &TinkoffApiConfig) -> Result<ResilientChannel> {
+        // Инициализация криптографического провайдера; повторная установка не нужна
+        let _ = aws_lc_rs::default_provider().install_default();
+
+        ResilientChannel::connect(api).map_err(|e| Error::new(ErrorKind::InvalidInput, e))
+    }
+
+    /// Текущее время в секундах; при воспроизведении — время начала записи,
+    /// чтобы загрузчик запрашивал те же периоды, что и при записи
+    pub fn now(&self) -> i64 {
+        self.replay_clock.unwrap_or_else(|| Utc::now().timestamp())
+    }
+
+    /// Создает новый gRPC запрос с токеном авторизации из пула
+    ///
+    /// Ошибка — все токены отключены после UNAUTHENTICATED
     pub fn create_request<T>(&self, request: T) -> Result<Request<T>> {
+        let (slot, token) = self.tokens.acquire().ok_or_else(|| {
+            Error::new(
+                ErrorKind::PermissionDenied,
+                "all Tinkoff tokens are disabled",
+            )
+        })?;
         let mut request = Request::new(request);
-        let auth_header_value = MetadataValue::try_from(&format!("Bearer {}", self.token))
-            .expect("Invalid token format");
+        let auth_header_value = MetadataValue::try_from(&format!("Bearer {}", token))
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
         request
             .metadata_mut()
             .insert("authorization", auth_header_value);
+        request.extensions_mut().insert(slot);
         Ok(request)
     }
 }
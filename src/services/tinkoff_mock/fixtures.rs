@@ -0,0 +1,265 @@
+use serde::Deserialize;
+use std::path::Path;
+use tonic::Code;
+
+use crate::env_config::models::retention::CandleResolution;
+use crate::generate::tinkoff_public_invest_api_contract_v1::{
+    Order, RealExchange, SecurityTradingStatus, Share, ShareType,
+};
+use crate::services::shares::models::quotation::f64_to_quotation;
+
+/// Методы, ответы которых можно подменить ошибкой
+pub const FAULT_METHODS: [&str; 5] = [
+    "Shares",
+    "ShareBy",
+    "GetCandles",
+    "MarketDataStream",
+    "MarketDataServerSideStream",
+];
+
+fn default_class_code() -> String {
+    "TQBR".to_string()
+}
+
+fn default_currency() -> String {
+    "rub".to_string()
+}
+
+fn default_exchange() -> String {
+    "MOEX".to_string()
+}
+
+fn default_lot() -> i32 {
+    1
+}
+
+fn default_min_price_increment() -> f64 {
+    0.01
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_depth() -> i32 {
+    10
+}
+
+fn default_stream_interval_ms() -> u64 {
+    1000
+}
+
+fn timestamp(seconds: i64) -> prost_types::Timestamp {
+    prost_types::Timestamp { seconds, nanos: 0 }
+}
+
+/// Акция из ответа Shares; не указанные поля заполняются как у обычной акции MOEX
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShareFixture {
+    pub uid: String,
+    pub figi: String,
+    pub ticker: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub isin: String,
+    #[serde(default = "default_class_code")]
+    pub class_code: String,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    #[serde(default = "default_exchange")]
+    pub exchange: String,
+    #[serde(default = "default_lot")]
+    pub lot: i32,
+    #[serde(default = "default_min_price_increment")]
+    pub min_price_increment: f64,
+    #[serde(default = "default_true")]
+    pub api_trade_available: bool,
+    pub first_1min_candle_date: Option<i64>, // Секунды
+    pub first_1day_candle_date: Option<i64>,
+}
+
+impl ShareFixture {
+    pub fn to_share(&self) -> Share {
+        Share {
+            uid: self.uid.clone(),
+            position_uid: self.uid.clone(),
+            figi: self.figi.clone(),
+            ticker: self.ticker.clone(),
+            name: self.name.clone(),
+            isin: self.isin.clone(),
+            class_code: self.class_code.clone(),
+            currency: self.currency.clone(),
+            exchange: self.exchange.clone(),
+            lot: self.lot,
+            min_price_increment: Some(f64_to_quotation(self.min_price_increment)),
+            api_trade_available_flag: self.api_trade_available,
+            buy_available_flag: true,
+            sell_available_flag: true,
+            liquidity_flag: true,
+            trading_status: SecurityTradingStatus::NormalTrading as i32,
+            share_type: ShareType::Common as i32,
+            real_exchange: RealExchange::Moex as i32,
+            country_of_risk: "RU".to_string(),
+            first_1min_candle_date: self.first_1min_candle_date.map(timestamp),
+            first_1day_candle_date: self.first_1day_candle_date.map(timestamp),
+            ..Default::default()
+        }
+    }
+}
+
+/// Свеча из ответа GetCandles
+#[derive(Debug, Clone, Deserialize)]
+pub struct CandleFixture {
+    pub instrument_uid: String,
+    #[serde(default = "default_interval")]
+    pub interval: CandleResolution,
+    pub time: i64, // Начало свечи, секунды
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+    #[serde(default = "default_true")]
+    pub is_complete: bool,
+}
+
+fn default_interval() -> CandleResolution {
+    CandleResolution::Minute
+}
+
+/// Стакан, который отдаётся подписчикам стрима
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderBookFixture {
+    pub instrument_uid: String,
+    #[serde(default)]
+    pub figi: String,
+    #[serde(default)]
+    pub bids: Vec<(f64, i64)>, // (цена, лоты), лучшая цена первой
+    #[serde(default)]
+    pub asks: Vec<(f64, i64)>,
+}
+
+impl OrderBookFixture {
+    pub fn orders(levels: &[(f64, i64)], depth: usize) -> Vec<Order> {
+        levels
+            .iter()
+            .take(depth)
+            .map(|&(price, quantity)| Order {
+                price: Some(f64_to_quotation(price)),
+                quantity,
+            })
+            .collect()
+    }
+}
+
+/// Код ошибки, которой отвечает подменённый вызов
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultCode {
+    Unavailable,
+    ResourceExhausted,
+    Internal,
+    InvalidArgument,
+    NotFound,
+    Unauthenticated,
+    DeadlineExceeded,
+}
+
+impl FaultCode {
+    pub fn code(&self) -> Code {
+        match self {
+            FaultCode::Unavailable => Code::Unavailable,
+            FaultCode::ResourceExhausted => Code::ResourceExhausted,
+            FaultCode::Internal => Code::Internal,
+            FaultCode::InvalidArgument => Code::InvalidArgument,
+            FaultCode::NotFound => Code::NotFound,
+            FaultCode::Unauthenticated => Code::Unauthenticated,
+            FaultCode::DeadlineExceeded => Code::DeadlineExceeded,
+        }
+    }
+}
+
+/// Ошибка, которой отвечают вызовы метода с номерами [after, after + times)
+///
+/// Номера считаются с нуля по каждому методу отдельно; без `times` ошибка
+/// возвращается на все вызовы после `after`
+#[derive(Debug, Clone, Deserialize)]
+pub struct FaultFixture {
+    pub method: String,
+    pub code: FaultCode,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub after: u32,
+    pub times: Option<u32>,
+    pub rate_limit_reset_seconds: Option<u32>, // Для resource_exhausted: заголовок x-ratelimit-reset
+}
+
+impl FaultFixture {
+    pub fn applies_to(&self, method: &str, call: u32) -> bool {
+        self.method == method
+            && call >= self.after
+            && self.times.is_none_or(|times| call - self.after < times)
+    }
+}
+
+/// Данные, которыми отвечает локальный сервер вместо Tinkoff
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockFixtures {
+    pub token: Option<String>, // Если задан, запросы без этого токена получают unauthenticated
+    #[serde(default)]
+    pub shares: Vec<ShareFixture>,
+    #[serde(default)]
+    pub candles: Vec<CandleFixture>,
+    #[serde(default)]
+    pub order_books: Vec<OrderBookFixture>,
+    #[serde(default)]
+    pub faults: Vec<FaultFixture>,
+    #[serde(default = "default_stream_interval_ms")]
+    pub stream_interval_ms: u64, // Как часто стрим присылает стаканы подписчикам
+    pub stream_disconnect_after: Option<u32>, // Разрыв стрима после стольких стаканов
+    #[serde(default = "default_depth")]
+    pub default_depth: i32,
+}
+
+impl MockFixtures {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read fixtures {}: {}", path.display(), e))?;
+        let fixtures: MockFixtures = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid fixtures {}: {}", path.display(), e))?;
+        fixtures.validate()?;
+        Ok(fixtures)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for fault in &self.faults {
+            if !FAULT_METHODS.contains(&fault.method.as_str()) {
+                return Err(format!(
+                    "Unknown fault method {} (expected one of {})",
+                    fault.method,
+                    FAULT_METHODS.join(", ")
+                ));
+            }
+        }
+        if self.stream_interval_ms == 0 {
+            return Err("stream_interval_ms must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::DEFAULT_MOCK_FIXTURES;
+
+    #[test]
+    fn bundled_fixtures_are_valid() {
+        let fixtures = MockFixtures::load(Path::new(DEFAULT_MOCK_FIXTURES)).unwrap();
+        assert!(!fixtures.shares.is_empty());
+        assert!(fixtures.faults[0].applies_to("GetCandles", 5));
+        assert!(!fixtures.faults[0].applies_to("GetCandles", 6));
+    }
+}
@@ -0,0 +1,665 @@
+// Handlers return tonic::Status by value, as the generated service traits do
+#![allow(clippy::result_large_err)]
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tokio_util::sync::CancellationToken;
+use tonic::metadata::MetadataValue;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{debug, info, warn};
+
+use super::fixtures::MockFixtures;
+use crate::env_config::models::retention::CandleResolution;
+use crate::generate::tinkoff_public_invest_api_contract_v1::{
+    self as contract, CandleInterval, GetCandlesRequest, GetCandlesResponse, HistoricCandle,
+    InstrumentRequest, InstrumentsRequest, MarketDataRequest, MarketDataResponse,
+    MarketDataServerSideStreamRequest, OrderBook, OrderBookSubscription, ShareResponse,
+    SharesResponse, SubscribeOrderBookRequest, SubscribeOrderBookResponse, SubscriptionAction,
+    SubscriptionStatus,
+    instruments_service_server::{InstrumentsService, InstrumentsServiceServer},
+    market_data_request, market_data_response,
+    market_data_service_server::{MarketDataService, MarketDataServiceServer},
+    market_data_stream_service_server::{MarketDataStreamService, MarketDataStreamServiceServer},
+};
+use crate::services::shares::models::quotation::f64_to_quotation;
+
+const DAY_SECONDS: i64 = 86_400;
+
+type StreamResult = Result<MarketDataResponse, Status>;
+
+/// Локальный сервер gRPC, отвечающий вместо Tinkoff данными из фикстур
+///
+/// Обслуживает Shares, ShareBy, GetCandles и стримы стаканов; остальные методы
+/// отвечают unimplemented. Ошибки и ограничения частоты задаются в `faults`
+#[derive(Clone)]
+pub struct MockTinkoff {
+    fixtures: Arc<MockFixtures>,
+    calls: Arc<Mutex<HashMap<&'static str, u32>>>,
+}
+
+impl MockTinkoff {
+    pub fn new(fixtures: MockFixtures) -> Self {
+        Self {
+            fixtures: Arc::new(fixtures),
+            calls: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Сколько раз вызывался метод, например "GetCandles"
+    #[cfg(test)]
+    pub fn calls(&self, method: &str) -> u32 {
+        let calls = self.calls.lock().unwrap();
+        calls.get(method).copied().unwrap_or(0)
+    }
+
+    /// Проверяет токен и отвечает ошибкой из `faults`, если она назначена на этот вызов
+    fn intercept<T>(&self, method: &'static str, request: &Request<T>) -> Result<(), Status> {
+        let call = {
+            let mut calls = self.calls.lock().unwrap();
+            let counter = calls.entry(method).or_insert(0);
+            *counter += 1;
+            *counter - 1
+        };
+
+        if let Some(token) = &self.fixtures.token {
+            let expected = format!("Bearer {}", token);
+            let authorization = request
+                .metadata()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok());
+            if authorization != Some(expected.as_str()) {
+                return Err(Status::unauthenticated("40003"));
+            }
+        }
+
+        let Some(fault) = self
+            .fixtures
+            .faults
+            .iter()
+            .find(|fault| fault.applies_to(method, call))
+        else {
+            return Ok(());
+        };
+
+        debug!(
+            "Mock Tinkoff: injecting {:?} into {} #{}",
+            fault.code, method, call
+        );
+        let mut status = Status::new(fault.code.code(), fault.message.clone());
+        if fault.code.code() == tonic::Code::ResourceExhausted {
+            let reset = fault.rate_limit_reset_seconds.unwrap_or(1);
+            let metadata = status.metadata_mut();
+            metadata.insert("x-ratelimit-limit", MetadataValue::from(0));
+            metadata.insert("x-ratelimit-remaining", MetadataValue::from(0));
+            metadata.insert("x-ratelimit-reset", MetadataValue::from(reset));
+        }
+        Err(status)
+    }
+
+    fn serve_share_by(&self, request: &InstrumentRequest) -> Result<ShareResponse, Status> {
+        self.fixtures
+            .shares
+            .iter()
+            .find(|share| [&share.uid, &share.figi, &share.ticker].contains(&&request.id))
+            .map(|share| ShareResponse {
+                instrument: Some(share.to_share()),
+            })
+            .ok_or_else(|| Status::not_found("50002"))
+    }
+
+    fn serve_candles(&self, request: &GetCandlesRequest) -> Result<GetCandlesResponse, Status> {
+        let resolution = match CandleInterval::try_from(request.interval) {
+            Ok(CandleInterval::CandleInterval1Min) => CandleResolution::Minute,
+            Ok(CandleInterval::CandleInterval5Min) => CandleResolution::FiveMinutes,
+            Ok(CandleInterval::Hour) => CandleResolution::Hour,
+            Ok(CandleInterval::Day) => CandleResolution::Day,
+            _ => {
+                return Err(Status::invalid_argument(format!(
+                    "interval {} is not served by the mock",
+                    request.interval
+                )));
+            }
+        };
+        let (Some(from), Some(to)) = (&request.from, &request.to) else {
+            return Err(Status::invalid_argument("30008"));
+        };
+        #[allow(deprecated)]
+        let instrument_id = if request.instrument_id.is_empty() {
+            &request.figi
+        } else {
+            &request.instrument_id
+        };
+        if instrument_id.is_empty() {
+            return Err(Status::invalid_argument("30008"));
+        }
+        // The real API allows at most one day of minute candles per request
+        if resolution == CandleResolution::Minute && to.seconds - from.seconds > DAY_SECONDS {
+            return Err(Status::invalid_argument("30014"));
+        }
+
+        let uid = self
+            .fixtures
+            .shares
+            .iter()
+            .find(|share| &share.figi == instrument_id)
+            .map_or(instrument_id.as_str(), |share| share.uid.as_str());
+        let mut candles: Vec<_> = self
+            .fixtures
+            .candles
+            .iter()
+            .filter(|candle| {
+                candle.instrument_uid == uid
+                    && candle.interval == resolution
+                    && candle.time >= from.seconds
+                    && candle.time < to.seconds
+            })
+            .collect();
+        candles.sort_by_key(|candle| candle.time);
+
+        Ok(GetCandlesResponse {
+            candles: candles
+                .into_iter()
+                .map(|candle| HistoricCandle {
+                    open: Some(f64_to_quotation(candle.open)),
+                    high: Some(f64_to_quotation(candle.high)),
+                    low: Some(f64_to_quotation(candle.low)),
+                    close: Some(f64_to_quotation(candle.close)),
+                    volume: candle.volume,
+                    time: Some(prost_types::Timestamp {
+                        seconds: candle.time,
+                        nanos: 0,
+                    }),
+                    is_complete: candle.is_complete,
+                })
+                .collect(),
+        })
+    }
+
+    /// Применяет (от)писку на стаканы и возвращает ответ на неё
+    fn subscribe_order_books(
+        &self,
+        request: &SubscribeOrderBookRequest,
+        subscriptions: &mut BTreeMap<String, i32>,
+    ) -> MarketDataResponse {
+        let subscribe = request.subscription_action == SubscriptionAction::Subscribe as i32;
+        let mut result = Vec::new();
+        for instrument in &request.instruments {
+            #[allow(deprecated)]
+            let id = if instrument.instrument_id.is_empty() {
+                &instrument.figi
+            } else {
+                &instrument.instrument_id
+            };
+            let book = self.fixtures.order_books.iter().find(|book| {
+                &book.instrument_uid == id || (!book.figi.is_empty() && &book.figi == id)
+            });
+            let depth = if instrument.depth > 0 {
+                instrument.depth
+            } else {
+                self.fixtures.default_depth
+            };
+
+            let status = match book {
+                None => SubscriptionStatus::InstrumentNotFound,
+                Some(book) if subscribe => {
+                    subscriptions.insert(book.instrument_uid.clone(), depth);
+                    SubscriptionStatus::Success
+                }
+                Some(book) => match subscriptions.remove(&book.instrument_uid) {
+                    Some(_) => SubscriptionStatus::Success,
+                    None => SubscriptionStatus::SubscriptionNotFound,
+                },
+            };
+            result.push(OrderBookSubscription {
+                figi: book.map(|book| book.figi.clone()).unwrap_or_default(),
+                depth,
+                subscription_status: status as i32,
+                instrument_uid: book.map_or(id.clone(), |book| book.instrument_uid.clone()),
+            });
+        }
+
+        MarketDataResponse {
+            payload: Some(market_data_response::Payload::SubscribeOrderBookResponse(
+                SubscribeOrderBookResponse {
+                    tracking_id: uuid::Uuid::new_v4().to_string(),
+                    order_book_subscriptions: result,
+                },
+            )),
+        }
+    }
+
+    fn order_book(&self, instrument_uid: &str, depth: i32) -> Option<MarketDataResponse> {
+        let book = self
+            .fixtures
+            .order_books
+            .iter()
+            .find(|book| book.instrument_uid == instrument_uid)?;
+        let now = Utc::now();
+        Some(MarketDataResponse {
+            payload: Some(market_data_response::Payload::Orderbook(OrderBook {
+                figi: book.figi.clone(),
+                depth,
+                is_consistent: true,
+                bids: super::fixtures::OrderBookFixture::orders(&book.bids, depth as usize),
+                asks: super::fixtures::OrderBookFixture::orders(&book.asks, depth as usize),
+                time: Some(prost_types::Timestamp {
+                    seconds: now.timestamp(),
+                    nanos: now.timestamp_subsec_nanos() as i32,
+                }),
+                instrument_uid: book.instrument_uid.clone(),
+                ..Default::default()
+            })),
+        })
+    }
+
+    /// Отдаёт стаканы подписанных инструментов каждые `stream_interval_ms`
+    ///
+    /// Для двунаправленного стрима подписки читаются из `inbound`, стрим закрывается
+    /// вместе с ним; серверный стрим получает подписку один раз в `initial`
+    fn spawn_order_book_stream(
+        &self,
+        initial: Option<SubscribeOrderBookRequest>,
+        mut inbound: Option<Streaming<MarketDataRequest>>,
+    ) -> ReceiverStream<StreamResult> {
+        let (sender, receiver) = mpsc::channel::<StreamResult>(16);
+        let mock = self.clone();
+
+        tokio::spawn(async move {
+            let mut subscriptions = BTreeMap::new();
+            if let Some(request) = &initial {
+                let response = mock.subscribe_order_books(request, &mut subscriptions);
+                if sender.send(Ok(response)).await.is_err() {
+                    return;
+                }
+            }
+
+            let mut interval =
+                tokio::time::interval(Duration::from_millis(mock.fixtures.stream_interval_ms));
+            let mut sent = 0u32;
+            loop {
+                let next_request = async {
+                    match inbound.as_mut() {
+                        Some(inbound) => inbound.message().await,
+                        None => std::future::pending().await,
+                    }
+                };
+
+                tokio::select! {
+                    message = next_request => match message {
+                        Ok(Some(request)) => {
+                            if let Some(market_data_request::Payload::SubscribeOrderBookRequest(
+                                request,
+                            )) = request.payload
+                            {
+                                let response = mock.subscribe_order_books(&request, &mut subscriptions);
+                                if sender.send(Ok(response)).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        Ok(None) | Err(_) => return,
+                    },
+                    _ = interval.tick() => {
+                        for (uid, depth) in &subscriptions {
+                            if mock
+                                .fixtures
+                                .stream_disconnect_after
+                                .is_some_and(|limit| sent >= limit)
+                            {
+                                let _ = sender
+                                    .send(Err(Status::unavailable("stream closed by the mock")))
+                                    .await;
+                                return;
+                            }
+                            let Some(book) = mock.order_book(uid, *depth) else {
+                                continue;
+                            };
+                            if sender.send(Ok(book)).await.is_err() {
+                                return;
+                            }
+                            sent += 1;
+                        }
+                    }
+                }
+            }
+        });
+
+        ReceiverStream::new(receiver)
+    }
+
+    /// Запускает сервер на `listener` и работает до отмены `shutdown`
+    pub async fn serve(
+        self,
+        listener: TcpListener,
+        shutdown: CancellationToken,
+    ) -> Result<(), tonic::transport::Error> {
+        let address = listener.local_addr().ok();
+        info!(
+            "Mock Tinkoff API listening on {:?}: {} shares, {} candles, {} order books, {} faults",
+            address,
+            self.fixtures.shares.len(),
+            self.fixtures.candles.len(),
+            self.fixtures.order_books.len(),
+            self.fixtures.faults.len()
+        );
+
+        Server::builder()
+            .add_service(InstrumentsServiceServer::new(self.clone()))
+            .add_service(MarketDataServiceServer::new(self.clone()))
+            .add_service(MarketDataStreamServiceServer::new(self))
+            .serve_with_incoming_shutdown(
+                TcpListenerStream::new(listener),
+                shutdown.cancelled_owned(),
+            )
+            .await
+    }
+
+    /// Привязывает сервер к `address` и запускает его в фоне; возвращает фактический адрес
+    pub async fn spawn(
+        self,
+        address: SocketAddr,
+        shutdown: CancellationToken,
+    ) -> std::io::Result<SocketAddr> {
+        let listener = TcpListener::bind(address).await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move {
+            if let Err(e) = self.serve(listener, shutdown).await {
+                warn!("Mock Tinkoff API stopped: {}", e);
+            }
+        });
+        Ok(address)
+    }
+}
+
+/// Реализует сервис gRPC: перечисленные методы отвечают unimplemented
+macro_rules! mock_service {
+    (
+        $service:ident { $($implemented:tt)* }
+        unimplemented { $($method:ident($request:ident) -> $response:ident;)* }
+    ) => {
+        #[tonic::async_trait]
+        impl $service for MockTinkoff {
+            $($implemented)*
+
+            $(
+                async fn $method(
+                    &self,
+                    _request: Request<contract::$request>,
+                ) -> Result<Response<contract::$response>, Status> {
+                    Err(Status::unimplemented(concat!(
+                        stringify!($method),
+                        " is not served by the mock"
+                    )))
+                }
+            )*
+        }
+    };
+}
+
+mock_service! {
+    InstrumentsService {
+        async fn shares(
+            &self,
+            request: Request<InstrumentsRequest>,
+        ) -> Result<Response<SharesResponse>, Status> {
+            self.intercept("Shares", &request)?;
+            Ok(Response::new(SharesResponse {
+                instruments: self.fixtures.shares.iter().map(|share| share.to_share()).collect(),
+            }))
+        }
+
+        async fn share_by(
+            &self,
+            request: Request<InstrumentRequest>,
+        ) -> Result<Response<ShareResponse>, Status> {
+            self.intercept("ShareBy", &request)?;
+            self.serve_share_by(request.get_ref()).map(Response::new)
+        }
+    }
+    unimplemented {
+        trading_schedules(TradingSchedulesRequest) -> TradingSchedulesResponse;
+        bond_by(InstrumentRequest) -> BondResponse;
+        bonds(InstrumentsRequest) -> BondsResponse;
+        get_bond_coupons(GetBondCouponsRequest) -> GetBondCouponsResponse;
+        currency_by(InstrumentRequest) -> CurrencyResponse;
+        currencies(InstrumentsRequest) -> CurrenciesResponse;
+        etf_by(InstrumentRequest) -> EtfResponse;
+        etfs(InstrumentsRequest) -> EtfsResponse;
+        future_by(InstrumentRequest) -> FutureResponse;
+        futures(InstrumentsRequest) -> FuturesResponse;
+        option_by(InstrumentRequest) -> OptionResponse;
+        options(InstrumentsRequest) -> OptionsResponse;
+        options_by(FilterOptionsRequest) -> OptionsResponse;
+        get_accrued_interests(GetAccruedInterestsRequest) -> GetAccruedInterestsResponse;
+        get_futures_margin(GetFuturesMarginRequest) -> GetFuturesMarginResponse;
+        get_instrument_by(InstrumentRequest) -> InstrumentResponse;
+        get_dividends(GetDividendsRequest) -> GetDividendsResponse;
+        get_asset_by(AssetRequest) -> AssetResponse;
+        get_assets(AssetsRequest) -> AssetsResponse;
+        get_favorites(GetFavoritesRequest) -> GetFavoritesResponse;
+        edit_favorites(EditFavoritesRequest) -> EditFavoritesResponse;
+        get_countries(GetCountriesRequest) -> GetCountriesResponse;
+        find_instrument(FindInstrumentRequest) -> FindInstrumentResponse;
+        get_brands(GetBrandsRequest) -> GetBrandsResponse;
+        get_brand_by(GetBrandRequest) -> Brand;
+    }
+}
+
+mock_service! {
+    MarketDataService {
+        async fn get_candles(
+            &self,
+            request: Request<GetCandlesRequest>,
+        ) -> Result<Response<GetCandlesResponse>, Status> {
+            self.intercept("GetCandles", &request)?;
+            self.serve_candles(request.get_ref()).map(Response::new)
+        }
+    }
+    unimplemented {
+        get_last_prices(GetLastPricesRequest) -> GetLastPricesResponse;
+        get_order_book(GetOrderBookRequest) -> GetOrderBookResponse;
+        get_trading_status(GetTradingStatusRequest) -> GetTradingStatusResponse;
+        get_trading_statuses(GetTradingStatusesRequest) -> GetTradingStatusesResponse;
+        get_last_trades(GetLastTradesRequest) -> GetLastTradesResponse;
+        get_close_prices(GetClosePricesRequest) -> GetClosePricesResponse;
+    }
+}
+
+mock_service! {
+    MarketDataStreamService {
+        type MarketDataStreamStream = ReceiverStream<StreamResult>;
+        type MarketDataServerSideStreamStream = ReceiverStream<StreamResult>;
+
+        async fn market_data_stream(
+            &self,
+            request: Request<Streaming<MarketDataRequest>>,
+        ) -> Result<Response<Self::MarketDataStreamStream>, Status> {
+            self.intercept("MarketDataStream", &request)?;
+            Ok(Response::new(
+                self.spawn_order_book_stream(None, Some(request.into_inner())),
+            ))
+        }
+
+        async fn market_data_server_side_stream(
+            &self,
+            request: Request<MarketDataServerSideStreamRequest>,
+        ) -> Result<Response<Self::MarketDataServerSideStreamStream>, Status> {
+            self.intercept("MarketDataServerSideStream", &request)?;
+            let subscription = request.into_inner().subscribe_order_book_request;
+            Ok(Response::new(self.spawn_order_book_stream(subscription, None)))
+        }
+    }
+    unimplemented {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::tinkoff_public_invest_api_contract_v1::OrderBookInstrument;
+    use crate::services::tinkoff_client_grpc::TinkoffClient;
+    use crate::services::tinkoff_mock::{spawn_test_mock, test_api};
+
+    async fn start(fixtures: serde_json::Value) -> (MockTinkoff, TinkoffClient, CancellationToken) {
+        let (mock, base_url, shutdown) = spawn_test_mock(fixtures).await;
+        let client = TinkoffClient::connect(&test_api(base_url), vec!["secret".to_string()])
+            .await
+            .unwrap();
+        (mock, client, shutdown)
+    }
+
+    fn candles_request(from: i64, to: i64) -> GetCandlesRequest {
+        GetCandlesRequest {
+            from: Some(prost_types::Timestamp {
+                seconds: from,
+                nanos: 0,
+            }),
+            to: Some(prost_types::Timestamp {
+                seconds: to,
+                nanos: 0,
+            }),
+            instrument_id: "uid-1".to_string(),
+            interval: CandleInterval::CandleInterval1Min as i32,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_fixtures_and_injected_faults() {
+        let (mock, client, shutdown) = start(serde_json::json!({
+            "token": "secret",
+            "shares": [{ "uid": "uid-1", "figi": "FIGI1", "ticker": "AAA" }],
+            "candles": [
+                { "instrument_uid": "uid-1", "time": 120, "open": 1.5, "high": 2, "low": 1, "close": 1.75, "volume": 10 },
+                { "instrument_uid": "uid-1", "time": 60, "open": 1, "high": 1, "low": 1, "close": 1, "volume": 5 },
+                { "instrument_uid": "uid-1", "interval": "1day", "time": 0, "open": 1, "high": 2, "low": 1, "close": 1.75, "volume": 15 }
+            ],
+            "faults": [
+                { "method": "GetCandles", "code": "resource_exhausted", "after": 1, "times": 1, "rate_limit_reset_seconds": 7 }
+            ]
+        }))
+        .await;
+
+        let shares = client
+            .instruments
+            .clone()
+            .shares(
+                client
+                    .create_request(InstrumentsRequest::default())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(shares.instruments[0].ticker, "AAA");
+
+        let mut market_data = client.market_data.clone();
+        let candles = market_data
+            .get_candles(client.create_request(candles_request(0, 3600)).unwrap())
+            .await
+            .unwrap()
+            .into_inner()
+            .candles;
+        let times: Vec<i64> = candles.iter().map(|c| c.time.unwrap().seconds).collect();
+        assert_eq!(times, vec![60, 120]);
+        assert_eq!(candles[1].close.as_ref().unwrap().nano, 750_000_000);
+
+        let limited = market_data
+            .get_candles(client.create_request(candles_request(0, 3600)).unwrap())
+            .await
+            .unwrap_err();
+        assert_eq!(limited.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(limited.metadata().get("x-ratelimit-reset").unwrap(), "7");
+
+        let too_long = market_data
+            .get_candles(
+                client
+                    .create_request(candles_request(0, 2 * DAY_SECONDS))
+                    .unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(too_long.code(), tonic::Code::InvalidArgument);
+
+        let unauthenticated = market_data
+            .get_candles(Request::new(candles_request(0, 3600)))
+            .await
+            .unwrap_err();
+        assert_eq!(unauthenticated.code(), tonic::Code::Unauthenticated);
+        assert_eq!(mock.calls("GetCandles"), 4);
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test]
+    async fn streams_subscribed_order_books() {
+        let (_mock, client, shutdown) = start(serde_json::json!({
+            "stream_interval_ms": 10,
+            "stream_disconnect_after": 2,
+            "order_books": [{ "instrument_uid": "uid-1", "bids": [[10, 1], [9, 2]], "asks": [[11, 3]] }]
+        }))
+        .await;
+
+        let subscribe = |id: &str| MarketDataRequest {
+            payload: Some(market_data_request::Payload::SubscribeOrderBookRequest(
+                SubscribeOrderBookRequest {
+                    subscription_action: SubscriptionAction::Subscribe as i32,
+                    instruments: vec![OrderBookInstrument {
+                        instrument_id: id.to_string(),
+                        depth: 1,
+                        ..Default::default()
+                    }],
+                },
+            )),
+        };
+        // The stream stays open only while the request sender is alive
+        let (sender, receiver) = mpsc::channel(2);
+        sender.send(subscribe("uid-1")).await.unwrap();
+        sender.send(subscribe("missing")).await.unwrap();
+        let requests = ReceiverStream::new(receiver);
+        let mut stream = client
+            .market_data_stream
+            .clone()
+            .market_data_stream(client.create_request(requests).unwrap())
+            .await
+            .unwrap()
+            .into_inner();
+
+        let mut statuses = Vec::new();
+        let mut books = 0;
+        let error = loop {
+            match stream.message().await {
+                Ok(Some(response)) => match response.payload {
+                    Some(market_data_response::Payload::SubscribeOrderBookResponse(result)) => {
+                        statuses.push(result.order_book_subscriptions[0].subscription_status)
+                    }
+                    Some(market_data_response::Payload::Orderbook(book)) => {
+                        assert_eq!((book.bids.len(), book.asks.len()), (1, 1));
+                        books += 1;
+                    }
+                    _ => {}
+                },
+                Ok(None) => panic!("stream ended without the injected disconnect"),
+                Err(status) => break status,
+            }
+        };
+
+        assert_eq!(
+            statuses,
+            vec![
+                SubscriptionStatus::Success as i32,
+                SubscriptionStatus::InstrumentNotFound as i32
+            ]
+        );
+        assert_eq!(books, 2);
+        assert_eq!(error.code(), tonic::Code::Unavailable);
+        shutdown.cancel();
+    }
+}
@@ -0,0 +1,48 @@
+pub mod fixtures;
+pub mod mock_server;
+
+#[cfg(test)]
+use crate::env_config::models::app_config::{
+    TinkoffApiConfig, TokenSelection, TrafficConfig, TrafficMode,
+};
+#[cfg(test)]
+use tokio_util::sync::CancellationToken;
+
+/// Настройки клиента для тестов: открытый текст до `base_url`, без резервных адресов и записи трафика
+#[cfg(test)]
+pub fn test_api(base_url: String) -> TinkoffApiConfig {
+    TinkoffApiConfig {
+        base_url,
+        domain: "localhost".to_string(),
+        timeout: 5,
+        keepalive: 60,
+        fallback_urls: Vec::new(),
+        ca_cert: None,
+        health_check_seconds: 0,
+        token_selection: TokenSelection::RoundRobin,
+        traffic: TrafficConfig {
+            mode: TrafficMode::Off,
+            dir: String::new(),
+            methods: Vec::new(),
+        },
+    }
+}
+
+/// Запускает мок с фикстурами из JSON на свободном порту
+///
+/// Возвращает мок, его адрес для [`test_api`] и токен, останавливающий сервер
+#[cfg(test)]
+pub async fn spawn_test_mock(
+    fixtures: serde_json::Value,
+) -> (mock_server::MockTinkoff, String, CancellationToken) {
+    let fixtures: fixtures::MockFixtures = serde_json::from_value(fixtures).unwrap();
+    fixtures.validate().unwrap();
+    let mock = mock_server::MockTinkoff::new(fixtures);
+    let shutdown = CancellationToken::new();
+    let address = mock
+        .clone()
+        .spawn("127.0.0.1:0".parse().unwrap(), shutdown.clone())
+        .await
+        .unwrap();
+    (mock, format!("http://{}", address), shutdown)
+}
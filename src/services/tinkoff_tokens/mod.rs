@@ -0,0 +1,2 @@
+pub mod quota_channel;
+pub mod token_pool;
@@ -0,0 +1,115 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tonic::Code;
+use tonic::body::BoxBody;
+use tower_service::Service;
+
+use super::token_pool::{TokenPool, TokenSlot};
+use crate::services::tinkoff_traffic::traffic_channel::TrafficChannel;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type ResponseFuture =
+    Pin<Box<dyn Future<Output = Result<http::Response<BoxBody>, BoxError>> + Send>>;
+
+/// Канал, который учитывает ответы в квотах токена, выданного запросу
+///
+/// Статус берётся из заголовков ответа: так приходят ошибки без тела, в том числе
+/// UNAUTHENTICATED и RESOURCE_EXHAUSTED; ответы со статусом в трейлерах считаются успешными
+#[derive(Clone)]
+pub struct QuotaChannel {
+    inner: TrafficChannel,
+    tokens: Arc<TokenPool>,
+}
+
+impl QuotaChannel {
+    pub fn new(inner: TrafficChannel, tokens: Arc<TokenPool>) -> Self {
+        Self { inner, tokens }
+    }
+}
+
+impl Service<http::Request<BoxBody>> for QuotaChannel {
+    type Response = http::Response<BoxBody>;
+    type Error = BoxError;
+    type Future = ResponseFuture;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
+        let slot = request.extensions().get::<TokenSlot>().copied();
+        let method = request
+            .uri()
+            .path()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let tokens = self.tokens.clone();
+        let future = self.inner.call(request);
+
+        Box::pin(async move {
+            let result = future.await;
+            if let Some(slot) = slot {
+                match &result {
+                    Ok(response) => {
+                        let code = response
+                            .headers()
+                            .get("grpc-status")
+                            .and_then(|value| value.to_str().ok())
+                            .and_then(|value| value.parse::<i32>().ok())
+                            .map_or(Code::Ok, Code::from);
+                        tokens.record(slot, &method, response.headers(), code);
+                    }
+                    Err(_) => {
+                        tokens.record(slot, &method, &http::HeaderMap::new(), Code::Unavailable)
+                    }
+                }
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate::tinkoff_public_invest_api_contract_v1::InstrumentsRequest;
+    use crate::services::tinkoff_client_grpc::TinkoffClient;
+    use crate::services::tinkoff_mock::{spawn_test_mock, test_api};
+
+    #[tokio::test]
+    async fn disables_rejected_tokens() {
+        let (_, base_url, shutdown) = spawn_test_mock(serde_json::json!({
+            "token": "good",
+            "shares": [{ "uid": "uid-1", "figi": "FIGI1", "ticker": "AAA" }]
+        }))
+        .await;
+        let client = TinkoffClient::connect(
+            &test_api(base_url),
+            vec!["bad".to_string(), "good".to_string()],
+        )
+        .await
+        .unwrap();
+
+        let shares = |client: TinkoffClient| async move {
+            let request = client
+                .create_request(InstrumentsRequest::default())
+                .unwrap();
+            client.instruments.clone().shares(request).await
+        };
+        let rejected = shares(client.clone()).await.unwrap_err();
+        assert_eq!(rejected.code(), tonic::Code::Unauthenticated);
+        // The rejected token is skipped from now on
+        for _ in 0..3 {
+            assert!(shares(client.clone()).await.is_ok());
+        }
+
+        let usage = client.tokens.usage();
+        assert!(usage[0].disabled && !usage[1].disabled);
+        assert_eq!(usage[0].methods["Shares"].errors, 1);
+        assert_eq!(usage[1].methods["Shares"].requests, 3);
+        shutdown.cancel();
+    }
+}
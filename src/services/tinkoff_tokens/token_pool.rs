@@ -0,0 +1,307 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tonic::Code;
+use tracing::{error, warn};
+
+use crate::env_config::models::app_config::TokenSelection;
+
+/// Номер токена в пуле; кладётся в расширения запроса, чтобы учесть ответ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenSlot(pub usize);
+
+/// Квота одного метода для одного токена
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MethodQuota {
+    pub requests: u64,
+    pub errors: u64,        // Ответы с ошибкой gRPC или транспорта
+    pub rate_limited: u64,  // RESOURCE_EXHAUSTED
+    pub limit: Option<i64>, // Последние x-ratelimit-limit, x-ratelimit-remaining и x-ratelimit-reset
+    pub remaining: Option<i64>,
+    pub reset_at: Option<i64>, // Секунды UTC
+}
+
+/// Использование токена для метрик; сам токен не раскрывается
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenUsage {
+    pub label: String,
+    pub disabled: bool,
+    pub requests: u64,
+    pub methods: BTreeMap<String, MethodQuota>,
+}
+
+#[derive(Debug)]
+struct TokenState {
+    token: String,
+    disabled: bool,
+    requests: u64,
+    minute: i64, // Минута, к которой относится minute_requests
+    minute_requests: u64,
+    methods: BTreeMap<String, MethodQuota>,
+}
+
+impl TokenState {
+    /// Исчерпана ли квота какого-либо метода до момента сброса
+    fn is_exhausted(&self, now: i64) -> bool {
+        self.methods.values().any(|quota| {
+            quota.remaining == Some(0) && quota.reset_at.is_some_and(|reset_at| reset_at > now)
+        })
+    }
+}
+
+#[derive(Debug)]
+struct PoolState {
+    tokens: Vec<TokenState>,
+    next: usize, // Следующий токен для round_robin
+}
+
+fn header_number(headers: &http::HeaderMap, name: &str) -> Option<i64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Токены Tinkoff API с учётом квот по токену и методу
+///
+/// Лимиты Tinkoff считаются на токен, поэтому запросы распределяются между токенами;
+/// токен, получивший UNAUTHENTICATED, больше не выдаётся, а токен с исчерпанной
+/// квотой пропускается до её сброса, пока есть другие
+#[derive(Debug)]
+pub struct TokenPool {
+    selection: TokenSelection,
+    state: Mutex<PoolState>,
+}
+
+impl TokenPool {
+    pub fn new(tokens: Vec<String>, selection: TokenSelection) -> Self {
+        let mut unique: Vec<String> = Vec::new();
+        for token in tokens {
+            if !token.is_empty() && !unique.contains(&token) {
+                unique.push(token);
+            }
+        }
+        let tokens = unique
+            .into_iter()
+            .map(|token| TokenState {
+                token,
+                disabled: false,
+                requests: 0,
+                minute: 0,
+                minute_requests: 0,
+                methods: BTreeMap::new(),
+            })
+            .collect();
+        Self {
+            selection,
+            state: Mutex::new(PoolState { tokens, next: 0 }),
+        }
+    }
+
+    pub fn label(slot: usize) -> String {
+        format!("token-{}", slot + 1)
+    }
+
+    /// Выбирает токен для запроса: номер и значение; None — все токены отключены
+    pub fn acquire(&self) -> Option<(TokenSlot, String)> {
+        let mut state = self.state.lock().unwrap();
+        let now = Utc::now().timestamp();
+        let minute = now.div_euclid(60);
+        let count = state.tokens.len();
+        // When every token is exhausted the request still goes out and waits for the reset
+        let all_exhausted = state
+            .tokens
+            .iter()
+            .filter(|token| !token.disabled)
+            .all(|token| token.is_exhausted(now));
+        let enabled = |state: &PoolState, slot: usize| {
+            let token = &state.tokens[slot];
+            !token.disabled && (all_exhausted || !token.is_exhausted(now))
+        };
+
+        let slot = match self.selection {
+            TokenSelection::RoundRobin => {
+                let start = state.next;
+                let slot = (0..count)
+                    .map(|offset| (start + offset) % count)
+                    .find(|&slot| enabled(&state, slot))?;
+                state.next = (slot + 1) % count;
+                slot
+            }
+            TokenSelection::LeastUsed => (0..count)
+                .filter(|&slot| enabled(&state, slot))
+                .min_by_key(|&slot| {
+                    let token = &state.tokens[slot];
+                    if token.minute == minute {
+                        token.minute_requests
+                    } else {
+                        0
+                    }
+                })?,
+        };
+
+        let token = &mut state.tokens[slot];
+        if token.minute != minute {
+            token.minute = minute;
+            token.minute_requests = 0;
+        }
+        token.minute_requests += 1;
+        token.requests += 1;
+        Some((TokenSlot(slot), token.token.clone()))
+    }
+
+    /// Учитывает ответ на запрос с токеном `slot`
+    ///
+    /// `code` — статус gRPC, если он известен из заголовков ответа; ошибка транспорта
+    /// передаётся как `Code::Unavailable` без заголовков
+    pub fn record(&self, slot: TokenSlot, method: &str, headers: &http::HeaderMap, code: Code) {
+        let mut state = self.state.lock().unwrap();
+        let active = state.tokens.iter().filter(|t| !t.disabled).count();
+        let Some(token) = state.tokens.get_mut(slot.0) else {
+            return;
+        };
+
+        let quota = token.methods.entry(method.to_string()).or_default();
+        quota.requests += 1;
+        if code != Code::Ok {
+            quota.errors += 1;
+        }
+        if code == Code::ResourceExhausted {
+            quota.rate_limited += 1;
+        }
+        if let Some(limit) = header_number(headers, "x-ratelimit-limit") {
+            quota.limit = Some(limit);
+        }
+        if let Some(remaining) = header_number(headers, "x-ratelimit-remaining") {
+            quota.remaining = Some(remaining);
+        } else if code == Code::ResourceExhausted {
+            quota.remaining = Some(0);
+        }
+        if let Some(reset) = header_number(headers, "x-ratelimit-reset") {
+            quota.reset_at = Some(Utc::now().timestamp() + reset);
+        }
+
+        if code == Code::Unauthenticated && !token.disabled {
+            token.disabled = true;
+            if active > 1 {
+                warn!(
+                    "{} was rejected as unauthenticated and is disabled",
+                    Self::label(slot.0)
+                );
+            } else {
+                error!(
+                    "{} was rejected as unauthenticated; no Tinkoff tokens are left",
+                    Self::label(slot.0)
+                );
+            }
+        }
+    }
+
+    pub fn usage(&self) -> Vec<TokenUsage> {
+        let state = self.state.lock().unwrap();
+        state
+            .tokens
+            .iter()
+            .enumerate()
+            .map(|(slot, token)| TokenUsage {
+                label: Self::label(slot),
+                disabled: token.disabled,
+                requests: token.requests,
+                methods: token.methods.clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(selection: TokenSelection) -> TokenPool {
+        let tokens = ["a", "b", "c", "a", ""]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        TokenPool::new(tokens, selection)
+    }
+
+    #[test]
+    fn rotates_and_disables_tokens() {
+        let pool = pool(TokenSelection::RoundRobin);
+        let tokens: Vec<String> = (0..4).map(|_| pool.acquire().unwrap().1).collect();
+        assert_eq!(tokens, vec!["a", "b", "c", "a"]);
+
+        let mut headers = http::HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", "0".parse().unwrap());
+        pool.record(
+            TokenSlot(1),
+            "GetCandles",
+            &headers,
+            Code::ResourceExhausted,
+        );
+        pool.record(
+            TokenSlot(1),
+            "GetCandles",
+            &http::HeaderMap::new(),
+            Code::Unauthenticated,
+        );
+        let tokens: Vec<String> = (0..3).map(|_| pool.acquire().unwrap().1).collect();
+        assert_eq!(tokens, vec!["c", "a", "c"]);
+
+        let usage = pool.usage();
+        assert!(usage[1].disabled);
+        let quota = &usage[1].methods["GetCandles"];
+        assert_eq!(
+            (quota.requests, quota.errors, quota.rate_limited),
+            (2, 2, 1)
+        );
+        assert_eq!(quota.remaining, Some(0));
+
+        pool.record(
+            TokenSlot(0),
+            "Shares",
+            &http::HeaderMap::new(),
+            Code::Unauthenticated,
+        );
+        pool.record(
+            TokenSlot(2),
+            "Shares",
+            &http::HeaderMap::new(),
+            Code::Unauthenticated,
+        );
+        assert!(pool.acquire().is_none());
+    }
+
+    #[test]
+    fn prefers_least_used_token() {
+        let pool = pool(TokenSelection::LeastUsed);
+        let tokens: Vec<String> = (0..6).map(|_| pool.acquire().unwrap().1).collect();
+        assert_eq!(tokens, vec!["a", "b", "c", "a", "b", "c"]);
+    }
+
+    #[test]
+    fn skips_exhausted_tokens_until_reset() {
+        let exhausted = |reset: &str| {
+            let mut headers = http::HeaderMap::new();
+            headers.insert("x-ratelimit-remaining", "0".parse().unwrap());
+            headers.insert("x-ratelimit-reset", reset.parse().unwrap());
+            headers
+        };
+
+        for selection in [TokenSelection::LeastUsed, TokenSelection::RoundRobin] {
+            let pool = pool(selection);
+            pool.record(TokenSlot(0), "GetCandles", &exhausted("60"), Code::Ok);
+            let tokens: Vec<String> = (0..4).map(|_| pool.acquire().unwrap().1).collect();
+            assert_eq!(tokens, vec!["b", "c", "b", "c"]);
+
+            // A quota that has already been reset no longer holds the token back
+            pool.record(TokenSlot(0), "GetCandles", &exhausted("-1"), Code::Ok);
+            assert_eq!(pool.acquire().unwrap().1, "a");
+        }
+
+        // With every token exhausted requests still go out
+        let pool = pool(TokenSelection::LeastUsed);
+        for slot in 0..3 {
+            pool.record(TokenSlot(slot), "GetCandles", &exhausted("60"), Code::Ok);
+        }
+        assert!(pool.acquire().is_some());
+    }
+}
@@ -0,0 +1,2 @@
+pub mod traffic_channel;
+pub mod traffic_store;
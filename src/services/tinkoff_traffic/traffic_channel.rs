@@ -0,0 +1,291 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use bytes::Bytes;
+use chrono::Utc;
+use http_body::Frame;
+use http_body_util::{BodyExt, Full, StreamBody};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tonic::Status;
+use tonic::body::BoxBody;
+use tower_service::Service;
+use tracing::{debug, warn};
+
+use super::traffic_store::{RecordedResponse, TrafficStore, headers_to_map, map_to_headers};
+use crate::env_config::models::app_config::TrafficMode;
+use crate::services::tinkoff_channel::resilient_channel::ResilientChannel;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type ResponseFuture =
+    Pin<Box<dyn Future<Output = Result<http::Response<BoxBody>, BoxError>> + Send>>;
+
+/// Канал клиентов Tinkoff: сеть, сеть с записью ответов или воспроизведение записей
+///
+/// Записываются и воспроизводятся только методы из `tinkoff_api.traffic.methods`;
+/// остальные при записи идут в сеть как есть, а при воспроизведении получают unavailable
+#[derive(Clone)]
+pub struct TrafficChannel {
+    channel: Option<ResilientChannel>, // None при воспроизведении
+    store: Option<Arc<TrafficStore>>,
+    mode: TrafficMode,
+}
+
+impl TrafficChannel {
+    pub fn network(channel: ResilientChannel) -> Self {
+        Self {
+            channel: Some(channel),
+            store: None,
+            mode: TrafficMode::Off,
+        }
+    }
+
+    pub fn record(channel: ResilientChannel, store: Arc<TrafficStore>) -> Self {
+        Self {
+            channel: Some(channel),
+            store: Some(store),
+            mode: TrafficMode::Record,
+        }
+    }
+
+    pub fn replay(store: Arc<TrafficStore>) -> Self {
+        Self {
+            channel: None,
+            store: Some(store),
+            mode: TrafficMode::Replay,
+        }
+    }
+
+    /// Забирает готовый к вызову канал, оставляя на его месте клон
+    fn take_ready(&mut self) -> Option<ResilientChannel> {
+        let channel = self.channel.as_mut()?;
+        let clone = channel.clone();
+        Some(std::mem::replace(channel, clone))
+    }
+}
+
+impl Service<http::Request<BoxBody>> for TrafficChannel {
+    type Response = http::Response<BoxBody>;
+    type Error = BoxError;
+    type Future = ResponseFuture;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.channel.as_mut() {
+            Some(channel) => channel.poll_ready(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
+        let store = self.store.clone();
+        let method = store
+            .as_ref()
+            .and_then(|store| store.method_name(request.uri().path()))
+            .map(str::to_string);
+
+        match (self.mode, store, method) {
+            (TrafficMode::Replay, Some(store), Some(method)) => {
+                Box::pin(async move { replay(&store, &method, request).await })
+            }
+            (TrafficMode::Replay, _, _) => {
+                let path = request.uri().path().to_string();
+                Box::pin(async move {
+                    Ok(Status::unavailable(format!("{} is not replayed", path)).into_http())
+                })
+            }
+            (TrafficMode::Record, Some(store), Some(method)) => {
+                let channel = self
+                    .take_ready()
+                    .expect("recording requires a network channel");
+                Box::pin(async move { record(channel, &store, &method, request).await })
+            }
+            _ => {
+                let future = match self.channel.as_mut() {
+                    Some(channel) => channel.call(request),
+                    None => unreachable!("network channel is missing outside replay mode"),
+                };
+                Box::pin(future)
+            }
+        }
+    }
+}
+
+/// Тело ответа из сохранённых данных и трейлеров
+fn response_body(body: Bytes, trailers: Option<http::HeaderMap>) -> BoxBody {
+    let mut frames: Vec<Result<Frame<Bytes>, Status>> = vec![Ok(Frame::data(body))];
+    if let Some(trailers) = trailers {
+        frames.push(Ok(Frame::trailers(trailers)));
+    }
+    tonic::body::boxed(StreamBody::new(tokio_stream::iter(frames)))
+}
+
+async fn record(
+    mut channel: ResilientChannel,
+    store: &TrafficStore,
+    method: &str,
+    request: http::Request<BoxBody>,
+) -> Result<http::Response<BoxBody>, BoxError> {
+    let (parts, body) = request.into_parts();
+    let request_body = body.collect().await?.to_bytes();
+    let path = parts.uri.path().to_string();
+    let request_headers = parts.headers.clone();
+
+    let request =
+        http::Request::from_parts(parts, tonic::body::boxed(Full::new(request_body.clone())));
+    let (parts, body) = channel.call(request).await?.into_parts();
+    let collected = body.collect().await?;
+    let trailers = collected.trailers().cloned();
+    let response_bytes = collected.to_bytes();
+
+    let recorded = RecordedResponse {
+        recorded_at: Utc::now().timestamp(),
+        status: parts.status.as_u16(),
+        headers: headers_to_map(&parts.headers),
+        body: STANDARD.encode(&response_bytes),
+        trailers: trailers.as_ref().map(headers_to_map),
+    };
+    match store.save(method, &path, &request_body, &request_headers, recorded) {
+        Ok(()) => debug!("Recorded {} response", method),
+        Err(e) => warn!("Failed to record {} response: {}", method, e),
+    }
+
+    Ok(http::Response::from_parts(
+        parts,
+        response_body(response_bytes, trailers),
+    ))
+}
+
+async fn replay(
+    store: &TrafficStore,
+    method: &str,
+    request: http::Request<BoxBody>,
+) -> Result<http::Response<BoxBody>, BoxError> {
+    let request_body = request.into_body().collect().await?.to_bytes();
+    let Some(recorded) = store.replay(method, &request_body) else {
+        return Ok(
+            Status::failed_precondition(format!("{} request is not recorded", method)).into_http(),
+        );
+    };
+
+    let mut response = http::Response::builder().status(recorded.status);
+    if let Some(headers) = response.headers_mut() {
+        *headers = map_to_headers(&recorded.headers);
+    }
+    let body = STANDARD.decode(&recorded.body)?;
+    let trailers = recorded.trailers.as_ref().map(map_to_headers);
+    Ok(response.body(response_body(Bytes::from(body), trailers))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env_config::models::app_config::{TinkoffApiConfig, TrafficConfig, TrafficMode};
+    use crate::generate::tinkoff_public_invest_api_contract_v1::{
+        CandleInterval, GetCandlesRequest, InstrumentsRequest,
+    };
+    use crate::services::tinkoff_client_grpc::TinkoffClient;
+    use crate::services::tinkoff_mock::{spawn_test_mock, test_api};
+
+    fn api(base_url: String, mode: TrafficMode, dir: &std::path::Path) -> TinkoffApiConfig {
+        TinkoffApiConfig {
+            traffic: TrafficConfig {
+                mode,
+                dir: dir.to_string_lossy().to_string(),
+                methods: vec!["GetCandles".to_string(), "Shares".to_string()],
+            },
+            ..test_api(base_url)
+        }
+    }
+
+    async fn get_candles(client: &TinkoffClient, from: i64) -> Result<usize, tonic::Status> {
+        let request = GetCandlesRequest {
+            from: Some(prost_types::Timestamp {
+                seconds: from,
+                nanos: 0,
+            }),
+            to: Some(prost_types::Timestamp {
+                seconds: 3600,
+                nanos: 0,
+            }),
+            instrument_id: "uid-1".to_string(),
+            interval: CandleInterval::CandleInterval1Min as i32,
+            ..Default::default()
+        };
+        let response = client
+            .market_data
+            .clone()
+            .get_candles(client.create_request(request).unwrap())
+            .await?;
+        Ok(response.into_inner().candles.len())
+    }
+
+    #[tokio::test]
+    async fn records_and_replays_responses() {
+        let dir = std::env::temp_dir().join(format!("t-candles-traffic-{}", uuid::Uuid::new_v4()));
+        let (_, base_url, shutdown) = spawn_test_mock(serde_json::json!({
+            "shares": [{ "uid": "uid-1", "figi": "FIGI1", "ticker": "AAA" }],
+            "candles": [{ "instrument_uid": "uid-1", "time": 60, "open": 1, "high": 1, "low": 1, "close": 1, "volume": 5 }],
+            "faults": [{ "method": "GetCandles", "code": "resource_exhausted", "times": 1, "rate_limit_reset_seconds": 3 }]
+        }))
+        .await;
+
+        let recorder = TinkoffClient::connect(
+            &api(base_url, TrafficMode::Record, &dir),
+            vec!["secret".to_string()],
+        )
+        .await
+        .unwrap();
+        assert!(get_candles(&recorder, 0).await.is_err());
+        assert_eq!(get_candles(&recorder, 0).await.unwrap(), 1);
+        let shares = recorder
+            .instruments
+            .clone()
+            .shares(
+                recorder
+                    .create_request(InstrumentsRequest::default())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(shares.into_inner().instruments.len(), 1);
+        shutdown.cancel();
+
+        let recording = std::fs::read_dir(dir.join("GetCandles"))
+            .unwrap()
+            .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect::<String>();
+        assert!(recording.contains("Bearer [REDACTED]"));
+        assert!(!recording.contains("secret"));
+
+        // Replay needs no server: the same requests get the same answers in order
+        let replayer = TinkoffClient::connect(
+            &api("http://127.0.0.1:1".to_string(), TrafficMode::Replay, &dir),
+            vec!["another-token".to_string()],
+        )
+        .await
+        .unwrap();
+        let limited = get_candles(&replayer, 0).await.unwrap_err();
+        assert_eq!(limited.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(limited.metadata().get("x-ratelimit-reset").unwrap(), "3");
+        assert_eq!(get_candles(&replayer, 0).await.unwrap(), 1);
+        assert_eq!(get_candles(&replayer, 0).await.unwrap(), 1);
+        assert_eq!(
+            get_candles(&replayer, 60).await.unwrap_err().code(),
+            tonic::Code::FailedPrecondition
+        );
+        let shares = replayer
+            .instruments
+            .clone()
+            .shares(
+                replayer
+                    .create_request(InstrumentsRequest::default())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(shares.into_inner().instruments[0].ticker, "AAA");
+        assert!((replayer.now() - chrono::Utc::now().timestamp()).abs() < 60);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,183 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use bytes::Bytes;
+use chrono::Utc;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::env_config::models::app_config::TrafficConfig;
+use crate::generate::tinkoff_public_invest_api_contract_v1::{
+    GetCandlesRequest, InstrumentsRequest,
+};
+
+const SESSION_FILE: &str = "session.json";
+const REDACTED: &str = "Bearer [REDACTED]";
+
+/// Ответ на записанный запрос
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub recorded_at: i64,
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: String, // Тело gRPC в base64
+    pub trailers: Option<BTreeMap<String, String>>,
+}
+
+/// Запрос и ответы на него в порядке получения
+///
+/// Повторы одного запроса (например, после rate limit) дописываются в `responses`,
+/// воспроизведение отдаёт их по очереди, а последний — на все следующие повторы
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub method: String,
+    pub request: String, // Тело gRPC в base64
+    pub request_debug: Option<String>,
+    pub request_headers: BTreeMap<String, String>, // authorization заменён
+    pub responses: Vec<RecordedResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Session {
+    recorded_at: i64, // Время начала записи; часы воспроизведения
+}
+
+pub fn headers_to_map(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            let value = if name.as_str() == "authorization" {
+                REDACTED
+            } else {
+                value.to_str().ok()?
+            };
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+pub fn map_to_headers(map: &BTreeMap<String, String>) -> HeaderMap {
+    map.iter()
+        .filter_map(|(name, value)| {
+            Some((
+                HeaderName::from_bytes(name.as_bytes()).ok()?,
+                HeaderValue::from_str(value).ok()?,
+            ))
+        })
+        .collect()
+}
+
+/// Файлы записанного трафика: `<dir>/<метод>/<sha256 тела запроса>.json`
+pub struct TrafficStore {
+    dir: PathBuf,
+    methods: Vec<String>,
+    replay_cursors: Mutex<HashMap<PathBuf, usize>>,
+    save_lock: Mutex<()>, // Повторы одного запроса дописываются в тот же файл
+}
+
+impl TrafficStore {
+    pub fn new(config: &TrafficConfig) -> Self {
+        Self {
+            dir: PathBuf::from(&config.dir),
+            methods: config.methods.clone(),
+            replay_cursors: Mutex::new(HashMap::new()),
+            save_lock: Mutex::new(()),
+        }
+    }
+
+    /// Короткое имя метода, если его трафик записывается: /package.Service/GetCandles -> GetCandles
+    pub fn method_name<'a>(&self, path: &'a str) -> Option<&'a str> {
+        let name = path.rsplit('/').next()?;
+        self.methods.iter().any(|m| m == name).then_some(name)
+    }
+
+    fn call_path(&self, method: &str, request: &[u8]) -> PathBuf {
+        let hash = format!("{:x}", Sha256::digest(request));
+        self.dir.join(method).join(format!("{}.json", hash))
+    }
+
+    /// Время начала записи; при воспроизведении заменяет текущее время
+    pub fn recorded_at(&self) -> Option<i64> {
+        let content = std::fs::read_to_string(self.dir.join(SESSION_FILE)).ok()?;
+        serde_json::from_str::<Session>(&content)
+            .ok()
+            .map(|session| session.recorded_at)
+    }
+
+    /// Отмечает начало записи, если в каталоге её ещё нет
+    pub fn start_session(&self) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(SESSION_FILE);
+        if path.exists() {
+            return Ok(());
+        }
+        let session = Session {
+            recorded_at: Utc::now().timestamp(),
+        };
+        std::fs::write(path, serde_json::to_vec_pretty(&session)?)
+    }
+
+    pub fn save(
+        &self,
+        method: &str,
+        path: &str,
+        request: &Bytes,
+        request_headers: &HeaderMap,
+        response: RecordedResponse,
+    ) -> std::io::Result<()> {
+        let file = self.call_path(method, request);
+        let _guard = self.save_lock.lock().unwrap();
+        let mut call = match read_call(&file) {
+            Some(call) => call,
+            None => RecordedCall {
+                method: path.to_string(),
+                request: STANDARD.encode(request),
+                request_debug: describe_request(method, request),
+                request_headers: headers_to_map(request_headers),
+                responses: Vec::new(),
+            },
+        };
+        call.responses.push(response);
+
+        std::fs::create_dir_all(file.parent().unwrap_or(&self.dir))?;
+        std::fs::write(&file, serde_json::to_vec_pretty(&call)?)
+    }
+
+    /// Следующий записанный ответ на запрос; None, если запрос не записывался
+    pub fn replay(&self, method: &str, request: &Bytes) -> Option<RecordedResponse> {
+        let file = self.call_path(method, request);
+        let call = read_call(&file)?;
+        let mut cursors = self.replay_cursors.lock().unwrap();
+        let cursor = cursors.entry(file).or_insert(0);
+        let response = call
+            .responses
+            .get(*cursor)
+            .or_else(|| call.responses.last())?
+            .clone();
+        *cursor += 1;
+        Some(response)
+    }
+}
+
+fn read_call(file: &Path) -> Option<RecordedCall> {
+    let content = std::fs::read(file).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+/// Читаемое описание запроса для записи; тело gRPC начинается с 5 байт заголовка кадра
+fn describe_request(method: &str, request: &Bytes) -> Option<String> {
+    let message = request.get(5..)?;
+    match method {
+        "GetCandles" => GetCandlesRequest::decode(message)
+            .ok()
+            .map(|r| format!("{:?}", r)),
+        "Shares" => InstrumentsRequest::decode(message)
+            .ok()
+            .map(|r| format!("{:?}", r)),
+        _ => None,
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use tracing::debug;
+
+/// Отправка уведомлений во внешний webhook в виде JSON
+///
+/// Если URL не задан, уведомления молча пропускаются
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: Option<String>,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: Option<String>, timeout_seconds: u64) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(timeout_seconds))
+            .build()
+            .expect("Failed to build webhook HTTP client");
+
+        Self {
+            client,
+            url: url.filter(|u| !u.is_empty()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.url.is_some()
+    }
+
+    /// POST `payload` на webhook; ответ с кодом ошибки считается неудачей
+    pub async fn send<T: Serialize + ?Sized>(&self, payload: &T) -> Result<(), reqwest::Error> {
+        let Some(url) = &self.url else {
+            return Ok(());
+        };
+
+        self.client
+            .post(url)
+            .json(payload)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        debug!("Webhook notification delivered to {}", url);
+        Ok(())
+    }
+}